@@ -13,6 +13,7 @@ use typed_builder::TypedBuilder;
 )]
 pub struct RichText {
     pub text: String,
+    #[serde(default)]
     pub formatting: Formatting,
 }
 
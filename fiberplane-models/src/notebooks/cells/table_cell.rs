@@ -46,10 +46,12 @@ pub struct TableCell {
     /// Describes the types used for the columns and the order they should be
     /// rendered in.
     #[builder(default, setter(into))]
+    #[serde(default)]
     pub column_defs: Vec<TableColumnDefinition>,
 
     /// Holds the table rows and their values.
     #[builder(default, setter(into))]
+    #[serde(default)]
     pub rows: Vec<TableRow>,
 }
 
@@ -191,6 +193,7 @@ pub struct TableRow {
     /// The types, order, and amount of the values should match the table's
     /// [column definitions](TableCell::column_defs).
     #[builder(setter(into))]
+    #[serde(default)]
     pub values: Vec<TableRowValue>,
 }
 
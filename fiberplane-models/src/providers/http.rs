@@ -5,6 +5,7 @@ use fp_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fmt::{self, Debug, Formatter};
+use strum_macros::IntoStaticStr;
 use typed_builder::TypedBuilder;
 
 /// HTTP request options.
@@ -136,8 +137,9 @@ impl Debug for HttpRequestError {
 
 /// HTTP request method.
 // Note: we use SCREAMING_SNAKE_CASE here because this is
-// effectively a constant
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+// effectively a constant. `IntoStaticStr` converts variants into their method
+// name, such as `"GET"`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, IntoStaticStr, PartialEq, Serialize)]
 #[cfg_attr(
     feature = "fp-bindgen",
     derive(Serializable),
@@ -145,6 +147,7 @@ impl Debug for HttpRequestError {
 )]
 #[non_exhaustive]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 pub enum HttpRequestMethod {
     Delete,
     #[default]
@@ -2,7 +2,9 @@ mod auto_suggest;
 mod constants;
 mod data;
 mod error;
+mod fixtures;
 mod http;
+mod logging;
 mod request;
 mod schema;
 mod status;
@@ -11,7 +13,9 @@ pub use auto_suggest::*;
 pub use constants::*;
 pub use data::*;
 pub use error::*;
+pub use fixtures::*;
 pub use http::*;
+pub use logging::*;
 pub use request::*;
 pub use schema::*;
 pub use status::*;
@@ -38,4 +38,14 @@ pub struct ProviderRequest {
     #[builder(default, setter(into, strip_option))]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub previous_response: Option<Blob>,
+
+    /// Whether the host accepts partial results for this request.
+    ///
+    /// If `true`, the provider may call `emit_partial()` to send parts of the
+    /// response as they become available, in which case the result returned
+    /// by `invoke2()` only contains the remainder of the response. Otherwise,
+    /// the provider needs to return the entire response.
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub accepts_partial_results: bool,
 }
@@ -0,0 +1,90 @@
+//! Host functions for running providers natively, outside of a Wasm runtime.
+//!
+//! When a provider is compiled for a target other than `wasm32`, the
+//! functions it imports from the host are forwarded to the [Host] that is
+//! installed on the current thread. This allows providers to be tested
+//! natively, for instance using the `fiberplane-provider-mock-host` crate.
+
+use crate::types::*;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+/// Future that resolves with the response to an HTTP request.
+pub type HttpResponseFuture = Pin<Box<dyn Future<Output = Result<HttpResponse, HttpRequestError>>>>;
+
+/// Implementation of the functions that providers import from the host.
+pub trait Host {
+    /// Emits a part of the response to the current `invoke2()` call.
+    fn emit_partial(&self, result: Blob);
+
+    /// Logs a message to the (development) console.
+    ///
+    /// By default, this logs the message with the `Info` level and without
+    /// any fields.
+    fn log(&self, message: String) {
+        self.log_structured(LogLevel::Info, message, BTreeMap::new())
+    }
+
+    /// Logs a message with the given level and fields.
+    fn log_structured(&self, level: LogLevel, message: String, fields: BTreeMap<String, String>);
+
+    /// Performs an HTTP request.
+    fn make_http_request(&self, request: HttpRequest) -> HttpResponseFuture;
+
+    /// Returns the current timestamp.
+    fn now(&self) -> Timestamp;
+
+    /// Generates random bytes.
+    fn random(&self, len: u32) -> Vec<u8>;
+}
+
+thread_local! {
+    static HOST: RefCell<Option<Rc<dyn Host>>> = RefCell::new(None);
+}
+
+/// Installs the host for the current thread, and returns the host that was
+/// installed before, if any.
+///
+/// Passing `None` uninstalls the current host.
+pub fn set_host(host: Option<Rc<dyn Host>>) -> Option<Rc<dyn Host>> {
+    HOST.with(|current| current.replace(host))
+}
+
+fn current_host() -> Rc<dyn Host> {
+    HOST.with(|current| current.borrow().clone())
+        .expect("No host is installed on this thread, see `fiberplane_provider_bindings::host`")
+}
+
+/// Emits a part of the response to the current `invoke2()` call.
+pub fn emit_partial(result: Blob) {
+    current_host().emit_partial(result)
+}
+
+/// Logs a message to the (development) console.
+pub fn log(message: String) {
+    current_host().log(message)
+}
+
+/// Logs a message with the given level and fields.
+pub fn log_structured(level: LogLevel, message: String, fields: BTreeMap<String, String>) {
+    current_host().log_structured(level, message, fields)
+}
+
+/// Performs an HTTP request.
+pub async fn make_http_request(request: HttpRequest) -> Result<HttpResponse, HttpRequestError> {
+    let response = current_host().make_http_request(request);
+    response.await
+}
+
+/// Returns the current timestamp.
+pub fn now() -> Timestamp {
+    current_host().now()
+}
+
+/// Generates random bytes.
+pub fn random(len: u32) -> Vec<u8> {
+    current_host().random(len)
+}
@@ -1,9 +1,32 @@
 use crate::types::*;
+use std::collections::BTreeMap;
+
+/// Emits a part of the response to the current `invoke2()` call, so the
+/// host can display it before the provider returns.
+///
+/// Partial results need to have the same MIME type as the final result,
+/// and are displayed in the order in which they are emitted. Providers may
+/// only emit partial results if the `accepts_partial_results` field of the
+/// request is set. Partial results that are emitted otherwise are
+/// discarded.
+#[fp_bindgen_support::fp_import_signature]
+pub fn emit_partial(result: Blob);
 
 /// Logs a message to the (development) console.
+///
+/// This is equivalent to calling `log_structured()` with the `Info` level
+/// and without any fields.
 #[fp_bindgen_support::fp_import_signature]
 pub fn log(message: String);
 
+/// Logs a message with the given level and fields.
+///
+/// The host emits the message together with the name of the provider and
+/// the ID of the invocation during which it was logged, so it can be
+/// filtered and correlated.
+#[fp_bindgen_support::fp_import_signature]
+pub fn log_structured(level: LogLevel, message: String, fields: BTreeMap<String, String>);
+
 /// Performs an HTTP request.
 #[fp_bindgen_support::fp_import_signature]
 pub async fn make_http_request(request: HttpRequest) -> Result<HttpResponse, HttpRequestError>;
@@ -1,12 +1,19 @@
 #![allow(unused_imports)]
 #[rustfmt::skip]
 mod export;
+#[cfg(target_arch = "wasm32")]
 #[rustfmt::skip]
 mod import;
 #[rustfmt::skip]
 mod types;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod host;
+
 pub use export::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use host::{emit_partial, log, log_structured, make_http_request, now, random};
+#[cfg(target_arch = "wasm32")]
 pub use import::*;
 pub use types::*;
 
@@ -33,6 +33,7 @@ pub use fiberplane_models::providers::LabelField;
 pub use fiberplane_models::notebooks::ListItemCell;
 pub use fiberplane_models::notebooks::ListType;
 pub use fiberplane_models::notebooks::LogCell;
+pub use fiberplane_models::providers::LogLevel;
 pub use fiberplane_models::notebooks::LogRecordIndex;
 pub use fiberplane_models::notebooks::LogVisibilityFilter;
 pub use fiberplane_models::formatting::Mention;
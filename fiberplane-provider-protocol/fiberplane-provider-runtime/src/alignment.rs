@@ -0,0 +1,57 @@
+//! Works around a misaligned instance context in Wasmer 2.3.
+//!
+//! The context of an instance starts with the IDs of the signatures the module
+//! uses, which are four bytes each, followed by the imported functions, which
+//! need to be aligned to eight bytes. Wasmer does not pad the IDs, so for
+//! modules with an odd number of signatures every field after them is
+//! misaligned. Instantiating such a module is undefined behavior, which debug
+//! builds abort on.
+
+use loupe::MemoryUsage;
+use wasmer::{FunctionMiddleware, FunctionType, LocalFunctionIndex, ModuleMiddleware};
+use wasmer_types::ModuleInfo;
+
+/// Middleware that adds an unused signature to modules with an odd number of
+/// signatures, so that the fields after the signature IDs are aligned.
+#[derive(Debug, Default, MemoryUsage)]
+pub(crate) struct AlignSignatures;
+
+impl ModuleMiddleware for AlignSignatures {
+    fn generate_function_middleware(&self, _: LocalFunctionIndex) -> Box<dyn FunctionMiddleware> {
+        Box::new(FunctionAlignSignatures)
+    }
+
+    fn transform_module_info(&self, module_info: &mut ModuleInfo) {
+        if module_info.signatures.len() % 2 == 1 {
+            module_info.signatures.push(FunctionType::new([], []));
+        }
+    }
+}
+
+#[derive(Debug)]
+struct FunctionAlignSignatures;
+
+impl FunctionMiddleware for FunctionAlignSignatures {}
+
+#[cfg(test)]
+mod tests {
+    use crate::spec::Runtime;
+
+    #[test]
+    fn test_odd_number_of_signatures() {
+        // Instantiating this module aborts debug builds unless its signatures
+        // are padded.
+        let runtime = Runtime::new(
+            r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "__fp_free") (param i64))
+                (func (export "__fp_guest_resolve_async_value") (param i64 i64))
+                (func (export "__fp_malloc") (param i32) (result i64)
+                    (i64.const 0)))
+            "#,
+        )
+        .unwrap();
+        assert_eq!(runtime.instance.module().info().signatures.len(), 4);
+    }
+}
@@ -0,0 +1,290 @@
+//! Checks whether providers are compatible with the runtime.
+
+use crate::errors::RuntimeError;
+use crate::limits::{Limiter, RuntimeLimits};
+use crate::logging::ProviderLogger;
+use crate::partials::PartialResults;
+use crate::policy::HttpClient;
+use crate::spec::{create_import_object, HostEnv, Runtime};
+use fp_bindgen_support::wasmer2_host::runtime::RuntimeInstanceData;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use wasmer::{Export, ExternType, FunctionType, ImportObject, Module, Type};
+
+/// Prefix of the names under which the functions of the provider protocol
+/// are exported.
+const PROTOCOL_FUNCTION_PREFIX: &str = "__fp_gen_";
+
+/// Exports the runtime uses for passing values to and from every provider.
+const REQUIRED_FUNCTIONS: [(&str, &[Type], &[Type]); 3] = [
+    ("__fp_free", &[Type::I64], &[]),
+    (
+        "__fp_guest_resolve_async_value",
+        &[Type::I64, Type::I64],
+        &[],
+    ),
+    ("__fp_malloc", &[Type::I32], &[Type::I64]),
+];
+
+/// Information about a provider that is compatible with the runtime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Compatibility {
+    /// Version of the provider protocol that is implemented by the provider.
+    ///
+    /// The version is derived from the invoke function that is exported:
+    /// version 2 providers export `invoke2()`, while version 1 providers
+    /// export `invoke()`. `None` if the provider exports neither.
+    pub protocol_version: Option<u32>,
+
+    /// Names of the protocol functions that are exported by the provider.
+    pub exported_functions: BTreeSet<String>,
+}
+
+impl Runtime {
+    /// Checks whether the provider is compatible with the runtime, without
+    /// instantiating it.
+    ///
+    /// Returns the same error as [Runtime::new()] would for incompatible
+    /// providers.
+    pub fn check_compatibility(
+        wasm_module: impl AsRef<[u8]>,
+    ) -> Result<Compatibility, RuntimeError> {
+        let limiter = Arc::new(Limiter::new(RuntimeLimits::default()));
+        let store = Self::default_store(&limiter);
+        let module = Module::new(&store, wasm_module)?;
+        let host_env = HostEnv::new(
+            RuntimeInstanceData::default(),
+            Arc::new(HttpClient::default()),
+            limiter,
+            Arc::new(ProviderLogger::default()),
+            Arc::new(PartialResults::default()),
+            Arc::default(),
+        );
+        let import_object = create_import_object(&store, &host_env);
+        check_module(&module, &import_object)
+    }
+}
+
+/// Checks whether the imports of the module are provided by the import
+/// object, and whether the module has the exports the runtime requires.
+pub(crate) fn check_module(
+    module: &Module,
+    import_object: &ImportObject,
+) -> Result<Compatibility, RuntimeError> {
+    for import in module.imports() {
+        let export = import_object
+            .get_export(import.module(), import.name())
+            .ok_or_else(|| RuntimeError::UnknownImport {
+                module: import.module().to_owned(),
+                name: import.name().to_owned(),
+            })?;
+
+        // The runtime only provides functions.
+        let Export::Function(function) = export else {
+            continue;
+        };
+
+        let expected = ExternType::Function(function.vm_function.signature);
+        if import.ty() != &expected {
+            return Err(RuntimeError::IncompatibleImport {
+                module: import.module().to_owned(),
+                name: import.name().to_owned(),
+                expected: describe_type(&expected),
+                actual: describe_type(import.ty()),
+            });
+        }
+    }
+
+    let exports: Vec<_> = module.exports().collect();
+    let find_export = |name: &str| {
+        exports
+            .iter()
+            .find(|export| export.name() == name)
+            .ok_or_else(|| RuntimeError::MissingExport {
+                name: name.to_owned(),
+            })
+    };
+
+    let memory = find_export("memory")?;
+    if !matches!(memory.ty(), ExternType::Memory(_)) {
+        return Err(RuntimeError::IncompatibleExport {
+            name: memory.name().to_owned(),
+            expected: "memory".to_owned(),
+            actual: describe_type(memory.ty()),
+        });
+    }
+
+    for (name, params, results) in REQUIRED_FUNCTIONS {
+        let export = find_export(name)?;
+        let expected = ExternType::Function(FunctionType::new(params, results));
+        if export.ty() != &expected {
+            return Err(RuntimeError::IncompatibleExport {
+                name: name.to_owned(),
+                expected: describe_type(&expected),
+                actual: describe_type(export.ty()),
+            });
+        }
+    }
+
+    let exported_functions: BTreeSet<String> = exports
+        .iter()
+        .filter(|export| matches!(export.ty(), ExternType::Function(_)))
+        .filter_map(|export| export.name().strip_prefix(PROTOCOL_FUNCTION_PREFIX))
+        .map(ToOwned::to_owned)
+        .collect();
+
+    let protocol_version = if exported_functions.contains("invoke2") {
+        Some(2)
+    } else if exported_functions.contains("invoke") {
+        Some(1)
+    } else {
+        None
+    };
+
+    Ok(Compatibility {
+        protocol_version,
+        exported_functions,
+    })
+}
+
+fn describe_type(ty: &ExternType) -> String {
+    match ty {
+        ExternType::Function(function) => format!("function {function}"),
+        ExternType::Global(_) => "global".to_owned(),
+        ExternType::Table(_) => "table".to_owned(),
+        ExternType::Memory(_) => "memory".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a provider with the given imports and exports, besides the
+    /// exports that every provider needs.
+    fn provider(imports: &str, exports: &str) -> String {
+        format!(
+            r#"
+            (module
+                {imports}
+                (memory (export "memory") 1)
+                (func (export "__fp_free") (param i64))
+                (func (export "__fp_guest_resolve_async_value") (param i64 i64))
+                {exports})
+            "#
+        )
+    }
+
+    const MALLOC: &str = r#"(func (export "__fp_malloc") (param i32) (result i64) (i64.const 0))"#;
+
+    #[test]
+    fn test_compatible_provider() {
+        let compatibility = Runtime::check_compatibility(provider(
+            r#"(import "fp" "__fp_gen_log" (func (param i64)))"#,
+            &format!(
+                r#"{MALLOC}
+                (func (export "__fp_gen_get_config_schema") (result i64) (i64.const 0))
+                (func (export "__fp_gen_invoke2") (param i64) (result i64) (i64.const 0))"#
+            ),
+        ))
+        .unwrap();
+        assert_eq!(
+            compatibility,
+            Compatibility {
+                protocol_version: Some(2),
+                exported_functions: BTreeSet::from([
+                    "get_config_schema".to_owned(),
+                    "invoke2".to_owned()
+                ]),
+            }
+        );
+
+        let compatibility = Runtime::check_compatibility(provider(
+            "",
+            &format!(
+                r#"{MALLOC}
+                (func (export "__fp_gen_invoke") (param i64) (result i64) (i64.const 0))"#
+            ),
+        ))
+        .unwrap();
+        assert_eq!(compatibility.protocol_version, Some(1));
+
+        let compatibility = Runtime::check_compatibility(provider("", MALLOC)).unwrap();
+        assert_eq!(compatibility.protocol_version, None);
+        assert!(compatibility.exported_functions.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_import() {
+        let result = Runtime::check_compatibility(provider(
+            r#"(import "fp" "__fp_gen_unknown" (func))"#,
+            MALLOC,
+        ));
+        assert!(
+            matches!(
+                &result,
+                Err(RuntimeError::UnknownImport { module, name })
+                    if module == "fp" && name == "__fp_gen_unknown"
+            ),
+            "unexpected result: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_incompatible_import() {
+        let result = Runtime::check_compatibility(provider(
+            r#"(import "fp" "__fp_gen_log" (func (param i32)))"#,
+            MALLOC,
+        ));
+        assert!(
+            matches!(
+                &result,
+                Err(RuntimeError::IncompatibleImport { module, name, expected, actual })
+                    if module == "fp"
+                        && name == "__fp_gen_log"
+                        && expected == "function [I64] -> []"
+                        && actual == "function [I32] -> []"
+            ),
+            "unexpected result: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_missing_export() {
+        let result = Runtime::check_compatibility(provider("", ""));
+        assert!(
+            matches!(&result, Err(RuntimeError::MissingExport { name }) if name == "__fp_malloc"),
+            "unexpected result: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_incompatible_export() {
+        let result = Runtime::check_compatibility(provider(
+            "",
+            r#"(func (export "__fp_malloc") (param i64) (result i64) (i64.const 0))"#,
+        ));
+        assert!(
+            matches!(
+                &result,
+                Err(RuntimeError::IncompatibleExport { name, expected, actual })
+                    if name == "__fp_malloc"
+                        && expected == "function [I32] -> [I64]"
+                        && actual == "function [I64] -> [I64]"
+            ),
+            "unexpected result: {result:?}"
+        );
+
+        let result = Runtime::check_compatibility(
+            r#"(module (global (export "memory") i32 (i32.const 0)))"#,
+        );
+        assert!(
+            matches!(
+                &result,
+                Err(RuntimeError::IncompatibleExport { name, expected, actual })
+                    if name == "memory" && expected == "memory" && actual == "global"
+            ),
+            "unexpected result: {result:?}"
+        );
+    }
+}
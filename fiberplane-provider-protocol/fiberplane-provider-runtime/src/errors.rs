@@ -0,0 +1,93 @@
+use fp_bindgen_support::wasmer2_host::errors as bindgen_errors;
+use thiserror::Error;
+
+/// Error that occurs when a provider cannot be loaded into a runtime.
+#[derive(Debug, Error)]
+pub enum RuntimeError {
+    #[error(transparent)]
+    CompileError(#[from] wasmer::CompileError),
+
+    #[error("compiled module could not be loaded: {0}")]
+    DeserializeError(#[from] wasmer::DeserializeError),
+
+    #[error("compiled module could not be serialized: {0}")]
+    SerializeError(#[from] wasmer::SerializeError),
+
+    #[error("provider imports `{module}.{name}`, which is not provided by the runtime")]
+    UnknownImport { module: String, name: String },
+
+    #[error("provider imports `{module}.{name}` as {actual}, but the runtime provides {expected}")]
+    IncompatibleImport {
+        module: String,
+        name: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("provider does not export `{name}`")]
+    MissingExport { name: String },
+
+    #[error("provider exports `{name}` as {actual}, but the runtime expects {expected}")]
+    IncompatibleExport {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("provider could not be instantiated: {0}")]
+    InstantiationError(Box<wasmer::InstantiationError>),
+}
+
+impl From<wasmer::InstantiationError> for RuntimeError {
+    fn from(error: wasmer::InstantiationError) -> Self {
+        Self::InstantiationError(Box::new(error))
+    }
+}
+
+/// Error that occurs when a function of a provider cannot be invoked, or when
+/// the invocation fails before the provider returns a result.
+///
+/// The [FuelExhausted](Self::FuelExhausted),
+/// [MemoryLimitExceeded](Self::MemoryLimitExceeded),
+/// [DeadlineExceeded](Self::DeadlineExceeded) and [Cancelled](Self::Cancelled)
+/// variants indicate the provider was stopped because of the
+/// [RuntimeLimits](crate::limits::RuntimeLimits) of its runtime. The state of
+/// the provider is unspecified after such an error, so the runtime should be
+/// discarded.
+#[derive(Debug, Error)]
+pub enum InvocationError {
+    #[error("expected function was not exported: {0}")]
+    FunctionNotExported(String),
+
+    #[error("returned data did not match expected type")]
+    UnexpectedReturnType,
+
+    #[error("provider ran out of fuel (limit: {limit})")]
+    FuelExhausted { limit: u64 },
+
+    #[error("provider exceeded its memory limit of {limit} bytes")]
+    MemoryLimitExceeded { limit: usize },
+
+    #[error("provider did not complete before its deadline")]
+    DeadlineExceeded,
+
+    #[error("invocation was cancelled")]
+    Cancelled,
+
+    #[error(transparent)]
+    WasmerRuntimeError(#[from] wasmer::RuntimeError),
+}
+
+impl From<bindgen_errors::InvocationError> for InvocationError {
+    fn from(error: bindgen_errors::InvocationError) -> Self {
+        match error {
+            bindgen_errors::InvocationError::FunctionNotExported(name) => {
+                Self::FunctionNotExported(name)
+            }
+            bindgen_errors::InvocationError::UnexpectedReturnType => Self::UnexpectedReturnType,
+            bindgen_errors::InvocationError::WasmerRuntimeError(error) => {
+                Self::WasmerRuntimeError(error)
+            }
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! Factory for creating runtimes for the same provider, without compiling the
+//! provider every time.
+
+use crate::errors::RuntimeError;
+use crate::limits::{Limiter, RuntimeLimits};
+use crate::policy::{HostPolicy, HttpClient};
+use crate::recording::HttpRecording;
+use crate::spec::Runtime;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+use wasmer::{Engine, Module, Store, Target};
+
+/// Options for creating a [RuntimeFactory].
+#[derive(Clone, Debug, Default)]
+pub struct RuntimeFactoryOptions {
+    /// Policy for the HTTP requests made by the runtimes.
+    ///
+    /// All runtimes created by the factory share a single HTTP client, so the
+    /// maximum number of concurrent requests applies to them combined.
+    pub policy: HostPolicy,
+
+    /// Limits that apply to every runtime created by the factory.
+    pub limits: RuntimeLimits,
+
+    /// Directory in which compiled modules are cached.
+    ///
+    /// Cached modules are loaded as native code, so this directory should
+    /// only be writable by the host.
+    pub cache_dir: Option<PathBuf>,
+
+    /// Number of runtimes that are kept instantiated, ready to be handed out.
+    pub pool_size: usize,
+
+    /// Name of the provider, with which the messages it logs are tagged.
+    pub provider_name: Option<String>,
+
+    /// Recording to which the HTTP requests of all runtimes created by the
+    /// factory are written, or from which they are replayed.
+    pub recording: Option<HttpRecording>,
+}
+
+/// Creates runtimes for a provider that is compiled only once.
+///
+/// The compiled module is optionally cached on disk, keyed by a hash of the
+/// provider, so that it can be reused across restarts of the host.
+pub struct RuntimeFactory {
+    artifact: Vec<u8>,
+    http_client: Arc<HttpClient>,
+    limits: RuntimeLimits,
+    pool: Mutex<Vec<Runtime>>,
+    pool_size: usize,
+    provider_name: Option<String>,
+    recording: Option<HttpRecording>,
+}
+
+impl RuntimeFactory {
+    /// Creates a factory for the given provider, and fills its pool.
+    pub fn new(
+        wasm_module: impl AsRef<[u8]>,
+        options: RuntimeFactoryOptions,
+    ) -> Result<Self, RuntimeError> {
+        let wasm_module = wasm_module.as_ref();
+        let mut factory = Self {
+            artifact: Vec::new(),
+            http_client: Arc::new(HttpClient::new(options.policy)),
+            limits: options.limits,
+            pool: Mutex::new(Vec::with_capacity(options.pool_size)),
+            pool_size: options.pool_size,
+            provider_name: options.provider_name,
+            recording: options.recording,
+        };
+
+        let cache_path = options
+            .cache_dir
+            .map(|cache_dir| cache_dir.join(cache_file_name(wasm_module, &factory.limits)));
+
+        // Cached modules are verified by instantiating the first runtime.
+        let mut runtime = None;
+        if let Some(artifact) = cache_path.as_deref().and_then(read_cached_artifact) {
+            factory.artifact = artifact;
+            match factory.instantiate() {
+                Ok(cached_runtime) => runtime = Some(cached_runtime),
+                Err(RuntimeError::DeserializeError(error)) => {
+                    warn!(%error, "ignoring cached module that could not be loaded");
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        let runtime = match runtime {
+            Some(runtime) => runtime,
+            None => {
+                factory.artifact = compile(wasm_module, &factory.limits)?;
+                if let Some(cache_path) = &cache_path {
+                    write_cached_artifact(cache_path, &factory.artifact);
+                }
+                factory.instantiate()?
+            }
+        };
+
+        factory.release(runtime);
+        factory.fill_pool()?;
+        Ok(factory)
+    }
+
+    /// Returns a runtime from the pool, or instantiates a new one if the pool
+    /// is empty.
+    ///
+    /// Runtimes can be returned to the pool using [Self::release()].
+    pub fn acquire(&self) -> Result<Runtime, RuntimeError> {
+        match self.pool.lock().unwrap().pop() {
+            Some(runtime) => Ok(runtime),
+            None => self.instantiate(),
+        }
+    }
+
+    /// Returns a runtime to the pool, so it can be handed out again.
+    ///
+    /// The runtime is dropped instead if the pool is full, or if any of its
+    /// invocations were stopped because of its limits.
+    pub fn release(&self, runtime: Runtime) {
+        if runtime.has_stopped() {
+            return;
+        }
+
+        let mut pool = self.pool.lock().unwrap();
+        if pool.len() < self.pool_size {
+            pool.push(runtime);
+        }
+    }
+
+    /// Instantiates runtimes until the pool is full.
+    pub fn fill_pool(&self) -> Result<(), RuntimeError> {
+        while self.pool.lock().unwrap().len() < self.pool_size {
+            let runtime = self.instantiate()?;
+            self.release(runtime);
+        }
+        Ok(())
+    }
+
+    fn instantiate(&self) -> Result<Runtime, RuntimeError> {
+        // SAFETY: The artifact was either compiled by us or read from the
+        // cache directory, which is trusted.
+        let runtime = unsafe {
+            Runtime::from_artifact(
+                &self.artifact,
+                self.http_client.clone(),
+                self.limits.clone(),
+            )?
+        };
+        if let Some(provider_name) = &self.provider_name {
+            runtime.set_provider_name(provider_name);
+        }
+        if let Some(recording) = &self.recording {
+            runtime.set_recording(recording.clone());
+        }
+        Ok(runtime)
+    }
+}
+
+impl Runtime {
+    /// Creates a runtime from a module that was compiled with
+    /// [Runtime::default_store()] and serialized.
+    ///
+    /// # Safety
+    ///
+    /// The artifact is loaded as native code, so it must come from a trusted
+    /// source.
+    pub(crate) unsafe fn from_artifact(
+        artifact: &[u8],
+        http_client: Arc<HttpClient>,
+        limits: RuntimeLimits,
+    ) -> Result<Self, RuntimeError> {
+        let limiter = Arc::new(Limiter::new(limits));
+        let engine = wasmer::Universal::headless().engine();
+        let store = Store::new_with_tunables(&engine, limiter.tunables(engine.target()));
+        let module = Module::deserialize(&store, artifact)?;
+        Self::instantiate(&module, http_client, limiter)
+    }
+}
+
+fn compile(wasm_module: &[u8], limits: &RuntimeLimits) -> Result<Vec<u8>, RuntimeError> {
+    let store = Runtime::default_store(&Limiter::new(limits.clone()));
+    let module = Module::new(&store, wasm_module)?;
+    Ok(module.serialize()?)
+}
+
+/// Version of the cached modules, which is bumped whenever the runtime changes
+/// the way in which it compiles modules.
+const CACHE_VERSION: u32 = 1;
+
+/// Returns the name of the file in which the compiled module is cached.
+///
+/// Besides the module itself, the hash covers everything that determines the
+/// compiled code: the versions of the cache, the runtime and Wasmer, the
+/// compiler, the target and its CPU features, and whether the module is
+/// metered. Cached modules are loaded without any validation, so a module that
+/// was compiled differently must never end up under the same name.
+fn cache_file_name(wasm_module: &[u8], limits: &RuntimeLimits) -> String {
+    // This is the target for which `Runtime::default_store()` compiles.
+    let target = Target::default();
+
+    let mut hasher = Sha256::new();
+    hasher.update(CACHE_VERSION.to_le_bytes());
+    hasher.update(env!("CARGO_PKG_VERSION"));
+    hasher.update(wasmer::VERSION);
+    hasher.update(Runtime::COMPILER);
+    hasher.update(target.triple().to_string());
+    for cpu_feature in target.cpu_features().iter() {
+        hasher.update(cpu_feature.to_string());
+    }
+    hasher.update([limits.fuel.is_some() as u8]);
+    hasher.update(wasm_module);
+    format!("{}.bin", hex::encode(hasher.finalize()))
+}
+
+fn read_cached_artifact(path: &Path) -> Option<Vec<u8>> {
+    match fs::read(path) {
+        Ok(artifact) => {
+            debug!(?path, "loaded module from cache");
+            Some(artifact)
+        }
+        Err(error) => {
+            debug!(?path, %error, "module not found in cache");
+            None
+        }
+    }
+}
+
+/// Writes the compiled module to the cache.
+///
+/// The module is written to a temporary file first, so that concurrent hosts
+/// never read a partially written module. Failures are logged, since the
+/// cache is only an optimization.
+fn write_cached_artifact(path: &Path, artifact: &[u8]) {
+    let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&temp_path, artifact))
+        .and_then(|_| fs::rename(&temp_path, path));
+
+    if let Err(error) = result {
+        warn!(?path, %error, "could not write module to cache");
+        let _ = fs::remove_file(&temp_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROVIDER: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (func (export "__fp_free") (param i64))
+            (func (export "__fp_guest_resolve_async_value") (param i64 i64))
+            (func (export "__fp_malloc") (param i32) (result i64)
+                (i64.const 0)))
+    "#;
+
+    fn cached_files(cache_dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<_> = fs::read_dir(cache_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_cache() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let options = RuntimeFactoryOptions {
+            cache_dir: Some(cache_dir.path().to_owned()),
+            ..Default::default()
+        };
+
+        // The first factory compiles the module and caches it.
+        let factory = RuntimeFactory::new(PROVIDER, options.clone()).unwrap();
+        let files = cached_files(cache_dir.path());
+        assert_eq!(files.len(), 1);
+        assert_eq!(fs::read(&files[0]).unwrap(), factory.artifact);
+
+        // Later factories load the cached module instead of compiling it.
+        let other_provider = PROVIDER.replace(
+            r#"(memory (export "memory") 1)"#,
+            r#"(memory (export "memory") 2)"#,
+        );
+        let other_artifact = compile(other_provider.as_bytes(), &RuntimeLimits::default()).unwrap();
+        fs::write(&files[0], &other_artifact).unwrap();
+        let factory = RuntimeFactory::new(PROVIDER, options.clone()).unwrap();
+        assert_eq!(factory.artifact, other_artifact);
+
+        // Cached modules that cannot be loaded are replaced.
+        fs::write(&files[0], b"invalid").unwrap();
+        let factory = RuntimeFactory::new(PROVIDER, options.clone()).unwrap();
+        assert_ne!(factory.artifact, b"invalid");
+        assert_eq!(fs::read(&files[0]).unwrap(), factory.artifact);
+        assert_eq!(cached_files(cache_dir.path()), files);
+
+        // Metered modules are compiled differently, so they are cached
+        // separately.
+        let metered_options = RuntimeFactoryOptions {
+            limits: RuntimeLimits {
+                fuel: Some(1_000),
+                ..Default::default()
+            },
+            ..options
+        };
+        RuntimeFactory::new(PROVIDER, metered_options).unwrap();
+        assert_eq!(cached_files(cache_dir.path()).len(), 2);
+    }
+
+    #[test]
+    fn test_pool() {
+        let factory = RuntimeFactory::new(
+            PROVIDER,
+            RuntimeFactoryOptions {
+                pool_size: 1,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // Released runtimes are handed out again.
+        let runtime = factory.acquire().unwrap();
+        let limiter = runtime.limiter.clone();
+        factory.release(runtime);
+        let runtime = factory.acquire().unwrap();
+        assert!(Arc::ptr_eq(&runtime.limiter, &limiter));
+
+        // Runtimes are instantiated when the pool is empty.
+        let other_runtime = factory.acquire().unwrap();
+        assert!(!Arc::ptr_eq(&other_runtime.limiter, &limiter));
+
+        // Runtimes are dropped when the pool is full.
+        factory.release(runtime);
+        factory.release(other_runtime);
+        assert_eq!(factory.pool.lock().unwrap().len(), 1);
+
+        // Stopped runtimes are dropped.
+        let runtime = factory.acquire().unwrap();
+        runtime.cancellation_token().cancel();
+        factory.release(runtime);
+        assert!(factory.pool.lock().unwrap().is_empty());
+    }
+}
@@ -10,4 +10,13 @@ This is the runtime used by [FPD](https://github.com/fiberplane/fpd).
 
 */
 
+mod alignment;
+pub mod compatibility;
+pub mod errors;
+pub mod factory;
+pub mod limits;
+pub mod logging;
+mod partials;
+pub mod policy;
+pub mod recording;
 pub mod spec;
@@ -0,0 +1,736 @@
+//! Limits on the resources that providers can use.
+
+use crate::errors::{InvocationError, RuntimeError};
+use crate::policy::{HostPolicy, HttpClient};
+use crate::spec::Runtime;
+use loupe::{MemoryUsage, MemoryUsageTracker};
+use std::collections::HashMap;
+use std::future::{pending, Future};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::{sleep_until, Instant};
+use tracing::debug;
+use wasmer::vm::{Memory, MemoryError, MemoryStyle, Table, TableStyle};
+use wasmer::vm::{VMMemoryDefinition, VMTableDefinition};
+use wasmer::wasmparser::Operator;
+use wasmer::{
+    BaseTunables, Global, HostEnvInitError, MemoryType, Module, ModuleMiddleware, Pages, TableType,
+    Target, Tunables, Value, WASM_MAX_PAGES, WASM_PAGE_SIZE,
+};
+use wasmer_middlewares::metering::Metering;
+
+pub use tokio_util::sync::CancellationToken;
+
+/// ID of the next invocation, shared by all runtimes so that IDs are unique
+/// within the process. Zero is used to indicate the absence of an invocation.
+static NEXT_INVOCATION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Limits that apply to the invocations of a provider.
+///
+/// By default, no limits are applied.
+#[derive(Clone, Debug, Default)]
+pub struct RuntimeLimits {
+    /// Amount of fuel that is available to a single invocation.
+    ///
+    /// Every WebAssembly instruction that is executed by the provider consumes
+    /// one unit of fuel. This includes the instructions that are executed when
+    /// the provider resumes after an async value has been resolved. Every
+    /// invocation gets its own supply of fuel, even when invocations of the
+    /// same runtime run concurrently.
+    ///
+    /// Unlike the timeout, this stops providers that never yield back to the
+    /// runtime, such as a provider stuck in an infinite loop.
+    pub fuel: Option<u64>,
+
+    /// Maximum size of the memory of the provider, in bytes.
+    ///
+    /// The limit is rounded down to a multiple of the WebAssembly page size
+    /// (64KiB).
+    pub max_memory: Option<usize>,
+
+    /// Maximum duration of a single invocation.
+    ///
+    /// The deadline is only enforced while the runtime waits for the result of
+    /// an async function. Use `fuel` to limit the time a provider can spend
+    /// executing synchronously.
+    pub timeout: Option<Duration>,
+}
+
+impl Runtime {
+    /// Creates a runtime of which the invocations are stopped when the
+    /// provider exceeds any of the given limits.
+    pub fn with_limits(
+        wasm_module: impl AsRef<[u8]>,
+        policy: HostPolicy,
+        limits: RuntimeLimits,
+    ) -> Result<Self, RuntimeError> {
+        let limiter = Arc::new(Limiter::new(limits));
+        let store = Self::default_store(&limiter);
+        let module = Module::new(&store, wasm_module)?;
+        Self::instantiate(&module, Arc::new(HttpClient::new(policy)), limiter)
+    }
+
+    /// Returns the token with which all invocations of this runtime can be
+    /// cancelled.
+    ///
+    /// Once the token is cancelled, pending and future invocations fail with
+    /// [InvocationError::Cancelled].
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.limiter.cancellation_token().clone()
+    }
+
+    /// Returns whether an invocation of this runtime was stopped because of
+    /// its limits, after which the runtime should be discarded.
+    pub fn has_stopped(&self) -> bool {
+        self.limiter.has_stopped()
+    }
+}
+
+/// Enforces the [RuntimeLimits] of a single runtime, and keeps track of its
+/// invocations.
+///
+/// A runtime can execute multiple invocations concurrently: while one
+/// invocation waits for an async value, such as the response to an HTTP
+/// request, the provider can be invoked again. Every time the runtime calls
+/// into the provider, it [enters](Self::enter()) the invocation on behalf of
+/// which it does so, which determines the fuel that is available to the
+/// provider and the invocation to which its host calls are attributed.
+pub(crate) struct Limiter {
+    limits: RuntimeLimits,
+    fuel_globals: OnceLock<FuelGlobals>,
+    invocations: Mutex<HashMap<u64, InvocationState>>,
+    current_invocation_id: AtomicU64,
+    memory_limit_reached: Arc<AtomicBool>,
+    stopped: AtomicBool,
+    cancellation_token: CancellationToken,
+}
+
+/// Globals in which the metering middleware keeps track of the fuel of the
+/// provider.
+struct FuelGlobals {
+    remaining_points: Global,
+    points_exhausted: Global,
+}
+
+/// State of an invocation that is shared with the calls that resume the
+/// provider on its behalf.
+struct InvocationState {
+    remaining_fuel: u64,
+    guest_error: Option<oneshot::Sender<InvocationError>>,
+}
+
+/// An invocation that is in progress.
+///
+/// The invocation is in progress until this is dropped.
+pub(crate) struct Invocation<'a> {
+    id: u64,
+    deadline: Option<Instant>,
+    guest_error: oneshot::Receiver<InvocationError>,
+    limiter: &'a Limiter,
+}
+
+impl<'a> Invocation<'a> {
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Enters the invocation, before calling into the provider on its behalf.
+    pub(crate) fn enter(&self) -> Entered<'a> {
+        self.limiter.enter(Some(self.id))
+    }
+}
+
+impl Drop for Invocation<'_> {
+    fn drop(&mut self) {
+        self.limiter.invocations.lock().unwrap().remove(&self.id);
+    }
+}
+
+/// Guard that is held while the runtime calls into the provider on behalf of
+/// an invocation.
+///
+/// Dropping the guard stores the fuel that remains for the invocation.
+pub(crate) struct Entered<'a> {
+    invocation_id: Option<u64>,
+    previous_invocation_id: u64,
+    limiter: &'a Limiter,
+}
+
+impl Entered<'_> {
+    /// Converts an error that was raised by the provider, attributing it to
+    /// one of the limits if the provider exceeded it.
+    pub(crate) fn to_invocation_error(&self, error: wasmer::RuntimeError) -> InvocationError {
+        let limiter = self.limiter;
+        if let (Some(limit), Some(fuel_globals)) = (limiter.limits.fuel, limiter.fuel_globals.get())
+        {
+            if fuel_globals.is_exhausted() {
+                return limiter.stop(InvocationError::FuelExhausted { limit });
+            }
+        }
+
+        // Providers typically abort when they fail to allocate memory, so the
+        // resulting trap does not tell us why they failed.
+        if let Some(limit) = limiter.limits.max_memory {
+            if limiter.memory_limit_reached.load(Ordering::SeqCst) {
+                return limiter.stop(InvocationError::MemoryLimitExceeded { limit });
+            }
+        }
+
+        InvocationError::WasmerRuntimeError(error)
+    }
+}
+
+impl Drop for Entered<'_> {
+    fn drop(&mut self) {
+        let limiter = self.limiter;
+        if let (Some(fuel), Some(fuel_globals)) = (limiter.limits.fuel, limiter.fuel_globals.get())
+        {
+            if let Some(id) = self.invocation_id {
+                if let Some(state) = limiter.invocations.lock().unwrap().get_mut(&id) {
+                    state.remaining_fuel = fuel_globals.remaining();
+                }
+            }
+
+            // Calls into the provider outside of an invocation, such as those
+            // that free the results of async functions, get a fresh supply.
+            fuel_globals.set_remaining(fuel);
+        }
+
+        limiter
+            .current_invocation_id
+            .store(self.previous_invocation_id, Ordering::SeqCst);
+    }
+}
+
+impl FuelGlobals {
+    fn is_exhausted(&self) -> bool {
+        self.points_exhausted.get().unwrap_i32() > 0
+    }
+
+    fn remaining(&self) -> u64 {
+        if self.is_exhausted() {
+            0
+        } else {
+            self.remaining_points.get().unwrap_i64() as u64
+        }
+    }
+
+    fn set_remaining(&self, fuel: u64) {
+        self.remaining_points
+            .set(Value::I64(fuel as i64))
+            .expect("Can't set remaining fuel");
+        self.points_exhausted
+            .set(Value::I32(0))
+            .expect("Can't reset exhausted fuel");
+    }
+}
+
+impl Limiter {
+    pub(crate) fn new(limits: RuntimeLimits) -> Self {
+        Self {
+            limits,
+            fuel_globals: OnceLock::new(),
+            invocations: Mutex::new(HashMap::new()),
+            current_invocation_id: AtomicU64::new(0),
+            memory_limit_reached: Arc::new(AtomicBool::new(false)),
+            stopped: AtomicBool::new(false),
+            cancellation_token: CancellationToken::new(),
+        }
+    }
+
+    /// Looks up the globals of the metering middleware, if the provider has a
+    /// fuel limit.
+    ///
+    /// The globals are referenced weakly, so that the limiter, which is part
+    /// of the environment of the instance, does not keep the instance alive.
+    pub(crate) fn init_with_instance(
+        &self,
+        instance: &wasmer::Instance,
+    ) -> Result<(), HostEnvInitError> {
+        if self.limits.fuel.is_none() {
+            return Ok(());
+        }
+
+        let fuel_globals = FuelGlobals {
+            remaining_points: instance
+                .exports
+                .get_with_generics_weak("wasmer_metering_remaining_points")?,
+            points_exhausted: instance
+                .exports
+                .get_with_generics_weak("wasmer_metering_points_exhausted")?,
+        };
+        let _ = self.fuel_globals.set(fuel_globals);
+        Ok(())
+    }
+
+    pub(crate) fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
+
+    /// Returns the ID of the invocation on behalf of which the provider is
+    /// executing, if any.
+    ///
+    /// Everything the provider does while it executes is attributed to this
+    /// invocation. Note that providers which run their own executor may make
+    /// progress on other invocations at the same time.
+    pub(crate) fn current_invocation_id(&self) -> Option<u64> {
+        match self.current_invocation_id.load(Ordering::SeqCst) {
+            0 => None,
+            id => Some(id),
+        }
+    }
+
+    /// Returns whether an invocation was stopped because of the limits, after
+    /// which the provider should no longer be used.
+    pub(crate) fn has_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst) || self.cancellation_token.is_cancelled()
+    }
+
+    /// Returns the middleware that meters the fuel consumption of the
+    /// provider, if it has a fuel limit.
+    pub(crate) fn metering(&self) -> Option<Arc<dyn ModuleMiddleware>> {
+        self.limits
+            .fuel
+            .map(|fuel| Arc::new(Metering::new(fuel, operator_cost)) as Arc<dyn ModuleMiddleware>)
+    }
+
+    /// Returns the tunables that enforce the memory limit of the provider.
+    pub(crate) fn tunables(&self, target: &Target) -> LimitingTunables {
+        let max_pages = self.limits.max_memory.map(|max_memory| {
+            Pages((max_memory / WASM_PAGE_SIZE).min(WASM_MAX_PAGES as usize) as u32)
+        });
+
+        LimitingTunables {
+            base: BaseTunables::for_target(target),
+            max_pages,
+            limit_reached: self.memory_limit_reached.clone(),
+        }
+    }
+
+    /// Starts a new invocation, with its own supply of fuel and its own
+    /// deadline.
+    ///
+    /// The returned invocation must be held on to until the invocation
+    /// completes, and must be [entered](Invocation::enter()) whenever the
+    /// runtime calls into the provider on its behalf.
+    pub(crate) fn start_invocation(&self) -> Result<Invocation<'_>, InvocationError> {
+        if self.cancellation_token.is_cancelled() {
+            return Err(InvocationError::Cancelled);
+        }
+
+        let invocation_id = NEXT_INVOCATION_ID.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = oneshot::channel();
+        self.invocations.lock().unwrap().insert(
+            invocation_id,
+            InvocationState {
+                remaining_fuel: self.limits.fuel.unwrap_or_default(),
+                guest_error: Some(sender),
+            },
+        );
+
+        Ok(Invocation {
+            id: invocation_id,
+            deadline: self.limits.timeout.map(|timeout| Instant::now() + timeout),
+            guest_error: receiver,
+            limiter: self,
+        })
+    }
+
+    /// Enters the invocation with the given ID, before calling into the
+    /// provider on its behalf.
+    ///
+    /// If the invocation is no longer in progress, the provider gets a fresh
+    /// supply of fuel, and nothing it does is attributed to an invocation.
+    pub(crate) fn enter(&self, invocation_id: Option<u64>) -> Entered<'_> {
+        let invocation_id =
+            invocation_id.filter(|id| self.invocations.lock().unwrap().contains_key(id));
+
+        if let (Some(fuel), Some(fuel_globals)) = (self.limits.fuel, self.fuel_globals.get()) {
+            let remaining_fuel = invocation_id
+                .and_then(|id| {
+                    let invocations = self.invocations.lock().unwrap();
+                    invocations.get(&id).map(|state| state.remaining_fuel)
+                })
+                .unwrap_or(fuel);
+            fuel_globals.set_remaining(remaining_fuel);
+        }
+        self.memory_limit_reached.store(false, Ordering::SeqCst);
+
+        let previous_invocation_id = self
+            .current_invocation_id
+            .swap(invocation_id.unwrap_or_default(), Ordering::SeqCst);
+        Entered {
+            invocation_id,
+            previous_invocation_id,
+            limiter: self,
+        }
+    }
+
+    /// Waits for the future that resolves with the result of an async
+    /// function, unless the invocation is stopped before it completes.
+    pub(crate) async fn complete<T>(
+        &self,
+        mut invocation: Invocation<'_>,
+        result: impl Future<Output = T>,
+    ) -> Result<T, InvocationError> {
+        let deadline = async {
+            match invocation.deadline {
+                Some(deadline) => sleep_until(deadline).await,
+                None => pending().await,
+            }
+        };
+
+        let guest_error = async {
+            match (&mut invocation.guest_error).await {
+                Ok(error) => error,
+                Err(_) => pending().await,
+            }
+        };
+
+        tokio::select! {
+            result = result => Ok(result),
+            error = guest_error => Err(error),
+            _ = deadline => Err(self.stop(InvocationError::DeadlineExceeded)),
+            _ = self.cancellation_token.cancelled() => Err(InvocationError::Cancelled),
+        }
+    }
+
+    /// Reports an error that occurred while the runtime was resuming the
+    /// provider outside of the call that started the invocation.
+    ///
+    /// Errors are only reported if the invocation on behalf of which the
+    /// provider was resumed is still in progress.
+    pub(crate) fn report_guest_error(&self, invocation_id: Option<u64>, error: InvocationError) {
+        let sender = invocation_id.and_then(|id| {
+            let mut invocations = self.invocations.lock().unwrap();
+            invocations
+                .get_mut(&id)
+                .and_then(|state| state.guest_error.take())
+        });
+        match sender {
+            Some(sender) => {
+                let _ = sender.send(error);
+            }
+            None => {
+                debug!(%error, ?invocation_id, "provider failed after its invocation completed")
+            }
+        }
+    }
+
+    fn stop(&self, error: InvocationError) -> InvocationError {
+        self.stopped.store(true, Ordering::SeqCst);
+        error
+    }
+}
+
+fn operator_cost(_: &Operator) -> u64 {
+    1
+}
+
+/// Tunables that cap the size of the memories a provider can create.
+pub(crate) struct LimitingTunables {
+    base: BaseTunables,
+    max_pages: Option<Pages>,
+    limit_reached: Arc<AtomicBool>,
+}
+
+impl LimitingTunables {
+    fn limit_memory(
+        &self,
+        ty: &MemoryType,
+        memory: impl FnOnce() -> Result<Arc<dyn Memory>, MemoryError>,
+    ) -> Result<Arc<dyn Memory>, MemoryError> {
+        let Some(max_pages) = self.max_pages else {
+            return memory();
+        };
+
+        if ty.minimum > max_pages {
+            self.limit_reached.store(true, Ordering::SeqCst);
+            return Err(MemoryError::MinimumMemoryTooLarge {
+                min_requested: ty.minimum,
+                max_allowed: max_pages,
+            });
+        }
+
+        Ok(Arc::new(LimitedMemory {
+            memory: memory()?,
+            max_pages,
+            limit_reached: self.limit_reached.clone(),
+        }))
+    }
+}
+
+impl MemoryUsage for LimitingTunables {
+    fn size_of_val(&self, tracker: &mut dyn MemoryUsageTracker) -> usize {
+        std::mem::size_of_val(self) + self.base.size_of_val(tracker)
+    }
+}
+
+impl Tunables for LimitingTunables {
+    fn memory_style(&self, memory: &MemoryType) -> MemoryStyle {
+        self.base.memory_style(memory)
+    }
+
+    fn table_style(&self, table: &TableType) -> TableStyle {
+        self.base.table_style(table)
+    }
+
+    fn create_host_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+    ) -> Result<Arc<dyn Memory>, MemoryError> {
+        self.limit_memory(ty, || self.base.create_host_memory(ty, style))
+    }
+
+    unsafe fn create_vm_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+        vm_definition_location: NonNull<VMMemoryDefinition>,
+    ) -> Result<Arc<dyn Memory>, MemoryError> {
+        self.limit_memory(ty, || {
+            self.base
+                .create_vm_memory(ty, style, vm_definition_location)
+        })
+    }
+
+    fn create_host_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+    ) -> Result<Arc<dyn Table>, String> {
+        self.base.create_host_table(ty, style)
+    }
+
+    unsafe fn create_vm_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+        vm_definition_location: NonNull<VMTableDefinition>,
+    ) -> Result<Arc<dyn Table>, String> {
+        self.base.create_vm_table(ty, style, vm_definition_location)
+    }
+}
+
+/// Memory that refuses to grow beyond a maximum number of pages, and records
+/// when it does so.
+#[derive(Debug)]
+struct LimitedMemory {
+    memory: Arc<dyn Memory>,
+    max_pages: Pages,
+    limit_reached: Arc<AtomicBool>,
+}
+
+impl MemoryUsage for LimitedMemory {
+    fn size_of_val(&self, tracker: &mut dyn MemoryUsageTracker) -> usize {
+        std::mem::size_of_val(self) + self.memory.size_of_val(tracker)
+    }
+}
+
+impl Memory for LimitedMemory {
+    fn ty(&self) -> MemoryType {
+        self.memory.ty()
+    }
+
+    fn style(&self) -> &MemoryStyle {
+        self.memory.style()
+    }
+
+    fn size(&self) -> Pages {
+        self.memory.size()
+    }
+
+    fn grow(&self, delta: Pages) -> Result<Pages, MemoryError> {
+        let current = self.memory.size();
+        if current.0.saturating_add(delta.0) > self.max_pages.0 {
+            self.limit_reached.store(true, Ordering::SeqCst);
+            return Err(MemoryError::CouldNotGrow {
+                current,
+                attempted_delta: delta,
+            });
+        }
+
+        self.memory.grow(delta)
+    }
+
+    fn vmmemory(&self) -> NonNull<VMMemoryDefinition> {
+        self.memory.vmmemory()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Provider that never completes any of its functions: `get_config_schema()`
+    /// loops forever, `extract_data()` keeps growing its memory, and
+    /// `invoke2()` returns a future that is never resolved. `burn()` loops the
+    /// given number of times, consuming six units of fuel per iteration.
+    const PROVIDER: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (func (export "__fp_free") (param i64))
+            (func (export "__fp_guest_resolve_async_value") (param i64 i64))
+            (func (export "__fp_malloc") (param i32) (result i64)
+                (i64.or (i64.const 0x100000000000) (i64.extend_i32_u (local.get 0))))
+            (func (export "__fp_gen_get_config_schema") (result i64)
+                (loop $loop (br $loop))
+                (i64.const 0))
+            (func (export "__fp_gen_extract_data") (param i64 i64 i64) (result i64)
+                (loop $loop
+                    (br_if $loop (i32.ne (memory.grow (i32.const 1)) (i32.const -1))))
+                unreachable)
+            (func (export "__fp_gen_invoke2") (param i64) (result i64)
+                (i64.const 0x800000000003))
+            (func (export "burn") (param i32)
+                (loop $loop
+                    (local.set 0 (i32.sub (local.get 0) (i32.const 1)))
+                    (br_if $loop (local.get 0)))))
+    "#;
+
+    fn runtime(limits: RuntimeLimits) -> Runtime {
+        Runtime::with_limits(PROVIDER, HostPolicy::default(), limits).unwrap()
+    }
+
+    #[test]
+    fn test_fuel_exhausted() {
+        let runtime = runtime(RuntimeLimits {
+            fuel: Some(1_000),
+            ..Default::default()
+        });
+
+        let result = runtime.get_config_schema_raw();
+        assert!(
+            matches!(result, Err(InvocationError::FuelExhausted { limit: 1_000 })),
+            "unexpected result: {result:?}"
+        );
+        assert!(runtime.has_stopped());
+    }
+
+    #[test]
+    fn test_memory_limit_exceeded() {
+        let limit = 4 * WASM_PAGE_SIZE;
+        let runtime = runtime(RuntimeLimits {
+            max_memory: Some(limit),
+            ..Default::default()
+        });
+
+        let result = runtime.extract_data_raw(vec![0xc0], vec![0xa0], vec![0xc0]);
+        assert!(
+            matches!(result, Err(InvocationError::MemoryLimitExceeded { limit: actual }) if actual == limit),
+            "unexpected result: {result:?}"
+        );
+        assert!(runtime.has_stopped());
+    }
+
+    #[tokio::test]
+    async fn test_deadline_exceeded() {
+        let runtime = runtime(RuntimeLimits {
+            timeout: Some(Duration::from_millis(10)),
+            ..Default::default()
+        });
+
+        let result = runtime.invoke2_raw(vec![0xc0]).await;
+        assert!(
+            matches!(result, Err(InvocationError::DeadlineExceeded)),
+            "unexpected result: {result:?}"
+        );
+        assert!(runtime.has_stopped());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled() {
+        let runtime = runtime(RuntimeLimits::default());
+        let token = runtime.cancellation_token();
+
+        let (result, _) = tokio::join!(runtime.invoke2_raw(vec![0xc0]), async { token.cancel() });
+        assert!(
+            matches!(result, Err(InvocationError::Cancelled)),
+            "unexpected result: {result:?}"
+        );
+        assert!(runtime.has_stopped());
+
+        let result = runtime.invoke2_raw(vec![0xc0]).await;
+        assert!(
+            matches!(result, Err(InvocationError::Cancelled)),
+            "unexpected result: {result:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_invocations() {
+        let runtime = runtime(RuntimeLimits {
+            fuel: Some(1_000),
+            timeout: Some(Duration::from_millis(10)),
+            ..Default::default()
+        });
+
+        // The provider can be invoked while another invocation is waiting for
+        // an async value.
+        let (first, second) = tokio::join!(runtime.invoke2_raw(vec![0xc0]), async {
+            runtime.get_config_schema_raw()
+        });
+        assert!(
+            matches!(first, Err(InvocationError::DeadlineExceeded)),
+            "unexpected result: {first:?}"
+        );
+        assert!(
+            matches!(second, Err(InvocationError::FuelExhausted { limit: 1_000 })),
+            "unexpected result: {second:?}"
+        );
+    }
+
+    #[test]
+    fn test_fuel_per_invocation() {
+        let runtime = runtime(RuntimeLimits {
+            fuel: Some(1_000),
+            ..Default::default()
+        });
+        let limiter = &runtime.limiter;
+        let burn = runtime
+            .instance
+            .exports
+            .get_native_function::<i32, ()>("burn")
+            .unwrap();
+
+        let first = limiter.start_invocation().unwrap();
+        let second = limiter.start_invocation().unwrap();
+        assert_eq!(limiter.current_invocation_id(), None);
+
+        {
+            let _entered = first.enter();
+            assert_eq!(limiter.current_invocation_id(), Some(first.id()));
+            burn.call(100).unwrap();
+        }
+        assert_eq!(limiter.current_invocation_id(), None);
+
+        // Every invocation has its own supply of fuel...
+        {
+            let _entered = second.enter();
+            assert_eq!(limiter.current_invocation_id(), Some(second.id()));
+            burn.call(100).unwrap();
+        }
+
+        // ...which is not replenished when the invocation is entered again.
+        let entered = first.enter();
+        let error = burn.call(100).unwrap_err();
+        assert!(
+            matches!(
+                entered.to_invocation_error(error),
+                InvocationError::FuelExhausted { limit: 1_000 }
+            ),
+            "expected the fuel to be exhausted"
+        );
+        drop(entered);
+
+        // Calls outside of an invocation are not attributed to it.
+        drop(first);
+        let _entered = limiter.enter(Some(u64::MAX));
+        assert_eq!(limiter.current_invocation_id(), None);
+        burn.call(100).unwrap();
+    }
+}
@@ -0,0 +1,180 @@
+//! Logging of the messages that providers log through the runtime.
+
+use crate::spec::{LogLevel, Runtime};
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use tracing::{event, Level};
+
+/// Target of the `tracing` events for messages logged by providers.
+///
+/// Events have the following fields, besides the message:
+///
+/// * `provider`: Name of the provider, if one was set using
+///   [Runtime::set_provider_name()](crate::spec::Runtime::set_provider_name).
+/// * `invocation_id`: ID of the invocation during which the message was
+///   logged. IDs are unique within the process, and are absent for messages
+///   that are logged outside of an invocation.
+/// * `fields`: Fields the provider logged along with the message.
+pub const LOG_TARGET: &str = "provider";
+
+impl Runtime {
+    /// Sets the name of the provider, with which the messages it logs are
+    /// tagged.
+    pub fn set_provider_name(&self, name: impl Into<String>) {
+        self.logger.set_provider_name(name.into());
+    }
+}
+
+/// Emits the messages logged by a provider as `tracing` events.
+#[derive(Debug, Default)]
+pub(crate) struct ProviderLogger {
+    provider_name: RwLock<Option<String>>,
+}
+
+impl ProviderLogger {
+    pub(crate) fn set_provider_name(&self, name: String) {
+        *self.provider_name.write().unwrap() = Some(name);
+    }
+
+    pub(crate) fn log(
+        &self,
+        invocation_id: Option<u64>,
+        level: LogLevel,
+        message: &str,
+        fields: &BTreeMap<String, String>,
+    ) {
+        let provider_name = self.provider_name.read().unwrap();
+        let provider = provider_name.as_deref();
+
+        macro_rules! log_event {
+            ($level:expr) => {
+                event!(
+                    target: LOG_TARGET,
+                    $level,
+                    provider,
+                    invocation_id,
+                    ?fields,
+                    "{message}"
+                )
+            };
+        }
+
+        match level {
+            LogLevel::Trace => log_event!(Level::TRACE),
+            LogLevel::Debug => log_event!(Level::DEBUG),
+            LogLevel::Warn => log_event!(Level::WARN),
+            LogLevel::Error => log_event!(Level::ERROR),
+            _ => log_event!(Level::INFO),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Event that was captured by the [CapturingSubscriber].
+    #[derive(Debug, Default)]
+    struct CapturedEvent {
+        target: String,
+        level: Option<Level>,
+        fields: BTreeMap<String, String>,
+    }
+
+    impl Visit for CapturedEvent {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.fields
+                .insert(field.name().to_owned(), format!("{value:?}"));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.fields
+                .insert(field.name().to_owned(), value.to_owned());
+        }
+    }
+
+    /// Subscriber that captures all events.
+    #[derive(Clone, Default)]
+    struct CapturingSubscriber {
+        events: Arc<Mutex<Vec<CapturedEvent>>>,
+    }
+
+    impl Subscriber for CapturingSubscriber {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut captured = CapturedEvent {
+                target: event.metadata().target().to_owned(),
+                level: Some(*event.metadata().level()),
+                ..Default::default()
+            };
+            event.record(&mut captured);
+            self.events.lock().unwrap().push(captured);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn test_log_events() {
+        let subscriber = CapturingSubscriber::default();
+        let logger = ProviderLogger::default();
+        logger.set_provider_name("prometheus".to_owned());
+
+        let fields = BTreeMap::from([("query".to_owned(), "up".to_owned())]);
+        tracing::subscriber::with_default(subscriber.clone(), || {
+            for level in [
+                LogLevel::Trace,
+                LogLevel::Debug,
+                LogLevel::Info,
+                LogLevel::Warn,
+                LogLevel::Error,
+            ] {
+                logger.log(Some(42), level, "querying", &fields);
+            }
+            logger.log(None, LogLevel::Info, "idle", &BTreeMap::new());
+        });
+
+        let events = subscriber.events.lock().unwrap();
+        let levels: Vec<_> = events.iter().map(|event| event.level.unwrap()).collect();
+        assert_eq!(
+            levels,
+            [
+                Level::TRACE,
+                Level::DEBUG,
+                Level::INFO,
+                Level::WARN,
+                Level::ERROR,
+                Level::INFO
+            ]
+        );
+
+        let event = &events[0];
+        assert_eq!(event.target, LOG_TARGET);
+        assert_eq!(event.fields["message"], "querying");
+        assert_eq!(event.fields["provider"], "prometheus");
+        assert_eq!(event.fields["invocation_id"], "42");
+        assert_eq!(event.fields["fields"], r#"{"query": "up"}"#);
+
+        let event = &events[5];
+        assert_eq!(event.fields["message"], "idle");
+        assert!(!event.fields.contains_key("invocation_id"));
+    }
+}
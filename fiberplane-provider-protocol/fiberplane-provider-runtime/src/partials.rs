@@ -0,0 +1,125 @@
+//! Routing of the partial results that providers emit during invocations.
+
+use crate::errors::InvocationError;
+use crate::spec::{Blob, Error, ProviderRequest, Runtime};
+use fp_bindgen_support::wasmer2_host::mem::{deserialize_from_slice, serialize_to_vec};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::debug;
+
+impl Runtime {
+    /// Invokes the provider to perform a data request, while passing the
+    /// partial results it emits to the given sender.
+    ///
+    /// The request is marked as accepting partial results, so the result
+    /// returned by the provider only contains the part of the response that
+    /// was not emitted as partial results yet.
+    pub async fn invoke2_streaming(
+        &self,
+        mut request: ProviderRequest,
+        partial_results: UnboundedSender<Blob>,
+    ) -> Result<Result<Blob, Error>, InvocationError> {
+        request.accepts_partial_results = true;
+        let request = serialize_to_vec(&request);
+        let result = self.invoke2_streaming_raw(request, partial_results);
+        let result = result.await;
+        result.map(|ref data| deserialize_from_slice(data))
+    }
+
+    /// Like [Self::invoke2_streaming()], but for requests that are already
+    /// serialized.
+    ///
+    /// Unlike [Self::invoke2_streaming()], this does not mark the request as
+    /// accepting partial results.
+    pub async fn invoke2_streaming_raw(
+        &self,
+        request: Vec<u8>,
+        partial_results: UnboundedSender<Blob>,
+    ) -> Result<Vec<u8>, InvocationError> {
+        self.invoke2_with_partial_results(request, Some(partial_results))
+            .await
+    }
+}
+
+/// Channels through which the partial results of streaming invocations are
+/// passed to the host, keyed by invocation ID.
+#[derive(Debug, Default)]
+pub(crate) struct PartialResults {
+    senders: Mutex<HashMap<u64, UnboundedSender<Blob>>>,
+}
+
+impl PartialResults {
+    /// Passes the partial results of the given invocation to the sender, until
+    /// the returned registration is dropped.
+    pub(crate) fn register(
+        self: &Arc<Self>,
+        invocation_id: u64,
+        sender: UnboundedSender<Blob>,
+    ) -> Registration {
+        self.senders.lock().unwrap().insert(invocation_id, sender);
+        Registration {
+            partial_results: self.clone(),
+            invocation_id,
+        }
+    }
+
+    /// Passes a partial result to the host, if the invocation that emitted it
+    /// is streaming.
+    pub(crate) fn emit(&self, invocation_id: Option<u64>, result: Blob) {
+        let senders = self.senders.lock().unwrap();
+        match invocation_id.and_then(|invocation_id| senders.get(&invocation_id)) {
+            Some(sender) => {
+                if sender.send(result).is_err() {
+                    debug!(
+                        invocation_id,
+                        "partial result was emitted after the receiver was dropped"
+                    );
+                }
+            }
+            None => debug!(
+                invocation_id,
+                "discarding partial result of invocation that does not stream"
+            ),
+        }
+    }
+}
+
+/// Registration of the sender for a streaming invocation.
+pub(crate) struct Registration {
+    partial_results: Arc<PartialResults>,
+    invocation_id: u64,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.partial_results
+            .senders
+            .lock()
+            .unwrap()
+            .remove(&self.invocation_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_emit_partial_results() {
+        let partial_results = Arc::new(PartialResults::default());
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let registration = partial_results.register(1, sender);
+
+        let blob = |data: &'static str| Blob::builder().data(data).mime_type("text/plain").build();
+        partial_results.emit(Some(1), blob("first"));
+        partial_results.emit(Some(2), blob("other"));
+        partial_results.emit(None, blob("other"));
+        drop(registration);
+        partial_results.emit(Some(1), blob("second"));
+
+        assert_eq!(receiver.try_recv().unwrap().data, "first");
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,487 @@
+//! Policies that govern the network access of providers.
+
+use crate::errors::RuntimeError;
+use crate::limits::RuntimeLimits;
+use crate::spec::Runtime;
+use reqwest::dns::{Name, Resolve, Resolving};
+use reqwest::header::HeaderMap;
+use reqwest::{redirect, Client, Proxy};
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::Semaphore;
+use url::{Host, Url};
+
+/// Default maximum size of HTTP responses that are passed to providers.
+pub const DEFAULT_MAX_RESPONSE_SIZE: usize = 1024 * 1024 * 2; // 2MiB
+
+const MAX_REDIRECTS: usize = 10;
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// Policy that determines which HTTP requests providers are allowed to make,
+/// and the limits that apply to them.
+///
+/// The default policy allows requests to any host, without a timeout or a
+/// limit on concurrent requests, and with a maximum response size of
+/// [DEFAULT_MAX_RESPONSE_SIZE].
+///
+/// Requests that are denied by the policy fail with an
+/// `HttpRequestError::Other`, of which the reason names the host that is not
+/// allowed.
+#[derive(Clone, Debug)]
+pub struct HostPolicy {
+    /// Hosts that providers are allowed to make requests to.
+    ///
+    /// If empty, requests to all hosts are allowed, except for those that
+    /// match `blocked_hosts`.
+    pub allowed_hosts: Vec<HostPattern>,
+
+    /// Hosts that providers are never allowed to make requests to, even if
+    /// they match `allowed_hosts`.
+    ///
+    /// See [HostPattern::link_local_and_metadata()] for a list of patterns
+    /// that should be blocked when running untrusted providers.
+    pub blocked_hosts: Vec<HostPattern>,
+
+    /// Timeout for a single request, including the time it takes to read the
+    /// response body.
+    pub timeout: Option<Duration>,
+
+    /// Maximum size of a response body, in bytes.
+    pub max_response_size: usize,
+
+    /// Maximum number of requests a provider can make concurrently.
+    ///
+    /// Requests that exceed the limit wait until an earlier request
+    /// completes.
+    pub max_concurrent_requests: Option<usize>,
+
+    /// Headers to add to every request, unless the provider sets them itself.
+    pub default_headers: HeaderMap,
+
+    /// Optional proxy through which all requests are made.
+    ///
+    /// Note that when a proxy is used, the proxy resolves the hosts of
+    /// requests. IP range patterns are then only matched against hosts that
+    /// are specified as IP addresses.
+    pub proxy: Option<Proxy>,
+}
+
+impl Default for HostPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_hosts: Vec::new(),
+            blocked_hosts: Vec::new(),
+            timeout: None,
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+            max_concurrent_requests: None,
+            default_headers: HeaderMap::new(),
+            proxy: None,
+        }
+    }
+}
+
+impl Runtime {
+    /// Creates a runtime of which the provider can only make HTTP requests
+    /// that are allowed by the given policy.
+    pub fn with_policy(
+        wasm_module: impl AsRef<[u8]>,
+        policy: HostPolicy,
+    ) -> Result<Self, RuntimeError> {
+        Self::with_limits(wasm_module, policy, RuntimeLimits::default())
+    }
+}
+
+impl HostPolicy {
+    /// Checks whether the host of the URL may be allowed, before its addresses
+    /// are resolved.
+    pub(crate) fn check_url(&self, url: &Url) -> Result<(), HostNotAllowedError> {
+        let allowed = match url.host() {
+            Some(Host::Domain(domain)) => self.check_domain(domain) != DomainAccess::Blocked,
+            Some(Host::Ipv4(ip)) => self.allows_address(ip.into(), false),
+            Some(Host::Ipv6(ip)) => self.allows_address(ip.into(), false),
+            None => false,
+        };
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(HostNotAllowedError::new(url.host_str().unwrap_or_default()))
+        }
+    }
+
+    fn check_domain(&self, domain: &str) -> DomainAccess {
+        let matches_domain = |pattern: &HostPattern| pattern.matches_domain(domain);
+
+        if self.blocked_hosts.iter().any(matches_domain) {
+            DomainAccess::Blocked
+        } else if self.allowed_hosts.is_empty() || self.allowed_hosts.iter().any(matches_domain) {
+            DomainAccess::Allowed
+        } else if self.allowed_hosts.iter().any(HostPattern::is_ip_range) {
+            DomainAccess::DependsOnAddress
+        } else {
+            DomainAccess::Blocked
+        }
+    }
+
+    fn allows_address(&self, address: IpAddr, domain_allowed: bool) -> bool {
+        let matches_address = |pattern: &HostPattern| pattern.matches_address(address);
+
+        !self.blocked_hosts.iter().any(matches_address)
+            && (domain_allowed
+                || self.allowed_hosts.is_empty()
+                || self.allowed_hosts.iter().any(matches_address))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum DomainAccess {
+    Allowed,
+    Blocked,
+    /// The domain is only allowed if it resolves to an allowed IP address.
+    DependsOnAddress,
+}
+
+/// Pattern to match the host of a request against.
+///
+/// Patterns can be parsed from strings:
+///
+/// * `example.com` matches the domain `example.com` only.
+/// * `*.example.com` matches all subdomains of `example.com`, but not
+///   `example.com` itself.
+/// * `10.0.0.0/8` or `fd00::/8` matches all IP addresses in the given range,
+///   both for hosts that are specified as IP address and for the addresses
+///   that domains resolve to.
+/// * `10.1.2.3` or `::1` matches a single IP address.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum HostPattern {
+    /// Matches the given domain, or any of its subdomains if `include_subdomains`
+    /// is `true`.
+    Domain {
+        domain: String,
+        include_subdomains: bool,
+    },
+
+    /// Matches all IP addresses in the given range.
+    IpRange { network: IpAddr, prefix_len: u8 },
+}
+
+impl HostPattern {
+    /// Returns patterns for link-local addresses and the metadata services of
+    /// cloud providers, which are commonly used to obtain credentials for the
+    /// machine a provider is running on.
+    pub fn link_local_and_metadata() -> Vec<Self> {
+        [
+            "169.254.0.0/16",
+            "fe80::/10",
+            "fd00:ec2::254",
+            "metadata.google.internal",
+        ]
+        .into_iter()
+        .map(|pattern| pattern.parse().expect("Invalid built-in host pattern"))
+        .collect()
+    }
+
+    fn is_ip_range(&self) -> bool {
+        matches!(self, Self::IpRange { .. })
+    }
+
+    fn matches_domain(&self, domain: &str) -> bool {
+        let domain = domain.trim_end_matches('.');
+        match self {
+            Self::Domain {
+                domain: pattern,
+                include_subdomains: false,
+            } => domain.eq_ignore_ascii_case(pattern),
+            Self::Domain {
+                domain: pattern,
+                include_subdomains: true,
+            } => {
+                let (domain, pattern) = (domain.as_bytes(), pattern.as_bytes());
+                let parent_start = domain.len().saturating_sub(pattern.len());
+                parent_start > 1
+                    && domain[parent_start - 1] == b'.'
+                    && domain[parent_start..].eq_ignore_ascii_case(pattern)
+            }
+            Self::IpRange { .. } => false,
+        }
+    }
+
+    fn matches_address(&self, address: IpAddr) -> bool {
+        let Self::IpRange {
+            network,
+            prefix_len,
+        } = self
+        else {
+            return false;
+        };
+
+        let address = match address {
+            IpAddr::V6(address) => address
+                .to_ipv4_mapped()
+                .map(IpAddr::V4)
+                .unwrap_or(IpAddr::V6(address)),
+            address => address,
+        };
+
+        match (network, address) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                prefix_matches(u32::from(*network), u32::from(address), *prefix_len)
+            }
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                prefix_matches(u128::from(*network), u128::from(address), *prefix_len)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn prefix_matches<T>(network: T, address: T, prefix_len: u8) -> bool
+where
+    T: Copy + Eq + std::ops::BitXor<Output = T> + std::ops::Shr<u32, Output = T> + From<u8>,
+{
+    let bits = 8 * std::mem::size_of::<T>() as u32;
+    let prefix_len = u32::from(prefix_len);
+    prefix_len == 0 || (network ^ address) >> (bits - prefix_len) == T::from(0)
+}
+
+impl FromStr for HostPattern {
+    type Err = InvalidHostPattern;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidHostPattern(pattern.to_owned());
+
+        if let Some((network, prefix_len)) = pattern.split_once('/') {
+            let network: IpAddr = network.parse().map_err(|_| invalid())?;
+            let prefix_len: u8 = prefix_len.parse().map_err(|_| invalid())?;
+            let max_prefix_len = if network.is_ipv4() { 32 } else { 128 };
+            return if prefix_len <= max_prefix_len {
+                Ok(Self::IpRange {
+                    network,
+                    prefix_len,
+                })
+            } else {
+                Err(invalid())
+            };
+        }
+
+        if let Ok(address) = pattern.parse::<IpAddr>() {
+            return Ok(Self::IpRange {
+                network: address,
+                prefix_len: if address.is_ipv4() { 32 } else { 128 },
+            });
+        }
+
+        let (domain, include_subdomains) = match pattern.strip_prefix("*.") {
+            Some(domain) => (domain, true),
+            None => (pattern, false),
+        };
+        match Host::parse(domain) {
+            Ok(Host::Domain(domain)) => Ok(Self::Domain {
+                domain,
+                include_subdomains,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid host pattern: {0}")]
+pub struct InvalidHostPattern(String);
+
+/// Error for requests that are denied by the [HostPolicy].
+#[derive(Debug, Error)]
+#[error("Host not allowed: {host}")]
+pub(crate) struct HostNotAllowedError {
+    pub host: String,
+}
+
+impl HostNotAllowedError {
+    fn new(host: impl Into<String>) -> Self {
+        Self { host: host.into() }
+    }
+}
+
+/// HTTP client that makes requests on behalf of providers, while enforcing a
+/// [HostPolicy].
+#[derive(Debug)]
+pub struct HttpClient {
+    client: Client,
+    policy: Arc<HostPolicy>,
+    request_slots: Option<Semaphore>,
+}
+
+impl HttpClient {
+    pub fn new(policy: HostPolicy) -> Self {
+        let policy = Arc::new(policy);
+
+        let redirect_policy = {
+            let policy = policy.clone();
+            redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else if let Err(error) = policy.check_url(attempt.url()) {
+                    attempt.error(error)
+                } else {
+                    attempt.follow()
+                }
+            })
+        };
+
+        let mut builder = Client::builder()
+            .tcp_keepalive(Some(TCP_KEEPALIVE))
+            .default_headers(policy.default_headers.clone())
+            .redirect(redirect_policy)
+            .dns_resolver(Arc::new(PolicyResolver {
+                policy: policy.clone(),
+            }));
+        if let Some(timeout) = policy.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(proxy) = policy.proxy.clone() {
+            builder = builder.proxy(proxy);
+        }
+
+        Self {
+            client: builder.build().expect("Failed to build HTTP client"),
+            request_slots: policy.max_concurrent_requests.map(Semaphore::new),
+            policy,
+        }
+    }
+
+    pub fn policy(&self) -> &HostPolicy {
+        &self.policy
+    }
+
+    pub(crate) fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub(crate) fn request_slots(&self) -> Option<&Semaphore> {
+        self.request_slots.as_ref()
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(HostPolicy::default())
+    }
+}
+
+/// DNS resolver that only returns the addresses the policy allows.
+struct PolicyResolver {
+    policy: Arc<HostPolicy>,
+}
+
+impl Resolve for PolicyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let policy = self.policy.clone();
+        Box::pin(async move {
+            let domain = name.as_str();
+            let access = policy.check_domain(domain);
+            if access == DomainAccess::Blocked {
+                return Err(HostNotAllowedError::new(domain).into());
+            }
+
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((domain, 0))
+                .await?
+                .filter(|address| {
+                    policy.allows_address(address.ip(), access == DomainAccess::Allowed)
+                })
+                .collect();
+            if addresses.is_empty() {
+                return Err(HostNotAllowedError::new(domain).into());
+            }
+
+            Ok(Box::new(addresses.into_iter()) as _)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allowed_hosts: &[&str], blocked_hosts: &[&str]) -> HostPolicy {
+        let parse = |patterns: &[&str]| {
+            patterns
+                .iter()
+                .map(|pattern| pattern.parse().unwrap())
+                .collect()
+        };
+        HostPolicy {
+            allowed_hosts: parse(allowed_hosts),
+            blocked_hosts: parse(blocked_hosts),
+            ..Default::default()
+        }
+    }
+
+    fn is_allowed(policy: &HostPolicy, url: &str) -> bool {
+        policy.check_url(&Url::parse(url).unwrap()).is_ok()
+    }
+
+    #[test]
+    fn test_parse_host_pattern() {
+        assert_eq!(
+            "*.Example.com".parse::<HostPattern>().unwrap(),
+            HostPattern::Domain {
+                domain: "example.com".to_owned(),
+                include_subdomains: true
+            }
+        );
+        assert_eq!(
+            "10.0.0.0/8".parse::<HostPattern>().unwrap(),
+            HostPattern::IpRange {
+                network: "10.0.0.0".parse().unwrap(),
+                prefix_len: 8
+            }
+        );
+        assert!("10.0.0.0/33".parse::<HostPattern>().is_err());
+        assert!("*.".parse::<HostPattern>().is_err());
+        assert_eq!(HostPattern::link_local_and_metadata().len(), 4);
+    }
+
+    #[test]
+    fn test_check_url() {
+        let policy = policy(
+            &["*.example.com", "10.0.0.0/8"],
+            &["secret.example.com", "10.1.0.0/16"],
+        );
+
+        assert!(is_allowed(&policy, "https://api.example.com/query"));
+        assert!(!is_allowed(&policy, "https://secret.example.com/"));
+        assert!(is_allowed(&policy, "http://10.2.3.4:9090/"));
+        assert!(!is_allowed(&policy, "http://10.1.3.4:9090/"));
+        assert!(!is_allowed(&policy, "http://192.168.1.1/"));
+
+        // Other domains may still resolve to an allowed address.
+        assert_eq!(
+            policy.check_domain("example.com"),
+            DomainAccess::DependsOnAddress
+        );
+
+        let policy = self::policy(&["*.example.com"], &[]);
+        assert!(!is_allowed(&policy, "https://example.com/"));
+        assert!(!is_allowed(&policy, "http://10.2.3.4:9090/"));
+    }
+
+    #[test]
+    fn test_block_link_local_and_metadata() {
+        let policy = HostPolicy {
+            blocked_hosts: HostPattern::link_local_and_metadata(),
+            ..Default::default()
+        };
+
+        assert!(is_allowed(&policy, "https://example.com/"));
+        assert!(!is_allowed(
+            &policy,
+            "http://169.254.169.254/latest/meta-data"
+        ));
+        assert!(!is_allowed(&policy, "http://[::ffff:169.254.169.254]/"));
+        assert!(!is_allowed(&policy, "http://[fd00:ec2::254]/"));
+        assert!(!is_allowed(&policy, "http://metadata.google.internal/"));
+    }
+}
@@ -1,21 +1,171 @@
+use crate::limits::Limiter;
+use crate::logging::ProviderLogger;
+use crate::partials::PartialResults;
+use crate::policy::{HostNotAllowedError, HttpClient};
+use crate::recording::HttpRecording;
+use fp_bindgen_support::common::{abi::WasmAbi, mem::FatPtr};
+use fp_bindgen_support::wasmer2_host::mem::{export_to_guest, import_from_guest};
+use fp_bindgen_support::wasmer2_host::r#async::{create_future_value, resolve_async_value};
+use fp_bindgen_support::wasmer2_host::runtime::RuntimeInstanceData;
 use rand::Rng;
-use reqwest::{Client, Url};
+use reqwest::{Method, Response, Url};
 use std::collections::BTreeMap;
-use std::time::{Duration, SystemTime};
+use std::ops::Deref;
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
 use tracing::{debug, error, instrument, trace};
+use wasmer::{
+    imports, Function, HostEnvInitError, ImportObject, Instance, LazyInit, NativeFunc, Store,
+    WasmerEnv,
+};
 
-mod bindings;
+mod runtime;
 pub mod types;
 
-pub use bindings::*;
+pub use runtime::Runtime;
 pub use types::*;
 
-const MAX_HTTP_RESPONSE_SIZE: usize = 1024 * 1024 * 2; // 2MiB
-const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
-thread_local!(static CLIENT: Client = Client::builder()
-    .tcp_keepalive(Some(TCP_KEEPALIVE))
-    .build()
-    .expect("Failed to build HTTP client"));
+/// Environment that is passed to the functions the runtime exposes to the
+/// provider.
+///
+/// Besides the instance data that is needed for passing values to and from the
+/// provider, this contains the state of the host for a single [Runtime].
+#[derive(Clone)]
+pub struct HostEnv {
+    instance_data: RuntimeInstanceData,
+    http_client: Arc<HttpClient>,
+    limiter: Arc<Limiter>,
+    logger: Arc<ProviderLogger>,
+    partial_results: Arc<PartialResults>,
+    recording: Arc<RwLock<Option<HttpRecording>>>,
+    resolve_async_value: LazyInit<NativeFunc<(FatPtr, FatPtr)>>,
+}
+
+impl HostEnv {
+    pub(crate) fn new(
+        instance_data: RuntimeInstanceData,
+        http_client: Arc<HttpClient>,
+        limiter: Arc<Limiter>,
+        logger: Arc<ProviderLogger>,
+        partial_results: Arc<PartialResults>,
+        recording: Arc<RwLock<Option<HttpRecording>>>,
+    ) -> Self {
+        Self {
+            instance_data,
+            http_client,
+            limiter,
+            logger,
+            partial_results,
+            recording,
+            resolve_async_value: LazyInit::new(),
+        }
+    }
+
+    /// Resumes the provider with the result of an async value that was
+    /// requested during the given invocation.
+    ///
+    /// The provider is resumed on behalf of that invocation, so it uses the
+    /// fuel of the invocation, and the host calls it makes are attributed to
+    /// it.
+    ///
+    /// Unlike [RuntimeInstanceData::guest_resolve_async_value()], errors that
+    /// are raised by the provider are reported to the invocation that awaits
+    /// the provider, instead of panicking.
+    pub fn guest_resolve_async_value(
+        &self,
+        invocation_id: Option<u64>,
+        async_ptr: FatPtr,
+        result_ptr: FatPtr,
+    ) {
+        let resolve_async_value = self
+            .resolve_async_value
+            .get_ref()
+            .expect("Host environment was not initialized");
+        let entered = self.limiter.enter(invocation_id);
+        if let Err(error) = resolve_async_value.call(async_ptr, result_ptr) {
+            debug!(%error, "provider failed to resolve async value");
+            let error = entered.to_invocation_error(error);
+            drop(entered);
+            self.limiter.report_guest_error(invocation_id, error);
+        }
+    }
+}
+
+impl Deref for HostEnv {
+    type Target = RuntimeInstanceData;
+
+    fn deref(&self) -> &Self::Target {
+        &self.instance_data
+    }
+}
+
+impl WasmerEnv for HostEnv {
+    fn init_with_instance(&mut self, instance: &Instance) -> Result<(), HostEnvInitError> {
+        self.instance_data.init_with_instance(instance)?;
+        self.resolve_async_value.initialize(
+            instance
+                .exports
+                .get_with_generics_weak("__fp_guest_resolve_async_value")?,
+        );
+        Ok(())
+    }
+}
+
+/// Creates the object with the functions the runtime exposes to the provider.
+pub(crate) fn create_import_object(store: &Store, env: &HostEnv) -> ImportObject {
+    imports! {
+        "fp" => {
+            "__fp_host_resolve_async_value" => Function::new_native_with_env(store, env.instance_data.clone(), resolve_async_value),
+            "__fp_gen_emit_partial" => Function::new_native_with_env(store, env.clone(), _emit_partial),
+            "__fp_gen_log" => Function::new_native_with_env(store, env.clone(), _log),
+            "__fp_gen_log_structured" => Function::new_native_with_env(store, env.clone(), _log_structured),
+            "__fp_gen_make_http_request" => Function::new_native_with_env(store, env.clone(), _make_http_request),
+            "__fp_gen_now" => Function::new_native_with_env(store, env.clone(), _now),
+            "__fp_gen_random" => Function::new_native_with_env(store, env.clone(), _random),
+        }
+    }
+}
+
+fn _emit_partial(env: &HostEnv, result: FatPtr) {
+    let result = import_from_guest::<Blob>(env, result);
+    emit_partial(env, result)
+}
+
+fn _log(env: &HostEnv, message: FatPtr) {
+    let message = import_from_guest::<String>(env, message);
+    log_structured(env, LogLevel::Info, message, BTreeMap::new())
+}
+
+fn _log_structured(env: &HostEnv, level: FatPtr, message: FatPtr, fields: FatPtr) {
+    let level = import_from_guest::<LogLevel>(env, level);
+    let message = import_from_guest::<String>(env, message);
+    let fields = import_from_guest::<BTreeMap<String, String>>(env, fields);
+    log_structured(env, level, message, fields)
+}
+
+fn _make_http_request(env: &HostEnv, request: FatPtr) -> FatPtr {
+    let request = import_from_guest::<HttpRequest>(env, request);
+    let env = env.clone();
+    let invocation_id = env.limiter.current_invocation_id();
+    let recording = env.recording.read().unwrap().clone();
+    let async_ptr = create_future_value(&env);
+    let handle = tokio::runtime::Handle::current();
+    handle.spawn(async move {
+        let result = make_http_request(&env.http_client, recording.as_ref(), request).await;
+        let result_ptr = export_to_guest(&env, &result);
+        env.guest_resolve_async_value(invocation_id, async_ptr, result_ptr);
+    });
+    async_ptr
+}
+
+fn _now(env: &HostEnv) -> FatPtr {
+    export_to_guest(env, &now())
+}
+
+fn _random(env: &HostEnv, len: <u32 as WasmAbi>::AbiType) -> FatPtr {
+    let len = WasmAbi::from_abi(len);
+    export_to_guest(env, &random(len))
+}
 
 #[instrument(skip_all, fields(
     url = ?req.url,
@@ -23,7 +173,11 @@ thread_local!(static CLIENT: Client = Client::builder()
     headers = ?req.headers.as_ref().map(|headers| headers.keys().map(|key| key.as_str()).collect::<Vec<_>>()).unwrap_or_default(),
     body_size = ?req.body.as_ref().map(|body| body.len()).unwrap_or_default())
 )]
-pub async fn make_http_request(req: HttpRequest) -> Result<HttpResponse, HttpRequestError> {
+pub async fn make_http_request(
+    client: &HttpClient,
+    recording: Option<&HttpRecording>,
+    req: HttpRequest,
+) -> Result<HttpResponse, HttpRequestError> {
     let url = Url::parse(&req.url).map_err(|error| HttpRequestError::Other {
         reason: error.to_string(),
     })?;
@@ -35,15 +189,52 @@ pub async fn make_http_request(req: HttpRequest) -> Result<HttpResponse, HttpReq
         }),
     }?;
 
+    client
+        .policy()
+        .check_url(&url)
+        .map_err(|error| denied_by_policy(&error))?;
+
+    let method = to_reqwest_method(&req.method)?;
+
+    if let Some(result) = recording.and_then(|recording| recording.replay_request(&req)) {
+        return result;
+    }
+
+    match recording.filter(|recording| recording.is_recording()) {
+        Some(recording) => {
+            let result = send_http_request(client, method, url, req.clone()).await;
+            recording.record_request(req, &result);
+            result
+        }
+        None => send_http_request(client, method, url, req).await,
+    }
+}
+
+async fn send_http_request(
+    client: &HttpClient,
+    method: Method,
+    url: Url,
+    req: HttpRequest,
+) -> Result<HttpResponse, HttpRequestError> {
+    // Holding on to the permit until the response body is read limits the
+    // number of concurrent requests.
+    let _permit = match client.request_slots() {
+        Some(request_slots) => {
+            Some(
+                request_slots
+                    .acquire()
+                    .await
+                    .map_err(|error| HttpRequestError::Other {
+                        reason: error.to_string(),
+                    })?,
+            )
+        }
+        None => None,
+    };
+
     trace!("making HTTP request");
 
-    let mut builder = CLIENT.with(|client| match req.method {
-        HttpRequestMethod::Delete => client.delete(url),
-        HttpRequestMethod::Get => client.get(url),
-        HttpRequestMethod::Head => client.head(url),
-        HttpRequestMethod::Post => client.post(url),
-        _ => panic!("Unknown HttpRequestMethod: {:?}", req.method),
-    });
+    let mut builder = client.client().request(method, url);
     if let Some(body) = req.body {
         builder = builder.body(body);
     }
@@ -53,17 +244,7 @@ pub async fn make_http_request(req: HttpRequest) -> Result<HttpResponse, HttpReq
         }
     }
 
-    let response = builder.send().await.map_err(|error| {
-        if error.is_timeout() {
-            debug!("request timed out");
-            HttpRequestError::Timeout
-        } else {
-            debug!(?error, "request error");
-            HttpRequestError::Other {
-                reason: error.to_string(),
-            }
-        }
-    })?;
+    let response = builder.send().await.map_err(to_http_request_error)?;
 
     debug!(
         status = ?response.status(),
@@ -81,19 +262,11 @@ pub async fn make_http_request(req: HttpRequest) -> Result<HttpResponse, HttpReq
         }
     }
 
-    let body = response.bytes().await.map_err(|error| {
-        error!(?error, "Failed to get response bytes");
-        HttpRequestError::Other {
-            reason: error.to_string(),
-        }
-    })?;
+    let body = read_response_body(response, client.policy().max_response_size).await?;
 
     trace!("Fetched {} bytes", body.len());
 
-    let body = body.to_vec();
-
     match status_code {
-        _ if body.len() > MAX_HTTP_RESPONSE_SIZE => Err(HttpRequestError::ResponseTooBig),
         200..=299 => Ok(HttpResponse::builder()
             .body(body)
             .headers(headers)
@@ -106,12 +279,99 @@ pub async fn make_http_request(req: HttpRequest) -> Result<HttpResponse, HttpReq
     }
 }
 
+/// Converts the method using its name, because `HttpRequestMethod` is
+/// non-exhaustive and can only be matched exhaustively in its own crate.
+fn to_reqwest_method(method: &HttpRequestMethod) -> Result<Method, HttpRequestError> {
+    let method: &'static str = method.into();
+    Method::from_bytes(method.as_bytes()).map_err(|error| HttpRequestError::Other {
+        reason: format!("Unsupported HTTP request method {method}: {error}"),
+    })
+}
+
+/// Reports a request that is denied by the host policy as an
+/// [HttpRequestError::Other], because providers built against earlier versions
+/// of the protocol cannot deserialize any variants that are added to
+/// [HttpRequestError].
+fn denied_by_policy(error: &HostNotAllowedError) -> HttpRequestError {
+    debug!(%error, "request denied by host policy");
+    HttpRequestError::Other {
+        reason: error.to_string(),
+    }
+}
+
+fn to_http_request_error(error: reqwest::Error) -> HttpRequestError {
+    if let Some(error) = find_source::<HostNotAllowedError>(&error) {
+        denied_by_policy(error)
+    } else if error.is_timeout() {
+        debug!("request timed out");
+        HttpRequestError::Timeout
+    } else {
+        debug!(?error, "request error");
+        HttpRequestError::Other {
+            reason: error.to_string(),
+        }
+    }
+}
+
+/// Finds an error of type `T` in the chain of sources of the given error.
+fn find_source<'a, T: std::error::Error + 'static>(
+    error: &'a (dyn std::error::Error + 'static),
+) -> Option<&'a T> {
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if let Some(error) = error.downcast_ref::<T>() {
+            return Some(error);
+        }
+        source = error.source();
+    }
+    None
+}
+
+/// Reads the body of the response as it is streamed in, so that responses
+/// exceeding `max_size` are rejected without buffering them entirely.
+async fn read_response_body(
+    mut response: Response,
+    max_size: usize,
+) -> Result<Vec<u8>, HttpRequestError> {
+    let content_length = response.content_length().unwrap_or_default() as usize;
+    if content_length > max_size {
+        debug!(content_length, "response too big");
+        return Err(HttpRequestError::ResponseTooBig);
+    }
+
+    let mut body = Vec::with_capacity(content_length);
+    while let Some(chunk) = response.chunk().await.map_err(|error| {
+        error!(?error, "Failed to get response bytes");
+        to_http_request_error(error)
+    })? {
+        if body.len() + chunk.len() > max_size {
+            debug!(received = body.len() + chunk.len(), "response too big");
+            return Err(HttpRequestError::ResponseTooBig);
+        }
+
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(body)
+}
+
 fn now() -> Timestamp {
     Timestamp::from(SystemTime::now())
 }
 
-fn log(message: String) {
-    eprintln!("Provider log: {message}");
+fn emit_partial(env: &HostEnv, result: Blob) {
+    let invocation_id = env.limiter.current_invocation_id();
+    env.partial_results.emit(invocation_id, result);
+}
+
+fn log_structured(
+    env: &HostEnv,
+    level: LogLevel,
+    message: String,
+    fields: BTreeMap<String, String>,
+) {
+    let invocation_id = env.limiter.current_invocation_id();
+    env.logger.log(invocation_id, level, &message, &fields);
 }
 
 fn random(len: u32) -> Vec<u8> {
@@ -122,3 +382,101 @@ fn random(len: u32) -> Vec<u8> {
     }
     vec
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::HostPolicy;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    /// Serves a single request with the given raw response and returns the
+    /// base URL of the server, along with a handle that resolves to the
+    /// request line and the body of the request.
+    async fn serve_once(response: &'static [u8]) -> (String, JoinHandle<(String, Vec<u8>)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(stream);
+
+            let mut request_line = String::new();
+            stream.read_line(&mut request_line).await.unwrap();
+
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                stream.read_line(&mut header).await.unwrap();
+                let header = header.trim_end();
+                if header.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+
+            let mut body = vec![0; content_length];
+            stream.read_exact(&mut body).await.unwrap();
+            stream.write_all(response).await.unwrap();
+            stream.shutdown().await.unwrap();
+
+            (request_line.trim_end().to_owned(), body)
+        });
+
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn test_make_http_request_methods() {
+        let mut options = HttpRequest::get("");
+        options.method = HttpRequestMethod::Options;
+        let requests = [
+            (HttpRequest::put("", "put body"), "PUT", "put body"),
+            (HttpRequest::patch("", "patch body"), "PATCH", "patch body"),
+            (options, "OPTIONS", ""),
+        ];
+
+        let client = HttpClient::new(HostPolicy::default());
+        for (mut request, method, body) in requests {
+            let (url, server) =
+                serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok")
+                    .await;
+            request.url = url;
+
+            let response = make_http_request(&client, None, request).await.unwrap();
+            assert_eq!(response.status_code, 200);
+            assert_eq!(response.body.as_ref(), b"ok");
+
+            let (request_line, request_body) = server.await.unwrap();
+            assert_eq!(request_line, format!("{method} / HTTP/1.1"));
+            assert_eq!(request_body, body.as_bytes());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streamed_response_too_big() {
+        // Without a content length, the size is only known while streaming.
+        let (url, server) = serve_once(
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n\
+              a\r\n0123456789\r\na\r\n0123456789\r\n0\r\n\r\n",
+        )
+        .await;
+
+        let client = HttpClient::new(HostPolicy {
+            max_response_size: 16,
+            ..Default::default()
+        });
+        let result = make_http_request(&client, None, HttpRequest::get(url)).await;
+        assert!(
+            matches!(result, Err(HttpRequestError::ResponseTooBig)),
+            "unexpected result: {result:?}"
+        );
+
+        server.await.unwrap();
+    }
+}
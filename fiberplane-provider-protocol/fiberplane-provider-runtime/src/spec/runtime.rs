@@ -1,49 +1,118 @@
-#![allow(unused)]
+//! Runtime that executes a single instance of a provider.
+//!
+//! Unlike the types in `types.rs`, which are generated from the provider
+//! protocol, the runtime is maintained by hand: the Wasmer runtime bindings
+//! that `fp-bindgen` generates cannot pass the state of a runtime to the
+//! functions that are imported from the host. The functions that call into
+//! the provider follow the generated bindings, so they need to be updated
+//! whenever the protocol changes.
+//!
+//! Constructors and functions for specific features are defined next to those
+//! features, such as [Runtime::with_policy()] in [crate::policy] and
+//! [Runtime::with_limits()] in [crate::limits].
+
 use super::types::*;
+use super::{create_import_object, HostEnv};
+use crate::alignment::AlignSignatures;
+use crate::compatibility::check_module;
+use crate::errors::{InvocationError, RuntimeError};
+use crate::limits::Limiter;
+use crate::logging::ProviderLogger;
+use crate::partials::PartialResults;
+use crate::policy::{HostPolicy, HttpClient};
+use crate::recording::HttpRecording;
 use fp_bindgen_support::{
     common::{abi::WasmAbi, mem::FatPtr},
     wasmer2_host::{
-        errors::{InvocationError, RuntimeError},
         mem::{
-            deserialize_from_slice, export_to_guest, export_to_guest_raw, import_from_guest,
-            import_from_guest_raw, serialize_to_vec,
+            deserialize_from_slice, export_to_guest_raw, import_from_guest_raw, serialize_to_vec,
         },
-        r#async::{create_future_value, future::ModuleRawFuture, resolve_async_value},
+        r#async::future::ModuleRawFuture,
         runtime::RuntimeInstanceData,
     },
 };
-use std::cell::RefCell;
-use wasmer::{imports, Function, ImportObject, Instance, Module, Store, WasmerEnv};
+use std::sync::{Arc, RwLock};
+use tokio::sync::mpsc::UnboundedSender;
+use wasmer::{CompilerConfig, Engine, Instance, Module, Store, WasmerEnv};
 
 #[derive(Clone)]
 pub struct Runtime {
-    instance: Instance,
+    pub(crate) instance: Instance,
     env: RuntimeInstanceData,
+    pub(crate) limiter: Arc<Limiter>,
+    pub(crate) logger: Arc<ProviderLogger>,
+    partial_results: Arc<PartialResults>,
+    pub(crate) recording: Arc<RwLock<Option<HttpRecording>>>,
 }
 
 impl Runtime {
     pub fn new(wasm_module: impl AsRef<[u8]>) -> Result<Self, RuntimeError> {
-        let store = Self::default_store();
-        let module = Module::new(&store, wasm_module)?;
+        Self::with_policy(wasm_module, HostPolicy::default())
+    }
+
+    /// Instantiates a module that was compiled with [Self::default_store()].
+    pub(crate) fn instantiate(
+        module: &Module,
+        http_client: Arc<HttpClient>,
+        limiter: Arc<Limiter>,
+    ) -> Result<Self, RuntimeError> {
         let mut env = RuntimeInstanceData::default();
-        let import_object = create_import_object(module.store(), &env);
-        let instance = Instance::new(&module, &import_object).unwrap();
-        env.init_with_instance(&instance).unwrap();
-        Ok(Self { instance, env })
+        let logger = Arc::new(ProviderLogger::default());
+        let partial_results = Arc::new(PartialResults::default());
+        let recording = Arc::new(RwLock::new(None));
+        let host_env = HostEnv::new(
+            env.clone(),
+            http_client,
+            limiter.clone(),
+            logger.clone(),
+            partial_results.clone(),
+            recording.clone(),
+        );
+        let import_object = create_import_object(module.store(), &host_env);
+        check_module(module, &import_object)?;
+        let instance = Instance::new(module, &import_object)?;
+        env.init_with_instance(&instance)
+            .and_then(|_| limiter.init_with_instance(&instance))
+            .map_err(wasmer::InstantiationError::HostEnvInitialization)?;
+        Ok(Self {
+            instance,
+            env,
+            limiter,
+            logger,
+            partial_results,
+            recording,
+        })
     }
 
+    /// Name of the compiler that is used by [Self::default_store()].
+    #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+    pub(crate) const COMPILER: &'static str = "cranelift";
+
+    #[cfg(not(any(target_arch = "arm", target_arch = "aarch64")))]
+    pub(crate) const COMPILER: &'static str = "singlepass";
+
     #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
-    fn default_store() -> wasmer::Store {
-        let compiler = wasmer::Cranelift::default();
+    pub(crate) fn default_store(limiter: &Limiter) -> wasmer::Store {
+        let mut compiler = wasmer::Cranelift::default();
+        compiler.push_middleware(Arc::new(AlignSignatures));
+        if let Some(metering) = limiter.metering() {
+            compiler.push_middleware(metering);
+        }
         let engine = wasmer::Universal::new(compiler).engine();
-        Store::new(&engine)
+        let tunables = limiter.tunables(engine.target());
+        Store::new_with_tunables(&engine, tunables)
     }
 
     #[cfg(not(any(target_arch = "arm", target_arch = "aarch64")))]
-    fn default_store() -> wasmer::Store {
-        let compiler = wasmer::Singlepass::default();
+    pub(crate) fn default_store(limiter: &Limiter) -> wasmer::Store {
+        let mut compiler = wasmer::Singlepass::default();
+        compiler.push_middleware(Arc::new(AlignSignatures));
+        if let Some(metering) = limiter.metering() {
+            compiler.push_middleware(metering);
+        }
         let engine = wasmer::Universal::new(compiler).engine();
-        Store::new(&engine)
+        let tunables = limiter.tunables(engine.target());
+        Store::new_with_tunables(&engine, tunables)
     }
 
     /// Creates output cells based on the response.
@@ -76,6 +145,8 @@ impl Runtime {
         query_type: Vec<u8>,
         response: Vec<u8>,
     ) -> Result<Vec<u8>, InvocationError> {
+        let invocation = self.limiter.start_invocation()?;
+        let entered = invocation.enter();
         let query_type = export_to_guest_raw(&self.env, query_type);
         let response = export_to_guest_raw(&self.env, response);
         let function = self
@@ -85,7 +156,9 @@ impl Runtime {
             .map_err(|_| {
                 InvocationError::FunctionNotExported("__fp_gen_create_cells".to_owned())
             })?;
-        let result = function.call(query_type.to_abi(), response.to_abi())?;
+        let result = function
+            .call(query_type.to_abi(), response.to_abi())
+            .map_err(|error| entered.to_invocation_error(error))?;
         let result = import_from_guest_raw(&self.env, result);
         Ok(result)
     }
@@ -122,6 +195,8 @@ impl Runtime {
         mime_type: Vec<u8>,
         query: Vec<u8>,
     ) -> Result<Vec<u8>, InvocationError> {
+        let invocation = self.limiter.start_invocation()?;
+        let entered = invocation.enter();
         let response = export_to_guest_raw(&self.env, response);
         let mime_type = export_to_guest_raw(&self.env, mime_type);
         let query = export_to_guest_raw(&self.env, query);
@@ -132,7 +207,9 @@ impl Runtime {
             .map_err(|_| {
                 InvocationError::FunctionNotExported("__fp_gen_extract_data".to_owned())
             })?;
-        let result = function.call(response.to_abi(), mime_type.to_abi(), query.to_abi())?;
+        let result = function
+            .call(response.to_abi(), mime_type.to_abi(), query.to_abi())
+            .map_err(|error| entered.to_invocation_error(error))?;
         let result = import_from_guest_raw(&self.env, result);
         Ok(result)
     }
@@ -153,6 +230,8 @@ impl Runtime {
     }
 
     pub fn get_config_schema_raw(&self) -> Result<Vec<u8>, InvocationError> {
+        let invocation = self.limiter.start_invocation()?;
+        let entered = invocation.enter();
         let function = self
             .instance
             .exports
@@ -160,7 +239,9 @@ impl Runtime {
             .map_err(|_| {
                 InvocationError::FunctionNotExported("__fp_gen_get_config_schema".to_owned())
             })?;
-        let result = function.call()?;
+        let result = function
+            .call()
+            .map_err(|error| entered.to_invocation_error(error))?;
         let result = import_from_guest_raw(&self.env, result);
         Ok(result)
     }
@@ -183,6 +264,8 @@ impl Runtime {
         &self,
         config: Vec<u8>,
     ) -> Result<Vec<u8>, InvocationError> {
+        let invocation = self.limiter.start_invocation()?;
+        let entered = invocation.enter();
         let config = export_to_guest_raw(&self.env, config);
         let function = self
             .instance
@@ -193,8 +276,12 @@ impl Runtime {
                     "__fp_gen_get_supported_query_types".to_owned(),
                 )
             })?;
-        let result = function.call(config.to_abi())?;
-        let result = ModuleRawFuture::new(self.env.clone(), result).await;
+        let result = function
+            .call(config.to_abi())
+            .map_err(|error| entered.to_invocation_error(error))?;
+        drop(entered);
+        let result = ModuleRawFuture::new(self.env.clone(), result);
+        let result = self.limiter.complete(invocation, result).await?;
         Ok(result)
     }
 
@@ -210,53 +297,32 @@ impl Runtime {
     }
 
     pub async fn invoke2_raw(&self, request: Vec<u8>) -> Result<Vec<u8>, InvocationError> {
+        self.invoke2_with_partial_results(request, None).await
+    }
+
+    /// Invokes the provider to perform a data request, passing the partial
+    /// results it emits to the given sender, if any.
+    pub(crate) async fn invoke2_with_partial_results(
+        &self,
+        request: Vec<u8>,
+        partial_results: Option<UnboundedSender<Blob>>,
+    ) -> Result<Vec<u8>, InvocationError> {
+        let invocation = self.limiter.start_invocation()?;
+        let _registration =
+            partial_results.map(|sender| self.partial_results.register(invocation.id(), sender));
+        let entered = invocation.enter();
         let request = export_to_guest_raw(&self.env, request);
         let function = self
             .instance
             .exports
             .get_native_function::<FatPtr, FatPtr>("__fp_gen_invoke2")
             .map_err(|_| InvocationError::FunctionNotExported("__fp_gen_invoke2".to_owned()))?;
-        let result = function.call(request.to_abi())?;
-        let result = ModuleRawFuture::new(self.env.clone(), result).await;
+        let result = function
+            .call(request.to_abi())
+            .map_err(|error| entered.to_invocation_error(error))?;
+        drop(entered);
+        let result = ModuleRawFuture::new(self.env.clone(), result);
+        let result = self.limiter.complete(invocation, result).await?;
         Ok(result)
     }
 }
-
-fn create_import_object(store: &Store, env: &RuntimeInstanceData) -> ImportObject {
-    imports! {
-        "fp" => {
-            "__fp_host_resolve_async_value" => Function::new_native_with_env(store, env.clone(), resolve_async_value),
-            "__fp_gen_log" => Function::new_native_with_env(store, env.clone(), _log),
-            "__fp_gen_make_http_request" => Function::new_native_with_env(store, env.clone(), _make_http_request),
-            "__fp_gen_now" => Function::new_native_with_env(store, env.clone(), _now),
-            "__fp_gen_random" => Function::new_native_with_env(store, env.clone(), _random),
-        }
-    }
-}
-
-pub fn _log(env: &RuntimeInstanceData, message: FatPtr) {
-    let message = import_from_guest::<String>(env, message);
-    super::log(message)
-}
-
-pub fn _make_http_request(env: &RuntimeInstanceData, request: FatPtr) -> FatPtr {
-    let request = import_from_guest::<HttpRequest>(env, request);
-    let env = env.clone();
-    let async_ptr = create_future_value(&env);
-    let handle = tokio::runtime::Handle::current();
-    handle.spawn(async move {
-        let result = super::make_http_request(request).await;
-        let result_ptr = export_to_guest(&env, &result);
-        env.guest_resolve_async_value(async_ptr, result_ptr);
-    });
-    async_ptr
-}
-
-pub fn _now(env: &RuntimeInstanceData) -> FatPtr {
-    export_to_guest(env, &super::now())
-}
-
-pub fn _random(env: &RuntimeInstanceData, len: <u32 as WasmAbi>::AbiType) -> FatPtr {
-    let len = WasmAbi::from_abi(len);
-    export_to_guest(env, &super::random(len))
-}
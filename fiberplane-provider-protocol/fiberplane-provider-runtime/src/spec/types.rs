@@ -47,6 +47,7 @@ pub use fiberplane_models::providers::HttpRequestMethod;
 pub use fiberplane_models::providers::HttpResponse;
 pub use fiberplane_models::providers::IntegerField;
 pub use fiberplane_models::providers::LabelField;
+pub use fiberplane_models::providers::LogLevel;
 pub use fiberplane_models::providers::Metric;
 pub use fiberplane_models::providers::OtelMetadata;
 pub use fiberplane_models::providers::OtelSeverityNumber;
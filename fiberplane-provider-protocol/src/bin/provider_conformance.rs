@@ -0,0 +1,140 @@
+use clap::Parser;
+use fiberplane_provider_protocol::conformance::{
+    check_provider, CheckOutcome, ConformanceOptions, SampleQuery,
+};
+use fiberplane_provider_runtime::limits::RuntimeLimits;
+use fiberplane_provider_runtime::policy::HostPolicy;
+use fiberplane_provider_runtime::recording::HttpRecording;
+use fiberplane_provider_runtime::spec::Runtime;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+/// Load a provider and check whether it follows the contract of the provider
+/// protocol
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Path to the Wasm module of the provider
+    provider: PathBuf,
+
+    /// JSON file with the config that is passed to the provider
+    #[clap(short, long)]
+    config: Option<PathBuf>,
+
+    /// Sample query, as `<query type>=<form-encoded query data>`, of which
+    /// the response is used for checking `extract_data()` and `create_cells()`
+    #[clap(short, long = "query", value_parser = parse_sample_query)]
+    queries: Vec<SampleQuery>,
+
+    /// Fixture file from which the HTTP responses of the provider are
+    /// replayed, instead of making requests
+    #[clap(short, long)]
+    replay: Option<PathBuf>,
+
+    /// Maximum duration of a single invocation, in seconds. Only enforced
+    /// while the provider waits for an async value, such as an HTTP response
+    #[clap(short, long, default_value_t = 30)]
+    timeout: u64,
+
+    /// Maximum number of Wasm instructions a single invocation can execute,
+    /// which stops providers that never yield back to the runtime
+    #[clap(short, long, default_value_t = 10_000_000_000)]
+    fuel: u64,
+}
+
+fn parse_sample_query(query: &str) -> Result<SampleQuery, String> {
+    let (query_type, query_data) = query
+        .split_once('=')
+        .ok_or("expected `<query type>=<query data>`")?;
+    Ok(SampleQuery::form_encoded(query_type, query_data))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let runtime = match create_runtime(&args) {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let config = match &args.config {
+        Some(path) => match fs::read(path)
+            .map_err(|err| err.to_string())
+            .and_then(|json| serde_json::from_slice(&json).map_err(|err| err.to_string()))
+        {
+            Ok(config) => config,
+            Err(err) => {
+                eprintln!("error: could not read config {}: {err}", path.display());
+                return ExitCode::FAILURE;
+            }
+        },
+        None => ConformanceOptions::default().config,
+    };
+
+    let options = ConformanceOptions {
+        config,
+        sample_queries: args.queries,
+    };
+    let results = check_provider(&runtime, &options).await;
+
+    let mut failures = 0;
+    for result in &results {
+        match &result.outcome {
+            CheckOutcome::Passed => println!("ok       {}", result.name),
+            CheckOutcome::Skipped(reason) => println!("skipped  {}: {reason}", result.name),
+            CheckOutcome::Failed(reason) => {
+                println!("FAILED   {}", result.name);
+                for line in reason.lines() {
+                    println!("         {line}");
+                }
+                failures += 1;
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "{} passed, {failures} failed, {} skipped",
+        results
+            .iter()
+            .filter(|result| result.outcome == CheckOutcome::Passed)
+            .count(),
+        results
+            .iter()
+            .filter(|result| matches!(result.outcome, CheckOutcome::Skipped(_)))
+            .count(),
+    );
+
+    if failures > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn create_runtime(args: &Args) -> Result<Runtime, String> {
+    let wasm_module = fs::read(&args.provider)
+        .map_err(|err| format!("could not read {}: {err}", args.provider.display()))?;
+
+    let limits = RuntimeLimits {
+        fuel: Some(args.fuel),
+        timeout: Some(Duration::from_secs(args.timeout)),
+        ..Default::default()
+    };
+    let runtime = Runtime::with_limits(wasm_module, HostPolicy::default(), limits)
+        .map_err(|err| err.to_string())?;
+
+    if let Some(path) = &args.replay {
+        let recording = HttpRecording::replay(path)
+            .map_err(|err| format!("could not read {}: {err}", path.display()))?;
+        runtime.set_recording(recording);
+    }
+
+    Ok(runtime)
+}
@@ -0,0 +1,792 @@
+//! Conformance checks for Fiberplane Providers.
+//!
+//! This module loads a provider into a
+//! [Runtime](fiberplane_provider_runtime::spec::Runtime) and checks whether it
+//! follows the contract of the provider protocol:
+//!
+//! - `get_supported_query_types()` returns query types that can be parsed,
+//!   including their query schemas.
+//! - Providers that support suggestions use the schema of
+//!   [AutoSuggestRequest::schema()], and reject invalid auto-suggest requests.
+//! - The responses to sample queries can be converted to every MIME type that
+//!   is advertised for their query type using `extract_data()`.
+//! - The cells returned by `create_cells()` can be parsed, and have unique IDs.
+//!
+//! Checks that need a response from the provider are only run for the query
+//! types for which [SampleQuery]s are given. If the provider is stopped because
+//! it exceeds the limits of its runtime, the remaining checks are skipped. To run these checks without
+//! access to the systems the provider queries, replay a recording of its HTTP
+//! traffic using an `HttpRecording`.
+//!
+//! The `fiberplane-provider-conformance` binary of this crate runs the checks
+//! from the command line.
+//!
+//! ```rust,no_run
+//! use fiberplane_provider_protocol::conformance::{check_provider, ConformanceOptions, SampleQuery};
+//! use fiberplane_provider_runtime::spec::Runtime;
+//!
+//! # async fn run() {
+//! let runtime = Runtime::new(std::fs::read("provider.wasm").unwrap()).unwrap();
+//! let options = ConformanceOptions {
+//!     sample_queries: vec![SampleQuery::form_encoded("timeseries", "query=up")],
+//!     ..Default::default()
+//! };
+//!
+//! for result in check_provider(&runtime, &options).await {
+//!     println!("{}: {:?}", result.name, result.outcome);
+//! }
+//! # }
+//! ```
+
+use fiberplane_models::providers::{
+    AutoSuggestRequest, CELLS_MIME_TYPE, FORM_ENCODED_MIME_TYPE, SUGGESTIONS_MIME_TYPE,
+    SUGGESTIONS_QUERY_TYPE,
+};
+use fiberplane_provider_runtime::spec::{
+    Blob, Cell, Error, ProviderConfig, ProviderRequest, Runtime, Suggestion, SupportedQueryType,
+};
+use fp_bindgen_support::wasmer2_host::mem::serialize_to_vec;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeSet;
+
+/// Options for checking a provider.
+#[derive(Clone, Debug)]
+pub struct ConformanceOptions {
+    /// Config that is passed to the provider.
+    pub config: ProviderConfig,
+
+    /// Queries of which the responses are used for checking `extract_data()`
+    /// and `create_cells()`.
+    pub sample_queries: Vec<SampleQuery>,
+}
+
+impl Default for ConformanceOptions {
+    fn default() -> Self {
+        Self {
+            config: ProviderConfig::Object(Default::default()),
+            sample_queries: Vec::new(),
+        }
+    }
+}
+
+/// Query with which the provider is invoked to obtain a sample response.
+#[derive(Clone, Debug)]
+pub struct SampleQuery {
+    pub query_type: String,
+    pub query_data: Blob,
+}
+
+impl SampleQuery {
+    /// Creates a sample query with form-encoded query data, such as
+    /// `query=up&time_range=...`.
+    pub fn form_encoded(query_type: impl Into<String>, query_data: impl Into<String>) -> Self {
+        Self {
+            query_type: query_type.into(),
+            query_data: Blob::builder()
+                .data(query_data.into())
+                .mime_type(FORM_ENCODED_MIME_TYPE)
+                .build(),
+        }
+    }
+}
+
+/// Result of a single conformance check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckResult {
+    /// Description of what was checked.
+    pub name: String,
+    pub outcome: CheckOutcome,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CheckOutcome {
+    /// The provider follows the contract.
+    Passed,
+
+    /// The provider does not follow the contract, for the given reason.
+    Failed(String),
+
+    /// The check could not be run, for the given reason.
+    Skipped(String),
+}
+
+impl CheckOutcome {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Self::Failed(_))
+    }
+}
+
+/// Runs all conformance checks against the provider that is loaded in the
+/// runtime.
+///
+/// Checks that depend on the query types of the provider are not run if
+/// `get_supported_query_types()` fails. Once an invocation is stopped because
+/// of the [RuntimeLimits](fiberplane_provider_runtime::limits::RuntimeLimits)
+/// of the runtime, the remaining checks are skipped, since the state of the
+/// provider is unspecified after that.
+pub async fn check_provider(runtime: &Runtime, options: &ConformanceOptions) -> Vec<CheckResult> {
+    let mut checks = Checks::default();
+
+    let Some(query_types) = check_supported_query_types(runtime, options, &mut checks).await else {
+        return checks.results;
+    };
+
+    if let Some(query_type) = query_types
+        .iter()
+        .find(|query_type| query_type.query_type == SUGGESTIONS_QUERY_TYPE)
+    {
+        check_suggestions(runtime, options, query_type, &mut checks).await;
+    }
+
+    for sample in &options.sample_queries {
+        if checks.stopped(runtime) {
+            return checks.results;
+        }
+
+        let name = format!("sample query for `{}` is supported", sample.query_type);
+        match query_types
+            .iter()
+            .find(|query_type| query_type.query_type == sample.query_type)
+        {
+            Some(query_type) => {
+                check_sample_query(runtime, options, query_type, sample, &mut checks).await
+            }
+            None => checks.fail(name, "the provider does not advertise this query type"),
+        }
+    }
+
+    if checks.stopped(runtime) {
+        return checks.results;
+    }
+
+    for query_type in &query_types {
+        let has_sample = options
+            .sample_queries
+            .iter()
+            .any(|sample| sample.query_type == query_type.query_type);
+        if !has_sample {
+            checks.skip(
+                format!("responses to `{}` queries", query_type.query_type),
+                "no sample query was given",
+            );
+        }
+    }
+
+    checks.results
+}
+
+#[derive(Default)]
+struct Checks {
+    results: Vec<CheckResult>,
+    stopped: bool,
+}
+
+impl Checks {
+    fn record(&mut self, name: impl Into<String>, result: Result<(), String>) {
+        let outcome = match result {
+            Ok(()) => CheckOutcome::Passed,
+            Err(reason) => CheckOutcome::Failed(reason),
+        };
+        self.results.push(CheckResult {
+            name: name.into(),
+            outcome,
+        });
+    }
+
+    fn fail(&mut self, name: impl Into<String>, reason: impl Into<String>) {
+        self.record(name, Err(reason.into()));
+    }
+
+    fn skip(&mut self, name: impl Into<String>, reason: impl Into<String>) {
+        self.results.push(CheckResult {
+            name: name.into(),
+            outcome: CheckOutcome::Skipped(reason.into()),
+        });
+    }
+
+    /// Returns whether the runtime has stopped the provider, in which case no
+    /// more checks should be run. The first time, the remaining checks are
+    /// reported as skipped.
+    fn stopped(&mut self, runtime: &Runtime) -> bool {
+        if runtime.has_stopped() && !self.stopped {
+            self.stopped = true;
+            self.skip(
+                "remaining checks",
+                "the provider was stopped because it exceeded the limits of the runtime",
+            );
+        }
+        self.stopped
+    }
+}
+
+async fn check_supported_query_types(
+    runtime: &Runtime,
+    options: &ConformanceOptions,
+    checks: &mut Checks,
+) -> Option<Vec<SupportedQueryType>> {
+    let result = runtime
+        .get_supported_query_types_raw(serialize_to_vec(&options.config))
+        .await
+        .map_err(|error| format!("invocation failed: {error}"))
+        .and_then(|data| decode::<Vec<SupportedQueryType>>(&data));
+
+    let query_types = match result {
+        Ok(query_types) => query_types,
+        Err(reason) => {
+            checks.fail("get_supported_query_types() returns query types", reason);
+            return None;
+        }
+    };
+    checks.record("get_supported_query_types() returns query types", Ok(()));
+
+    let mut seen = BTreeSet::new();
+    let duplicates: Vec<_> = query_types
+        .iter()
+        .filter(|query_type| !seen.insert(query_type.query_type.as_str()))
+        .map(|query_type| query_type.query_type.as_str())
+        .collect();
+    checks.record(
+        "query types are unique and not empty",
+        if seen.contains("") {
+            Err("a query type is empty".to_owned())
+        } else if !duplicates.is_empty() {
+            Err(format!("duplicate query types: {}", duplicates.join(", ")))
+        } else {
+            Ok(())
+        },
+    );
+
+    Some(query_types)
+}
+
+async fn check_suggestions(
+    runtime: &Runtime,
+    options: &ConformanceOptions,
+    query_type: &SupportedQueryType,
+    checks: &mut Checks,
+) {
+    checks.record(
+        "suggestions use the schema of AutoSuggestRequest",
+        if query_type.schema == AutoSuggestRequest::schema() {
+            Ok(())
+        } else {
+            Err(format!(
+                "expected {:?}, got {:?}",
+                AutoSuggestRequest::schema(),
+                query_type.schema
+            ))
+        },
+    );
+
+    checks.record(
+        "suggestions advertise the suggestions MIME type",
+        if query_type
+            .mime_types
+            .iter()
+            .any(|mime_type| mime_type.starts_with(SUGGESTIONS_MIME_TYPE))
+        {
+            Ok(())
+        } else {
+            Err(format!("`{SUGGESTIONS_MIME_TYPE}` is missing"))
+        },
+    );
+
+    // A request without a field and query type is not a valid auto-suggest
+    // request, so the provider should reject it without making any requests.
+    let invalid_request = SampleQuery::form_encoded(SUGGESTIONS_QUERY_TYPE, "query=");
+    checks.record(
+        "invalid auto-suggest requests are rejected",
+        match invoke(runtime, options, &invalid_request).await {
+            Ok(Err(Error::ValidationError { .. } | Error::UnsupportedRequest)) => Ok(()),
+            Ok(Err(error)) => Err(format!(
+                "expected a validation error, got another error: {error}"
+            )),
+            Ok(Ok(_)) => Err("the provider returned suggestions".to_owned()),
+            Err(reason) => Err(reason),
+        },
+    );
+}
+
+async fn check_sample_query(
+    runtime: &Runtime,
+    options: &ConformanceOptions,
+    query_type: &SupportedQueryType,
+    sample: &SampleQuery,
+    checks: &mut Checks,
+) {
+    let name = |check: &str| format!("`{}` {check}", sample.query_type);
+
+    if sample.query_type == SUGGESTIONS_QUERY_TYPE {
+        if let Err(error) = AutoSuggestRequest::parse(sample.query_data.clone()) {
+            checks.fail(
+                name("sample query is an auto-suggest request"),
+                error.to_string(),
+            );
+            return;
+        }
+    }
+
+    let response = match invoke(runtime, options, sample).await {
+        Ok(Ok(response)) => {
+            checks.record(name("query returns a response"), Ok(()));
+            response
+        }
+        Ok(Err(error)) => {
+            checks.fail(
+                name("query returns a response"),
+                format!("the provider returned an error: {error}"),
+            );
+            return;
+        }
+        Err(reason) => {
+            checks.fail(name("query returns a response"), reason);
+            return;
+        }
+    };
+
+    if sample.query_type == SUGGESTIONS_QUERY_TYPE {
+        checks.record(
+            name("response contains suggestions"),
+            decode_blob::<Vec<Suggestion>>(&response, SUGGESTIONS_MIME_TYPE).map(|_| ()),
+        );
+        return;
+    }
+
+    for mime_type in &query_type.mime_types {
+        if checks.stopped(runtime) {
+            return;
+        }
+
+        checks.record(
+            name(&format!("response can be extracted as `{mime_type}`")),
+            check_extract_data(runtime, &response, mime_type),
+        );
+    }
+
+    if checks.stopped(runtime) {
+        return;
+    }
+
+    checks.record(
+        name("response can be converted to cells"),
+        check_create_cells(runtime, &sample.query_type, &response),
+    );
+}
+
+async fn invoke(
+    runtime: &Runtime,
+    options: &ConformanceOptions,
+    query: &SampleQuery,
+) -> Result<Result<Blob, Error>, String> {
+    let request = ProviderRequest::builder()
+        .query_type(query.query_type.clone())
+        .query_data(query.query_data.clone())
+        .config(options.config.clone())
+        .build();
+
+    let data = runtime
+        .invoke2_raw(serialize_to_vec(&request))
+        .await
+        .map_err(|error| format!("invocation failed: {error}"))?;
+    decode(&data)
+}
+
+fn check_extract_data(runtime: &Runtime, response: &Blob, mime_type: &str) -> Result<(), String> {
+    let data = runtime
+        .extract_data_raw(
+            serialize_to_vec(response),
+            serialize_to_vec(&mime_type),
+            serialize_to_vec(&None::<String>),
+        )
+        .map_err(|error| format!("invocation failed: {error}"))?;
+
+    match decode::<Result<Blob, Error>>(&data)? {
+        Ok(extracted) if extracted.mime_type != mime_type => Err(format!(
+            "returned data of type `{}` instead",
+            extracted.mime_type
+        )),
+        // Studio skips the call if the response already has the requested
+        // type, which should not change the outcome.
+        Ok(extracted) if response.mime_type == mime_type && &extracted != response => {
+            Err("returned different data than the response of the same type".to_owned())
+        }
+        Ok(_) => Ok(()),
+        Err(Error::UnsupportedRequest) => {
+            Err("the advertised MIME type is not supported".to_owned())
+        }
+        Err(error) => Err(format!("the provider returned an error: {error}")),
+    }
+}
+
+fn check_create_cells(runtime: &Runtime, query_type: &str, response: &Blob) -> Result<(), String> {
+    // Studio parses responses of the cells type directly, without calling
+    // `create_cells()`.
+    let cells = if response.mime_type.starts_with(CELLS_MIME_TYPE) {
+        decode_blob::<Vec<Cell>>(response, CELLS_MIME_TYPE)?
+    } else {
+        let data = runtime
+            .create_cells_raw(serialize_to_vec(&query_type), serialize_to_vec(response))
+            .map_err(|error| format!("invocation failed: {error}"))?;
+        decode::<Result<Vec<Cell>, Error>>(&data)?
+            .map_err(|error| format!("the provider returned an error: {error}"))?
+    };
+
+    check_cell_ids(&cells)
+}
+
+fn check_cell_ids(cells: &[Cell]) -> Result<(), String> {
+    let mut ids = BTreeSet::new();
+    for cell in cells {
+        if cell.id().is_empty() {
+            return Err("a cell has an empty ID".to_owned());
+        }
+        if !ids.insert(cell.id()) {
+            return Err(format!("multiple cells have the ID `{}`", cell.id()));
+        }
+    }
+    Ok(())
+}
+
+/// Decodes a value that was returned by the provider, the same way the runtime
+/// does.
+fn decode<T: DeserializeOwned>(data: &[u8]) -> Result<T, String> {
+    let mut deserializer = rmp_serde::Deserializer::new(data).with_human_readable();
+    T::deserialize(&mut deserializer).map_err(|error| format!("invalid return value: {error}"))
+}
+
+/// Decodes a blob of the given MIME type, encoded as either JSON or
+/// MessagePack.
+fn decode_blob<T: DeserializeOwned>(blob: &Blob, mime_type: &str) -> Result<T, String> {
+    match blob.mime_type.strip_prefix(mime_type) {
+        Some("+json") => serde_json::from_slice(&blob.data)
+            .map_err(|error| format!("invalid `{}` data: {error}", blob.mime_type)),
+        Some("+msgpack") => rmp_serde::from_slice(&blob.data)
+            .map_err(|error| format!("invalid `{}` data: {error}", blob.mime_type)),
+        _ => Err(format!(
+            "expected `{mime_type}+json` or `{mime_type}+msgpack`, got `{}`",
+            blob.mime_type
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fiberplane_models::notebooks::TextCell;
+    use fiberplane_models::providers::TextField;
+    use fiberplane_provider_runtime::limits::RuntimeLimits;
+    use fiberplane_provider_runtime::policy::HostPolicy;
+    use std::fmt::Write;
+
+    #[test]
+    fn test_decode_blob() {
+        let suggestion = Suggestion::builder()
+            .from(None)
+            .text("up".to_owned())
+            .description(None)
+            .build();
+        let json = Blob::builder()
+            .data(serde_json::to_vec(&vec![suggestion.clone()]).unwrap())
+            .mime_type(format!("{SUGGESTIONS_MIME_TYPE}+json"))
+            .build();
+        let msgpack = Blob::builder()
+            .data(rmp_serde::to_vec_named(&vec![suggestion.clone()]).unwrap())
+            .mime_type(format!("{SUGGESTIONS_MIME_TYPE}+msgpack"))
+            .build();
+
+        for blob in [json, msgpack] {
+            let suggestions: Vec<Suggestion> = decode_blob(&blob, SUGGESTIONS_MIME_TYPE).unwrap();
+            assert_eq!(suggestions, vec![suggestion.clone()]);
+        }
+
+        let other = Blob::builder()
+            .data("[]")
+            .mime_type("application/json")
+            .build();
+        assert!(decode_blob::<Vec<Suggestion>>(&other, SUGGESTIONS_MIME_TYPE).is_err());
+    }
+
+    #[test]
+    fn test_check_cell_ids() {
+        let cell = |id: &str| Cell::Text(TextCell::builder().id(id.to_owned()).content("").build());
+
+        assert_eq!(check_cell_ids(&[cell("a"), cell("b")]), Ok(()));
+        assert!(check_cell_ids(&[cell("a"), cell("a")]).is_err());
+        assert!(check_cell_ids(&[cell("")]).is_err());
+    }
+
+    /// Data segments of a provider, starting at a fixed offset in its memory.
+    struct Segments {
+        wat: String,
+        offset: u32,
+    }
+
+    impl Segments {
+        fn new() -> Self {
+            Self {
+                wat: String::new(),
+                offset: 1024,
+            }
+        }
+
+        /// Adds a segment with the given bytes, and returns a fat pointer to
+        /// it.
+        fn add(&mut self, bytes: &[u8]) -> u64 {
+            write!(self.wat, r#"(data (i32.const {}) ""#, self.offset).unwrap();
+            for byte in bytes {
+                write!(self.wat, "\\{byte:02x}").unwrap();
+            }
+            self.wat.push_str("\")\n");
+
+            let fat_ptr = (self.offset as u64) << 32 | bytes.len() as u64;
+            self.offset += (bytes.len() as u32 + 7) / 8 * 8;
+            fat_ptr
+        }
+
+        /// Adds a future that has resolved to the given value, and returns a
+        /// fat pointer to the future.
+        fn add_future(&mut self, value: &[u8]) -> u64 {
+            let value = self.add(value);
+            let future: Vec<u8> = [1, (value >> 32) as u32, value as u32]
+                .iter()
+                .flat_map(|word| word.to_le_bytes())
+                .collect();
+            self.add(&future)
+        }
+    }
+
+    /// Returns a provider that ignores its arguments and always returns the
+    /// given values, which are already serialized.
+    fn provider(query_types: &[u8], response: &[u8], extracted: &[u8], cells: &[u8]) -> Runtime {
+        Runtime::new(provider_wat(query_types, response, extracted, cells)).unwrap()
+    }
+
+    /// Returns the module of a [provider()].
+    fn provider_wat(query_types: &[u8], response: &[u8], extracted: &[u8], cells: &[u8]) -> String {
+        let mut segments = Segments::new();
+        let query_types = segments.add_future(query_types);
+        let response = segments.add_future(response);
+        let extracted = segments.add(extracted);
+        let cells = segments.add(cells);
+
+        // Arguments are written to the second page of memory.
+        format!(
+            r#"
+            (module
+                (memory (export "memory") 2)
+                (func (export "__fp_free") (param i64))
+                (func (export "__fp_guest_resolve_async_value") (param i64 i64))
+                (func (export "__fp_malloc") (param i32) (result i64)
+                    (i64.or (i64.const {arguments}) (i64.extend_i32_u (local.get 0))))
+                (func (export "__fp_gen_get_supported_query_types") (param i64) (result i64)
+                    (i64.const {query_types}))
+                (func (export "__fp_gen_invoke2") (param i64) (result i64)
+                    (i64.const {response}))
+                (func (export "__fp_gen_extract_data") (param i64 i64 i64) (result i64)
+                    (i64.const {extracted}))
+                (func (export "__fp_gen_create_cells") (param i64 i64) (result i64)
+                    (i64.const {cells}))
+                {data})
+            "#,
+            arguments = 65536u64 << 32,
+            data = segments.wat,
+        )
+    }
+
+    fn json_blob(data: &str) -> Blob {
+        Blob::builder()
+            .data(data.to_owned())
+            .mime_type("application/json")
+            .build()
+    }
+
+    fn text_cell(id: &str) -> Cell {
+        Cell::Text(TextCell::builder().id(id.to_owned()).content("").build())
+    }
+
+    fn status_query_types() -> Vec<SupportedQueryType> {
+        vec![SupportedQueryType::new("status").supporting_mime_types(&["application/json"])]
+    }
+
+    fn options() -> ConformanceOptions {
+        ConformanceOptions {
+            sample_queries: vec![SampleQuery::form_encoded("status", "")],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_conforming_provider() {
+        let response = Ok::<_, Error>(json_blob(r#"{"status":"ok"}"#));
+        let runtime = provider(
+            &serialize_to_vec(&status_query_types()),
+            &serialize_to_vec(&response),
+            &serialize_to_vec(&response),
+            &serialize_to_vec(&Ok::<_, Error>(vec![text_cell("a"), text_cell("b")])),
+        );
+
+        let results = check_provider(&runtime, &options()).await;
+        let names: Vec<_> = results.iter().map(|result| result.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "get_supported_query_types() returns query types",
+                "query types are unique and not empty",
+                "`status` query returns a response",
+                "`status` response can be extracted as `application/json`",
+                "`status` response can be converted to cells",
+            ]
+        );
+        assert!(
+            results
+                .iter()
+                .all(|result| result.outcome == CheckOutcome::Passed),
+            "unexpected results: {results:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_malformed_provider() {
+        let mut query_types = status_query_types();
+        query_types.push(SupportedQueryType::new("status"));
+        let extracted = Blob::builder().data("ok").mime_type("text/plain").build();
+        let mut cells = serialize_to_vec(&Ok::<_, Error>(vec![text_cell("a")]));
+        cells.truncate(cells.len() / 2);
+        let runtime = provider(
+            &serialize_to_vec(&query_types),
+            &serialize_to_vec(&Ok::<_, Error>(json_blob(r#"{"status":"ok"}"#))),
+            &serialize_to_vec(&Ok::<_, Error>(extracted)),
+            &cells,
+        );
+
+        let results = check_provider(&runtime, &options()).await;
+        let failures: Vec<_> = results
+            .iter()
+            .filter_map(|result| match &result.outcome {
+                CheckOutcome::Failed(reason) => Some((result.name.as_str(), reason.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(failures.len(), 3, "unexpected results: {results:?}");
+        assert_eq!(
+            failures[0],
+            (
+                "query types are unique and not empty",
+                "duplicate query types: status"
+            )
+        );
+        assert_eq!(
+            failures[1],
+            (
+                "`status` response can be extracted as `application/json`",
+                "returned data of type `text/plain` instead"
+            )
+        );
+        assert_eq!(failures[2].0, "`status` response can be converted to cells");
+        assert!(
+            failures[2].1.starts_with("invalid return value"),
+            "unexpected reason: {}",
+            failures[2].1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_suggestions() {
+        let query_types = |schema, mime_types: &[&str]| {
+            serialize_to_vec(&vec![SupportedQueryType::new(SUGGESTIONS_QUERY_TYPE)
+                .with_schema(schema)
+                .supporting_mime_types(mime_types)])
+        };
+        let options = ConformanceOptions::default();
+
+        let conforming = provider(
+            &query_types(AutoSuggestRequest::schema(), &[SUGGESTIONS_MIME_TYPE]),
+            &serialize_to_vec(&Err::<Blob, _>(Error::ValidationError {
+                errors: Vec::new(),
+            })),
+            &[],
+            &[],
+        );
+        let results = check_provider(&conforming, &options).await;
+        let names: Vec<_> = results.iter().map(|result| result.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "get_supported_query_types() returns query types",
+                "query types are unique and not empty",
+                "suggestions use the schema of AutoSuggestRequest",
+                "suggestions advertise the suggestions MIME type",
+                "invalid auto-suggest requests are rejected",
+                "responses to `suggestions` queries",
+            ]
+        );
+        assert!(
+            results.iter().all(|result| !result.outcome.is_failure()),
+            "unexpected results: {results:?}"
+        );
+
+        let malformed = provider(
+            &query_types(vec![TextField::new().into()], &["application/json"]),
+            &serialize_to_vec(&Ok::<_, Error>(json_blob("[]"))),
+            &[],
+            &[],
+        );
+        let results = check_provider(&malformed, &options).await;
+        let failures: Vec<_> = results
+            .iter()
+            .filter(|result| result.outcome.is_failure())
+            .map(|result| result.name.as_str())
+            .collect();
+        assert_eq!(
+            failures,
+            [
+                "suggestions use the schema of AutoSuggestRequest",
+                "suggestions advertise the suggestions MIME type",
+                "invalid auto-suggest requests are rejected",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stop_after_exceeding_limits() {
+        let mut query_types = status_query_types();
+        query_types[0].mime_types.push("text/plain".to_owned());
+        let response = Ok::<_, Error>(json_blob(r#"{"status":"ok"}"#));
+        let wat = provider_wat(
+            &serialize_to_vec(&query_types),
+            &serialize_to_vec(&response),
+            &serialize_to_vec(&response),
+            &serialize_to_vec(&Ok::<_, Error>(vec![text_cell("a")])),
+        )
+        .replace(
+            r#"(func (export "__fp_gen_extract_data") (param i64 i64 i64) (result i64)"#,
+            r#"(func (export "__fp_gen_extract_data") (param i64 i64 i64) (result i64)
+                    (loop (br 0))"#,
+        );
+        let limits = RuntimeLimits {
+            fuel: Some(1_000_000),
+            ..Default::default()
+        };
+        let runtime = Runtime::with_limits(wat, HostPolicy::default(), limits).unwrap();
+
+        let results = check_provider(&runtime, &options()).await;
+        let names: Vec<_> = results.iter().map(|result| result.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "get_supported_query_types() returns query types",
+                "query types are unique and not empty",
+                "`status` query returns a response",
+                "`status` response can be extracted as `application/json`",
+                "remaining checks",
+            ]
+        );
+        assert_eq!(
+            results[3].outcome,
+            CheckOutcome::Failed(
+                "invocation failed: provider ran out of fuel (limit: 1000000)".to_owned()
+            )
+        );
+        assert!(matches!(results[4].outcome, CheckOutcome::Skipped(_)));
+    }
+}
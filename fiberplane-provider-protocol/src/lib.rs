@@ -0,0 +1,5 @@
+//! Tools for working with providers that implement the provider protocol.
+//!
+//! The protocol itself is defined in the bindings generator, `src/main.rs`.
+
+pub mod conformance;
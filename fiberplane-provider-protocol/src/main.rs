@@ -3,11 +3,32 @@ use fiberplane_models::timestamps::{TimeRange, Timestamp};
 use fiberplane_models::{blobs::Blob, providers::*};
 use fp_bindgen::{prelude::*, types::CargoDependency};
 use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
 
 fp_import! {
     /// Logs a message to the (development) console.
+    ///
+    /// This is equivalent to calling `log_structured()` with the `Info` level
+    /// and without any fields.
     fn log(message: String);
 
+    /// Logs a message with the given level and fields.
+    ///
+    /// The host emits the message together with the name of the provider and
+    /// the ID of the invocation during which it was logged, so it can be
+    /// filtered and correlated.
+    fn log_structured(level: LogLevel, message: String, fields: BTreeMap<String, String>);
+
+    /// Emits a part of the response to the current `invoke2()` call, so the
+    /// host can display it before the provider returns.
+    ///
+    /// Partial results need to have the same MIME type as the final result,
+    /// and are displayed in the order in which they are emitted. Providers may
+    /// only emit partial results if the `accepts_partial_results` field of the
+    /// request is set. Partial results that are emitted otherwise are
+    /// discarded.
+    fn emit_partial(result: Blob);
+
     /// Performs an HTTP request.
     async fn make_http_request(request: HttpRequest) -> Result<HttpResponse, HttpRequestError>;
 
@@ -120,7 +141,7 @@ fn main() {
                     .name("fiberplane-provider-bindings")
                     .description("Fiberplane Provider protocol bindings")
                     .readme("README.md")
-                    .version("2.0.0-beta.11")
+                    .version("2.0.0-beta.13")
                     .authors(RustPluginConfigValue::Workspace)
                     .license(RustPluginConfigValue::Workspace)
                     .dependencies(dependencies)
@@ -128,16 +149,28 @@ fn main() {
             ),
             path,
         });
+        adjust_plugin_bindings(path);
         println!("Rust plugin bindings written to `{path}/`.");
     }
 
     {
-        let path = "./fiberplane-provider-runtime/src/spec";
+        // Only the types are taken from the runtime bindings, because the
+        // generated runtime cannot pass the state of a runtime to the host
+        // functions. See `fiberplane-provider-runtime/src/spec/runtime.rs`.
+        let generated_path = std::env::temp_dir().join("fiberplane-provider-runtime-bindings");
+        let generated_path = generated_path.to_str().expect("invalid temp dir");
         fp_bindgen!(BindingConfig {
             bindings_type: BindingsType::RustWasmer2Runtime,
-            path,
+            path: generated_path,
         });
-        println!("Rust Wasmer runtime bindings written to `{path}/`.");
+
+        let path = "./fiberplane-provider-runtime/src/spec";
+        fs::copy(
+            format!("{generated_path}/types.rs"),
+            format!("{path}/types.rs"),
+        )
+        .expect("could not copy runtime types");
+        println!("Rust Wasmer runtime types written to `{path}/`.");
     }
 
     {
@@ -151,3 +184,46 @@ fn main() {
         println!("TypeScript runtime bindings written to `{path}/`.");
     }
 }
+
+/// Replaces the `lib.rs` that is generated for the plugin bindings, so that
+/// native builds use the functions of the `host` module instead of the Wasm
+/// imports.
+const PLUGIN_BINDINGS_LIB: &str = r#"#![allow(unused_imports)]
+#[rustfmt::skip]
+mod export;
+#[cfg(target_arch = "wasm32")]
+#[rustfmt::skip]
+mod import;
+#[rustfmt::skip]
+mod types;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod host;
+
+pub use export::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use host::{emit_partial, log, log_structured, make_http_request, now, random};
+#[cfg(target_arch = "wasm32")]
+pub use import::*;
+pub use types::*;
+
+pub use fp_bindgen_support::*;
+"#;
+
+/// Makes the adjustments to the plugin bindings that `fp-bindgen` does not
+/// support.
+fn adjust_plugin_bindings(path: &str) {
+    fs::write(format!("{path}/src/lib.rs"), PLUGIN_BINDINGS_LIB)
+        .expect("could not write plugin bindings");
+
+    // `fp-bindgen` does not import the standard types that are used in the
+    // signatures of imported functions.
+    let import_path = format!("{path}/src/import.rs");
+    let import = fs::read_to_string(&import_path).expect("could not read plugin imports");
+    let import = import.replacen(
+        "use crate::types::*;\n",
+        "use crate::types::*;\nuse std::collections::BTreeMap;\n",
+        1,
+    );
+    fs::write(import_path, import).expect("could not write plugin imports");
+}
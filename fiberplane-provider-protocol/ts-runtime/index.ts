@@ -12,7 +12,9 @@ import type * as types from "./types";
 type FatPtr = bigint;
 
 export type Imports = {
+    emitPartial: (result: types.Blob) => void;
     log: (message: string) => void;
+    logStructured: (level: types.LogLevel, message: string, fields: Record<string, string>) => void;
     makeHttpRequest: (request: types.HttpRequest) => Promise<types.Result<types.HttpResponse, types.HttpRequestError>>;
     now: () => types.Timestamp;
     random: (len: number) => Array<number>;
@@ -146,10 +148,20 @@ export async function createRuntime(
 
     const { instance } = await WebAssembly.instantiateStreaming(source, {
         fp: {
+            __fp_gen_emit_partial: (result_ptr: FatPtr) => {
+                const result = parseObject<types.Blob>(result_ptr);
+                importFunctions.emitPartial(result);
+            },
             __fp_gen_log: (message_ptr: FatPtr) => {
                 const message = parseObject<string>(message_ptr);
                 importFunctions.log(message);
             },
+            __fp_gen_log_structured: (level_ptr: FatPtr, message_ptr: FatPtr, fields_ptr: FatPtr) => {
+                const level = parseObject<types.LogLevel>(level_ptr);
+                const message = parseObject<string>(message_ptr);
+                const fields = parseObject<Record<string, string>>(fields_ptr);
+                importFunctions.logStructured(level, message, fields);
+            },
             __fp_gen_make_http_request: (request_ptr: FatPtr): FatPtr => {
                 const request = parseObject<types.HttpRequest>(request_ptr);
                 const _async_result_ptr = createAsyncValue();
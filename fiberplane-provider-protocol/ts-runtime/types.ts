@@ -650,6 +650,16 @@ export type LogCell = {
     highlightedIndices?: Array<LogRecordIndex>;
 };
 
+/**
+ * Severity of a message logged by a provider.
+ */
+export type LogLevel =
+    | "trace"
+    | "debug"
+    | "info"
+    | "warn"
+    | "error";
+
 /**
  * A single expanded row of log records, as identified by [key] and [index]
  * pointing into the source data of the LogCell.
@@ -817,6 +827,16 @@ export type ProviderRequest = {
      * server roundtrip.
      */
     previousResponse?: Blob;
+
+    /**
+     * Whether the host accepts partial results for this request.
+     *
+     * If `true`, the provider may call `emit_partial()` to send parts of the
+     * response as they become available, in which case the result returned
+     * by `invoke2()` only contains the remainder of the response. Otherwise,
+     * the provider needs to return the entire response.
+     */
+    acceptsPartialResults?: boolean;
 };
 
 /**
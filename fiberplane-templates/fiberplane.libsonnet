@@ -17,6 +17,10 @@ local validate = {
   number(name, value):: (self + { types+: ['number'] }).assertType(name, value),
   object(name, value):: (self + { types+: ['object'] }).assertType(name, value),
   array(name, value):: (self + { types+: ['array'] }).assertType(name, value),
+  oneOf(name, value, options)::
+    if std.member(options, value) then
+      value
+    else error 'expected ' + name + ' to be one of: ' + std.join(', ', options),
 };
 local isCell(value) = std.isObject(value) && std.objectHasAll(value, '_class') && value._class == 'CELL';
 local isFormattedContent(value) = std.isObject(value) && std.objectHasAll(value, '_class') && value._class == 'FORMATTED_CONTENT';
@@ -210,9 +214,10 @@ local formattedContent(content='') =
      * @returns {format.FormattedContent}
      */
     mention(userName, userId):: self {
-      content+: '@' + userId,
+      content+: '@' + userName,
       formatting+: [{
         type: 'mention',
+        offset: std.length(super.content),
         name: userName,
         userId: userId,
       }],
@@ -228,6 +233,7 @@ local formattedContent(content='') =
       content+: timestamp,
       formatting+: [{
         type: 'timestamp',
+        offset: std.length(super.content),
         timestamp: timestamp,
       }],
     },
@@ -921,6 +927,156 @@ local cell = {
       content: null,
       url: validate.nullOr.string('url', url),
     },
+
+  // Base type for cells that do not have any text content of their own
+  local contentless = function(type, readOnly)
+    base(type, null, readOnly) + {
+      content:: '',
+      formatting:: [],
+    },
+
+  local validateDataLinks = function(links)
+    std.map(function(link) validate.string('dataLink', link), validate.array('dataLinks', links)),
+
+  local logRecordIndices = function(name, indices)
+    if std.type(indices) == 'null' then
+      null
+    else
+      std.map(function(index) {
+        linkIndex: validate.number(name + '.linkIndex', index.linkIndex),
+        recordIndex: validate.number(name + '.recordIndex', index.recordIndex),
+      }, validate.array(name, indices)),
+
+  /**
+   * Create a table cell
+   *
+   * Columns and rows are automatically given IDs (`col1`, `col2`, ... and
+   * `row1`, `row2`, ...), unless they are given as objects with an explicit `id`.
+   * IDs must be alphanumeric, at least 4 characters long and unique within the table.
+   *
+   * @function cell.table
+   * @param {Array.<(string | {id: string, title: string})>} columns=[] - The column titles
+   * @param {Array.<(Array | {id: string, values: Array})>} rows=[] - The rows of the table.
+   *  Each row is an array with a value for every column, where each value can be
+   *  a string, formatted content or an array of those
+   * @param {boolean} readOnly=false - Whether the cell is locked
+   * @returns {cell.Cell}
+   *
+   * @example c.table(
+   *   columns=['Service', 'Status'],
+   *   rows=[
+   *     ['api', fmt.bold('down')],
+   *     ['web', 'up'],
+   *   ],
+   * )
+   */
+  table(columns=[], rows=[], readOnly=null)::
+    contentless('table', readOnly) + {
+      columnDefs: std.mapWithIndex(function(index, column)
+        if std.isObject(column) then {
+          id: validate.string('column.id', column.id),
+          title: validate.string('column.title', column.title),
+        } else {
+          id: 'col' + (index + 1),
+          title: validate.string('column', column),
+        }, validate.array('columns', columns)),
+      rows: std.mapWithIndex(function(index, row)
+        local values = if std.isObject(row) then row.values else row;
+        {
+          id: if std.isObject(row) then validate.string('row.id', row.id) else 'row' + (index + 1),
+          values: std.map(function(value)
+            local text = formattedContent(value);
+            {
+              type: 'text',
+              text: text.content,
+              formatting: text.formatting,
+            }, validate.array('row', values)),
+        }, validate.array('rows', rows)),
+    },
+
+  /**
+   * Create a timeline cell
+   *
+   * @function cell.timeline
+   * @param {string[]} dataLinks=[] - Links to the data to render in the timeline
+   * @param {boolean} readOnly=false - Whether the cell is locked
+   * @returns {cell.Cell}
+   */
+  timeline(dataLinks=[], readOnly=null)::
+    contentless('timeline', readOnly) + {
+      dataLinks: validateDataLinks(dataLinks),
+    },
+
+  /**
+   * Create a discussion cell
+   *
+   * @function cell.discussion
+   * @param {string} threadId - ID of the discussion thread
+   * @param {boolean} readOnly=false - Whether the cell is locked
+   * @returns {cell.Cell}
+   */
+  discussion(threadId='', readOnly=null)::
+    contentless('discussion', readOnly) + {
+      threadId: validate.string('threadId', threadId),
+    },
+
+  /**
+   * Create a graph cell
+   *
+   * @function cell.graph
+   * @param {string[]} dataLinks=[] - Links to the data to render in the graph
+   * @param {string} graphType='line' - Either `'line'` or `'bar'`
+   * @param {string} stackingType='none' - One of `'none'`, `'stacked'` or `'percentage'`
+   * @param {boolean} readOnly=false - Whether the cell is locked
+   * @returns {cell.Cell}
+   *
+   * @example c.graph(['cell-data:application/vnd.fiberplane.timeseries+json,1'], graphType='bar', stackingType='stacked')
+   */
+  graph(dataLinks=[], graphType='line', stackingType='none', readOnly=null)::
+    contentless('graph', readOnly) + {
+      dataLinks: validateDataLinks(dataLinks),
+      graphType: validate.oneOf('graphType', graphType, ['bar', 'line']),
+      stackingType: validate.oneOf('stackingType', stackingType, ['none', 'stacked', 'percentage']),
+    },
+
+  /**
+   * Create a log cell
+   *
+   * Log records are identified by objects of the form `{ linkIndex: 0, recordIndex: 12 }`,
+   * referring to the index of the data link and the index of the record in its data.
+   *
+   * @function cell.log
+   * @param {string[]} dataLinks=[] - Links to the data to render in the log
+   * @param {string[] | null} displayFields=null - Fields to display for each record
+   * @param {boolean | null} hideSimilarValues=null - Whether to hide values that are the same as in the previous record
+   * @param {string | null} visibilityFilter=null - One of `'all'`, `'selected'` or `'highlighted'`
+   * @param {object[] | null} expandedIndices=null - Records that are expanded
+   * @param {object[] | null} selectedIndices=null - Records that are selected
+   * @param {object[] | null} highlightedIndices=null - Records that are highlighted
+   * @param {boolean} readOnly=false - Whether the cell is locked
+   * @returns {cell.Cell}
+   */
+  log(
+    dataLinks=[],
+    displayFields=null,
+    hideSimilarValues=null,
+    visibilityFilter=null,
+    expandedIndices=null,
+    selectedIndices=null,
+    highlightedIndices=null,
+    readOnly=null,
+  )::
+    contentless('log', readOnly) + {
+      dataLinks: validateDataLinks(dataLinks),
+      displayFields: if std.type(displayFields) == 'null' then null else
+        std.map(function(field) validate.string('displayField', field), validate.array('displayFields', displayFields)),
+      hideSimilarValues: validate.nullOr.boolean('hideSimilarValues', hideSimilarValues),
+      visibilityFilter: if std.type(visibilityFilter) == 'null' then null else
+        validate.oneOf('visibilityFilter', visibilityFilter, ['all', 'selected', 'highlighted']),
+      expandedIndices: logRecordIndices('expandedIndices', expandedIndices),
+      selectedIndices: logRecordIndices('selectedIndices', selectedIndices),
+      highlightedIndices: logRecordIndices('highlightedIndices', highlightedIndices),
+    },
 };
 
 // Create a dummy notebook just to reuse the addCells functionality (which adds cell IDs)
@@ -953,6 +1109,42 @@ local snippet = function(cells)
    * ])
    */
   snippet: snippet,
+  /**
+   * Include a snippet by name. The snippet is looked up by the application
+   * that expands the template.
+   *
+   * If the snippet exports a function, it is called with the arguments that
+   * correspond to its parameters.
+   *
+   * @function useSnippet
+   * @param {string} name - Name of the snippet
+   * @param {object} args - Arguments to pass to the snippet
+   * @returns {cell.Cell[]}
+   *
+   * @example notebook.addCells(fp.useSnippet('oncall-checklist', { service: 'api' }))
+   */
+  useSnippet(name, args={}): std.native('fiberplane.useSnippet')(
+    validate.string('name', name),
+    validate.object('args', args),
+  ),
+  /**
+   * Include a template by name. The template is looked up by the application
+   * that expands the template.
+   *
+   * If the template exports a function, it is called with the arguments that
+   * correspond to its parameters.
+   *
+   * @function useTemplate
+   * @param {string} name - Name of the template
+   * @param {object} args - Arguments to pass to the template
+   * @returns {notebook.Notebook}
+   *
+   * @example notebook.addCells(fp.useTemplate('incident', { service: 'api' }).cells)
+   */
+  useTemplate(name, args={}): std.native('fiberplane.useTemplate')(
+    validate.string('name', name),
+    validate.object('args', args),
+  ),
   /**
    * Functions for creating notebook cells
    * @namespace cell
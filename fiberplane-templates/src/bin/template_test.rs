@@ -0,0 +1,75 @@
+use clap::Parser;
+use fiberplane_templates::{TemplateTestRunner, TestOutcome};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Expand a directory of templates with the arguments in their fixtures and
+/// compare the resulting notebooks against the stored snapshots
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Directory that contains a subdirectory for every template
+    #[clap(default_value = ".")]
+    dir: PathBuf,
+
+    /// Write the snapshots that are missing or do not match
+    #[clap(short, long)]
+    update: bool,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let results = match TemplateTestRunner::new(&args.dir)
+        .update_snapshots(args.update)
+        .run()
+    {
+        Ok(results) => results,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut failures = 0;
+    for result in &results {
+        let name = format!("{} ({})", result.template, result.fixture);
+        match &result.outcome {
+            TestOutcome::Passed => println!("ok       {name}"),
+            TestOutcome::SnapshotWritten => {
+                println!("written  {name} -> {}", result.snapshot.display())
+            }
+            TestOutcome::MissingSnapshot => {
+                println!("missing  {name}: run with --update to create the snapshot")
+            }
+            TestOutcome::Mismatch {
+                path,
+                expected,
+                actual,
+            } => {
+                println!("FAILED   {name}: `{path}` does not match the snapshot");
+                println!("         expected: {expected}");
+                println!("         actual:   {actual}");
+            }
+            TestOutcome::ExpansionFailed(err) => {
+                println!("FAILED   {name}: could not expand template");
+                for line in err.lines() {
+                    println!("         {line}");
+                }
+            }
+        }
+
+        if !result.outcome.is_success() {
+            failures += 1;
+        }
+    }
+
+    println!();
+    println!("{} passed, {failures} failed", results.len() - failures);
+
+    if failures > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
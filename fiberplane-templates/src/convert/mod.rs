@@ -1,29 +1,70 @@
 use self::code_writer::CodeWriter;
 use self::escape_string::escape_string;
+use self::parameters::{mustache_parameter_names, Substitutions, TemplateParameters};
 use crate::FIBERPLANE_LIBRARY_PATH;
 use fiberplane_models::formatting::{Annotation, AnnotationWithOffset, Formatting};
-use fiberplane_models::notebooks::{Cell, HeadingType, ListType, NewNotebook};
+use fiberplane_models::notebooks::{
+    Cell, GraphType, HeadingType, ListType, LogRecordIndex, LogVisibilityFilter, NewNotebook,
+    StackingType, TableRowValue,
+};
 use fiberplane_models::timestamps::TimeRange;
 use fiberplane_models::utils::{char_count, char_slice, char_slice_from};
-use once_cell::sync::Lazy;
 use percent_encoding::percent_decode_str;
-use regex::Regex;
-use std::{collections::BTreeSet, fmt::Write};
+use std::collections::HashMap;
+use std::fmt::Write;
 use time::Duration;
 use tracing::warn;
 
 mod code_writer;
 mod escape_string;
+mod parameters;
 #[cfg(test)]
 mod tests;
 
-static MUSTACHE_SUBSTITUTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\{(\w+)\}\}").unwrap());
 const FORM_ENCODED_QUERY_PREFIX: &str = "application/x-www-form-urlencoded,";
 
+/// Options that control which parts of a notebook are turned into
+/// parameters of the generated template.
+///
+/// `{{mustache}}` placeholders in the title and cells are always turned into
+/// parameters. If the notebook does not end up with any parameters, the title
+/// is added as a parameter to show how they are used.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NotebookToTemplateOptions {
+    /// Turn the value of every label (that has a value) into a parameter.
+    pub parameterize_labels: bool,
+
+    /// Turn every front matter value into a parameter.
+    pub parameterize_front_matter: bool,
+
+    /// Turn the names of the selected data sources, and of the proxies they
+    /// belong to, into parameters.
+    pub parameterize_data_sources: bool,
+
+    /// Strings that should be replaced by a parameter wherever they appear,
+    /// given as `(parameter name, value)` pairs.
+    ///
+    /// This is useful for values that recur throughout a notebook, such as
+    /// the name of a service that is used in the title, text cells and
+    /// provider cell queries. Substitutions are made in the title, label
+    /// values, cell content, image URLs and queries of the Prometheus,
+    /// Elasticsearch and Loki cells.
+    pub string_parameters: Vec<(String, String)>,
+}
+
 // Note: we use the NewNotebook struct because it contains
 // the subset of the Notebook fields that we need to
 // create a template from it.
 pub fn notebook_to_template(notebook: impl Into<NewNotebook>) -> String {
+    notebook_to_template_with_options(notebook, &NotebookToTemplateOptions::default())
+}
+
+/// Convert the notebook into a template, using the given options to
+/// determine which values are turned into template parameters.
+pub fn notebook_to_template_with_options(
+    notebook: impl Into<NewNotebook>,
+    options: &NotebookToTemplateOptions,
+) -> String {
     let notebook = notebook.into();
 
     // We assume that the time range for the template should be
@@ -33,27 +74,84 @@ pub fn notebook_to_template(notebook: impl Into<NewNotebook>) -> String {
     let time_range = TimeRange::from(notebook.time_range);
     let duration: Duration = time_range.to - time_range.from;
 
+    let mut parameters = TemplateParameters::default();
+    let mut substitutions = Substitutions::default();
+
+    // Add all of the variables from the title and content of the notebook
+    // (this will preserve the order in which the parameters are first used)
+    let mustache_parameters = mustache_parameter_names(
+        std::iter::once(notebook.title.as_str())
+            .chain(notebook.cells.iter().flat_map(Cell::content)),
+    );
+    for name in mustache_parameters {
+        // This will print "name='{{name}}'" (the extra braces are for escaping the braces)
+        parameters.declare_exact(name, format!("'{{{{{name}}}}}'"));
+    }
+
+    for (name, value) in &options.string_parameters {
+        let name = parameters.declare_unique(name, escape_string(value));
+        substitutions.add_string(value, name);
+    }
+
+    let label_parameters: Vec<Option<String>> = notebook
+        .labels
+        .iter()
+        .map(|label| {
+            (options.parameterize_labels && !label.value.is_empty()).then(|| {
+                parameters
+                    .declare_unique(&format!("label {}", label.key), escape_string(&label.value))
+            })
+        })
+        .collect();
+
+    let front_matter_parameters: Vec<Option<String>> = notebook
+        .front_matter
+        .iter()
+        .map(|(fm_key, fm_value)| {
+            options.parameterize_front_matter.then(|| {
+                parameters.declare_unique(
+                    &format!("front matter {fm_key}"),
+                    serde_json::to_string(fm_value).expect("A JSON Value is always serializable"),
+                )
+            })
+        })
+        .collect();
+
+    let data_source_parameters: Vec<Option<(String, Option<String>)>> = notebook
+        .selected_data_sources
+        .iter()
+        .map(|(provider_type, data_source)| {
+            options.parameterize_data_sources.then(|| {
+                let name = parameters.declare_unique(
+                    &format!("{provider_type} data source"),
+                    escape_string(data_source.name.as_str()),
+                );
+                let proxy_name = data_source.proxy_name.as_ref().map(|proxy_name| {
+                    parameters.declare_unique(
+                        &format!("{provider_type} proxy"),
+                        escape_string(proxy_name.as_str()),
+                    )
+                });
+                (name, proxy_name)
+            })
+        })
+        .collect();
+
+    // If there aren't any parameters, add the title as an example of how to use template parameters
+    let include_title_as_parameter = parameters.is_empty();
+    if include_title_as_parameter {
+        parameters.declare_exact("title", escape_string(&notebook.title));
+    }
+
     let mut writer = CodeWriter::new();
 
     // Write the preamble
     write_preamble(&mut writer);
     writer.println("function(");
     writer.indent();
-
-    // Add all of the variables from the title and content of the notebook
-    let template_function_parameters = parse_template_function_parameters(
-        &notebook.title,
-        notebook.cells.iter().flat_map(Cell::content),
-    );
-    // If there aren't any parameters, add the title as an example of how to use template parameters
-    let include_title_as_parameter = template_function_parameters.is_empty();
-
-    for parameter_declaration in template_function_parameters {
+    for parameter_declaration in parameters.declarations() {
         writer.println(parameter_declaration);
     }
-    if include_title_as_parameter {
-        writer.println(format!("title={}", escape_string(&notebook.title)));
-    }
 
     // Close the template function signature
     writer.dedent();
@@ -68,7 +166,7 @@ pub fn notebook_to_template(notebook: impl Into<NewNotebook>) -> String {
     } else {
         writer.println(format!(
             ".new({})",
-            escape_string_and_replace_mustache_substitutions(&notebook.title, " + ")
+            substitutions.escape_and_substitute(&notebook.title, " + ")
         ));
     }
 
@@ -83,12 +181,12 @@ pub fn notebook_to_template(notebook: impl Into<NewNotebook>) -> String {
         writer.println(r#".addLabels({})"#);
     } else {
         writer.println(".addLabels({");
-        for label in notebook.labels {
-            writer.println(format!(
-                "{}: {},",
-                escape_string(&label.key),
-                escape_string(&label.value)
-            ));
+        for (label, parameter) in notebook.labels.iter().zip(label_parameters) {
+            let value = match parameter {
+                Some(parameter) => parameter,
+                None => substitutions.escape_and_substitute(&label.value, " + "),
+            };
+            writer.println(format!("{}: {},", escape_string(&label.key), value));
         }
         writer.println("})");
     }
@@ -96,12 +194,16 @@ pub fn notebook_to_template(notebook: impl Into<NewNotebook>) -> String {
     // Add front matter
     if !notebook.front_matter.is_empty() {
         writer.println(".addFrontMatterValues({");
-        for (fm_key, fm_value) in notebook.front_matter {
-            writer.println(format!(
-                "{}: {},",
-                escape_string(fm_key),
-                serde_json::to_string(&fm_value).expect("A JSON Value is always serializable")
-            ));
+        for ((fm_key, fm_value), parameter) in
+            notebook.front_matter.iter().zip(front_matter_parameters)
+        {
+            let value = match parameter {
+                Some(parameter) => parameter,
+                None => {
+                    serde_json::to_string(fm_value).expect("A JSON Value is always serializable")
+                }
+            };
+            writer.println(format!("{}: {},", escape_string(fm_key), value));
         }
         writer.println("})");
     }
@@ -117,19 +219,33 @@ pub fn notebook_to_template(notebook: impl Into<NewNotebook>) -> String {
     }
 
     // Add selected data sources
-    for (provider_type, data_source) in notebook.selected_data_sources.iter() {
-        if let Some(proxy_name) = &data_source.proxy_name {
+    for ((provider_type, data_source), parameters) in notebook
+        .selected_data_sources
+        .iter()
+        .zip(data_source_parameters)
+    {
+        let (name, proxy_name) = match parameters {
+            Some((name, proxy_name)) => (name, proxy_name),
+            None => (
+                escape_string(data_source.name.as_str()),
+                data_source
+                    .proxy_name
+                    .as_ref()
+                    .map(|proxy_name| escape_string(proxy_name.as_str())),
+            ),
+        };
+        if let Some(proxy_name) = proxy_name {
             writer.println(format!(
                 ".setDataSourceForProviderType({}, {}, {})",
                 escape_string(provider_type),
-                escape_string(data_source.name.as_str()),
-                escape_string(proxy_name.as_str())
+                name,
+                proxy_name
             ));
         } else {
             writer.println(format!(
                 ".setDataSourceForProviderType({}, {})",
                 escape_string(provider_type),
-                escape_string(data_source.name.as_str())
+                name
             ));
         }
     }
@@ -137,8 +253,9 @@ pub fn notebook_to_template(notebook: impl Into<NewNotebook>) -> String {
     // Add cells
     writer.println(".addCells([");
     writer.indent();
+    let cell_ids = CellIds::new(&notebook.cells);
     for cell in &notebook.cells {
-        print_cell(&mut writer, cell);
+        print_cell(&mut writer, cell, &substitutions, &cell_ids);
     }
     writer.dedent();
     writer.println("])");
@@ -148,14 +265,20 @@ pub fn notebook_to_template(notebook: impl Into<NewNotebook>) -> String {
 }
 
 /// Convert the given cells into a Snippet
+///
+/// Unlike in templates, data links are not renumbered: snippets are inserted
+/// after the cells that are already in a notebook, so the cells they link to
+/// do not end up with the IDs they have in the snippet.
 pub fn cells_to_snippet(cells: &[Cell]) -> String {
     let mut writer = CodeWriter::new();
     write_preamble(&mut writer);
 
     writer.println("fp.snippet([");
     writer.indent();
+    let substitutions = Substitutions::default();
+    let cell_ids = CellIds::default();
     for cell in cells {
-        print_cell(&mut writer, cell);
+        print_cell(&mut writer, cell, &substitutions, &cell_ids);
     }
     writer.dedent();
     writer.println("])");
@@ -179,14 +302,22 @@ fn write_preamble(writer: &mut CodeWriter) {
 /// We try to print the cell in the most compact form that is still readable.
 /// If it only has 1-2 properties, we print it on a single line.
 /// If it has more, we print it on multiple lines and write out each property name.
-fn print_cell(writer: &mut CodeWriter, cell: &Cell) {
+fn print_cell(
+    writer: &mut CodeWriter,
+    cell: &Cell,
+    substitutions: &Substitutions,
+    cell_ids: &CellIds,
+) {
     let mut args = Vec::with_capacity(5);
 
     // Get the helper function name, arguments, and read only property from each cell
     // (read_only is handled separately because every cell has it)
     let (function_name, read_only) = match cell {
         Cell::Checkbox(cell) => {
-            args.push(("content", format_content(&cell.content, &cell.formatting)));
+            args.push((
+                "content",
+                format_content(&cell.content, &cell.formatting, substitutions),
+            ));
             args.push(("checked", cell.checked.to_string()));
             if let Some(level) = cell.level {
                 args.push(("level", level.to_string()));
@@ -195,7 +326,10 @@ fn print_cell(writer: &mut CodeWriter, cell: &Cell) {
             ("checkbox", cell.read_only)
         }
         Cell::Code(cell) => {
-            args.push(("content", escape_string(&cell.content)));
+            args.push((
+                "content",
+                substitutions.escape_and_substitute(&cell.content, " + "),
+            ));
             if let Some(syntax) = &cell.syntax {
                 args.push(("syntax", escape_string(syntax)));
             }
@@ -209,12 +343,15 @@ fn print_cell(writer: &mut CodeWriter, cell: &Cell) {
                 HeadingType::H3 => "h3",
                 _ => panic!("Unknown HeadingType"),
             };
-            args.push(("content", format_content(&cell.content, &cell.formatting)));
+            args.push((
+                "content",
+                format_content(&cell.content, &cell.formatting, substitutions),
+            ));
             (heading_type, cell.read_only)
         }
         Cell::Image(cell) => {
             if let Some(url) = &cell.url {
-                args.push(("url", escape_string(url)));
+                args.push(("url", substitutions.escape_and_substitute(url, " + ")));
             }
             ("image", cell.read_only)
         }
@@ -224,7 +361,10 @@ fn print_cell(writer: &mut CodeWriter, cell: &Cell) {
                 ListType::Unordered => "listItem.unordered",
                 _ => panic!("Unknown ListType"),
             };
-            args.push(("content", format_content(&cell.content, &cell.formatting)));
+            args.push((
+                "content",
+                format_content(&cell.content, &cell.formatting, substitutions),
+            ));
             if let Some(level) = cell.level {
                 args.push(("level", level.to_string()));
             }
@@ -234,7 +374,10 @@ fn print_cell(writer: &mut CodeWriter, cell: &Cell) {
             (function_name, cell.read_only)
         }
         Cell::Text(cell) => {
-            args.push(("content", format_content(&cell.content, &cell.formatting)));
+            args.push((
+                "content",
+                format_content(&cell.content, &cell.formatting, substitutions),
+            ));
             ("text", cell.read_only)
         }
         Cell::Provider(cell) => {
@@ -249,7 +392,10 @@ fn print_cell(writer: &mut CodeWriter, cell: &Cell) {
                     if let Some(query) = decode_provider_cell_query_data(&cell.query_data) {
                         args.extend(query.into_iter().filter_map(|(key, value)| {
                             if key == "query" {
-                                Some(("content", escape_string(value)))
+                                Some((
+                                    "content",
+                                    substitutions.escape_and_substitute(&value, " + "),
+                                ))
                             } else {
                                 warn!("Unexpected argument: {key}");
                                 None
@@ -275,8 +421,112 @@ fn print_cell(writer: &mut CodeWriter, cell: &Cell) {
             };
             (cell_type, cell.read_only)
         }
-        // Ignored cell types:
-        Cell::Discussion(_) | Cell::Graph(_) | Cell::Log(_) | Cell::Table(_) => return,
+        Cell::Discussion(cell) => {
+            args.push(("threadId", escape_string(&cell.thread_id)));
+            ("discussion", cell.read_only)
+        }
+        Cell::Graph(cell) => {
+            args.push(("dataLinks", cell_ids.format_data_links(&cell.data_links)));
+            if cell.graph_type != GraphType::Line {
+                let graph_type = match cell.graph_type {
+                    GraphType::Bar => "bar",
+                    _ => panic!("Unknown GraphType"),
+                };
+                args.push(("graphType", escape_string(graph_type)));
+            }
+            if cell.stacking_type != StackingType::None {
+                let stacking_type = match cell.stacking_type {
+                    StackingType::Stacked => "stacked",
+                    StackingType::Percentage => "percentage",
+                    _ => panic!("Unknown StackingType"),
+                };
+                args.push(("stackingType", escape_string(stacking_type)));
+            }
+            ("graph", cell.read_only)
+        }
+        Cell::Log(cell) => {
+            args.push(("dataLinks", cell_ids.format_data_links(&cell.data_links)));
+            if let Some(display_fields) = &cell.display_fields {
+                args.push(("displayFields", format_string_array(display_fields)));
+            }
+            if let Some(hide_similar_values) = cell.hide_similar_values {
+                args.push(("hideSimilarValues", hide_similar_values.to_string()));
+            }
+            if let Some(visibility_filter) = &cell.visibility_filter {
+                let visibility_filter = match visibility_filter {
+                    LogVisibilityFilter::All => "all",
+                    LogVisibilityFilter::Selected => "selected",
+                    LogVisibilityFilter::Highlighted => "highlighted",
+                    _ => panic!("Unknown LogVisibilityFilter"),
+                };
+                args.push(("visibilityFilter", escape_string(visibility_filter)));
+            }
+            if let Some(indices) = &cell.expanded_indices {
+                args.push(("expandedIndices", format_log_record_indices(indices)));
+            }
+            if let Some(indices) = &cell.selected_indices {
+                args.push(("selectedIndices", format_log_record_indices(indices)));
+            }
+            if let Some(indices) = &cell.highlighted_indices {
+                args.push(("highlightedIndices", format_log_record_indices(indices)));
+            }
+            ("log", cell.read_only)
+        }
+        Cell::Table(cell) => {
+            // Columns and rows only need explicit IDs if they differ from the
+            // ones that are generated by the template library
+            let columns = cell
+                .column_defs
+                .iter()
+                .enumerate()
+                .map(|(index, column)| {
+                    let title = escape_string(&column.title);
+                    if column.id.to_string() == format!("col{}", index + 1) {
+                        title
+                    } else {
+                        format!(
+                            "{{ id: {}, title: {} }}",
+                            escape_string(column.id.to_string()),
+                            title
+                        )
+                    }
+                })
+                .collect::<Vec<_>>();
+            let rows = cell
+                .rows
+                .iter()
+                .enumerate()
+                .map(|(index, row)| {
+                    let values = row
+                        .values
+                        .iter()
+                        .map(|value| match value {
+                            TableRowValue::Text(text) => {
+                                format_content(&text.text, &text.formatting, substitutions)
+                            }
+                            _ => panic!("Unknown TableRowValue"),
+                        })
+                        .collect::<Vec<_>>();
+                    let values = format!("[{}]", values.join(", "));
+                    if row.id.to_string() == format!("row{}", index + 1) {
+                        values
+                    } else {
+                        format!(
+                            "{{ id: {}, values: {} }}",
+                            escape_string(row.id.to_string()),
+                            values
+                        )
+                    }
+                })
+                .collect::<Vec<_>>();
+            args.push(("columns", format!("[{}]", columns.join(", "))));
+            args.push(("rows", format!("[{}]", rows.join(", "))));
+            ("table", cell.read_only)
+        }
+        Cell::Timeline(cell) => {
+            args.push(("dataLinks", cell_ids.format_data_links(&cell.data_links)));
+            ("timeline", cell.read_only)
+        }
         _ => {
             // Unknown cell type
             return;
@@ -310,9 +560,69 @@ fn print_cell(writer: &mut CodeWriter, cell: &Cell) {
     };
 }
 
-fn format_content(content: &str, formatting: &Formatting) -> String {
+/// Maps the IDs of the cells in a notebook to the IDs the template library
+/// will assign to them, so that data links that refer to other cells keep
+/// working.
+///
+/// The default leaves all data links unchanged.
+#[derive(Default)]
+struct CellIds<'a>(HashMap<&'a str, String>);
+
+impl<'a> CellIds<'a> {
+    fn new(cells: &'a [Cell]) -> Self {
+        // The template library numbers the cells in the order they are added
+        Self(
+            cells
+                .iter()
+                .enumerate()
+                .map(|(index, cell)| (cell.id(), (index + 1).to_string()))
+                .collect(),
+        )
+    }
+
+    /// Format the data links as an array, replacing references to cells in
+    /// links of the form `cell-data:<mime-type>,<cell-id>`.
+    fn format_data_links(&self, data_links: &[String]) -> String {
+        let links = data_links
+            .iter()
+            .map(|link| {
+                match link
+                    .strip_prefix("cell-data:")
+                    .and_then(|link| link.rsplit_once(','))
+                    .and_then(|(mime_type, cell_id)| Some((mime_type, self.0.get(cell_id)?)))
+                {
+                    Some((mime_type, cell_id)) => {
+                        escape_string(format!("cell-data:{mime_type},{cell_id}"))
+                    }
+                    None => escape_string(link),
+                }
+            })
+            .collect::<Vec<_>>();
+        format!("[{}]", links.join(", "))
+    }
+}
+
+fn format_string_array(strings: &[String]) -> String {
+    let strings = strings.iter().map(escape_string).collect::<Vec<_>>();
+    format!("[{}]", strings.join(", "))
+}
+
+fn format_log_record_indices(indices: &[LogRecordIndex]) -> String {
+    let indices = indices
+        .iter()
+        .map(|index| {
+            format!(
+                "{{ linkIndex: {}, recordIndex: {} }}",
+                index.link_index, index.record_index
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", indices.join(", "))
+}
+
+fn format_content(content: &str, formatting: &Formatting, substitutions: &Substitutions) -> String {
     if formatting.is_empty() {
-        return escape_string(content);
+        return substitutions.escape_and_substitute(content, " + ");
     }
 
     let mut output = "[".to_string();
@@ -332,10 +642,9 @@ fn format_content(content: &str, formatting: &Formatting) -> String {
     {
         // Add any content before this annotation to the output
         if *offset > index {
-            output.push_str(&escape_string_and_replace_mustache_substitutions(
-                char_slice(content, index, *offset),
-                ", ",
-            ));
+            output.push_str(
+                &substitutions.escape_and_substitute(char_slice(content, index, *offset), ", "),
+            );
             output.push_str(", ");
             index = *offset;
         }
@@ -383,8 +692,9 @@ fn format_content(content: &str, formatting: &Formatting) -> String {
             Annotation::Mention(mention) => {
                 write!(
                     output,
-                    "fmt.mention('{}', '{}'), ",
-                    mention.name, mention.user_id
+                    "fmt.mention({}, {}), ",
+                    escape_string(&mention.name),
+                    escape_string(&mention.user_id)
                 )
                 .expect("Cannot write mention instruction");
                 // Adding + 1 to the mention length to account for the @ sign
@@ -398,8 +708,12 @@ fn format_content(content: &str, formatting: &Formatting) -> String {
             }
             Annotation::Label(label) => {
                 let args = match label.value.is_empty() {
-                    true => format!("'{}'", label.key),
-                    false => format!("'{}', '{}'", label.key, label.value),
+                    true => escape_string(&label.key),
+                    false => format!(
+                        "{}, {}",
+                        escape_string(&label.key),
+                        escape_string(&label.value)
+                    ),
                 };
                 output.push_str(&format!("fmt.label({args}), "));
                 index += char_count(&label.to_string())
@@ -409,10 +723,8 @@ fn format_content(content: &str, formatting: &Formatting) -> String {
     }
     // If the content ends with plain text, make sure to add it to the output
     if index < char_count(&content) {
-        output.push_str(&escape_string_and_replace_mustache_substitutions(
-            char_slice_from(content, index),
-            ", ",
-        ));
+        output
+            .push_str(&substitutions.escape_and_substitute(char_slice_from(content, index), ", "));
     }
     // If there are unclosed annotations, add extra closing brackets and parens
     // to close the helper function calls
@@ -435,48 +747,6 @@ fn finish_enclosure(string: &mut String, brackets: &str) {
     string.push_str(brackets);
 }
 
-fn escape_string_and_replace_mustache_substitutions(content: &str, separator: &str) -> String {
-    let escaped = escape_string(content);
-    if let Some(quote) = escaped.chars().next() {
-        let replaced = MUSTACHE_SUBSTITUTION
-            .replace_all(&escaped, format!("{quote}{separator}$1{separator}{quote}"));
-        // Remove empty strings (which can happen if the mustache substitution happens at the beginning or
-        // end of the string, or if there are multiple substitutions in a row)
-        replaced
-            .replace(&format!("{quote}{quote}{separator}"), "")
-            .replace(&format!("{separator}{quote}{quote}"), "")
-    } else {
-        escaped
-    }
-}
-
-/// Parse the template function parameters from the title and cells and return
-/// their jsonnet declarations in the form "parameter=defaultValue".
-///
-/// Note this will preserve the order in which the parameters are first used
-fn parse_template_function_parameters<'a>(
-    title: &str,
-    cell_content: impl Iterator<Item = &'a str>,
-) -> Vec<String> {
-    let mut unique_parameters = BTreeSet::new();
-    let mustache_substitution = &*MUSTACHE_SUBSTITUTION;
-    let title_substitutions = mustache_substitution.captures_iter(title);
-    let cell_substitutions = cell_content.flat_map(|c| mustache_substitution.captures_iter(c));
-    title_substitutions
-        .chain(cell_substitutions)
-        .flat_map(|c| c.get(1))
-        .flat_map(|variable| {
-            let variable = variable.as_str();
-            if unique_parameters.insert(variable) {
-                // This will print "variable={{variable}}" (the extra braces are for escaping the braces)
-                Some(format!("{variable}='{{{{{variable}}}}}',"))
-            } else {
-                None
-            }
-        })
-        .collect()
-}
-
 fn decode_provider_cell_query_data<'a>(
     query_data: &'a Option<impl AsRef<str> + 'a>,
 ) -> Option<Vec<(String, String)>> {
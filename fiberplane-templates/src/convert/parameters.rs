@@ -0,0 +1,281 @@
+use super::escape_string::escape_string;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+
+static MUSTACHE_SUBSTITUTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\{(\w+)\}\}").unwrap());
+
+/// Identifiers that cannot be used as parameter names, either because they
+/// are Jsonnet keywords or because the generated template already uses them
+/// for the library imports.
+const RESERVED_IDENTIFIERS: [&str; 21] = [
+    "assert",
+    "else",
+    "error",
+    "false",
+    "for",
+    "function",
+    "if",
+    "import",
+    "importstr",
+    "importbin",
+    "in",
+    "local",
+    "null",
+    "tailstrict",
+    "then",
+    "self",
+    "super",
+    "true",
+    "fp",
+    "c",
+    "fmt",
+];
+
+/// Keeps track of the parameters of the template function.
+///
+/// Parameters are declared in the order they are added, and generated
+/// names are made unique so that they never shadow each other.
+#[derive(Default)]
+pub(crate) struct TemplateParameters {
+    declarations: Vec<String>,
+    names: BTreeSet<String>,
+}
+
+impl TemplateParameters {
+    /// Declare a parameter with exactly the given name.
+    ///
+    /// This is used for mustache substitutions, which are referenced by name
+    /// in the content. Returns false if the parameter was already declared.
+    pub fn declare_exact(&mut self, name: &str, default_value: String) -> bool {
+        if self.names.insert(name.to_string()) {
+            self.declarations.push(format!("{name}={default_value},"));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Declare a parameter based on the given name, adding a numeric suffix
+    /// if the name is already taken. Returns the name that was declared.
+    pub fn declare_unique(&mut self, name: &str, default_value: String) -> String {
+        let base = to_identifier(name);
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while self.names.contains(&name) || RESERVED_IDENTIFIERS.contains(&name.as_str()) {
+            name = format!("{base}{suffix}");
+            suffix += 1;
+        }
+
+        self.names.insert(name.clone());
+        self.declarations.push(format!("{name}={default_value},"));
+        name
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.declarations.is_empty()
+    }
+
+    /// The parameter declarations in the form "parameter=defaultValue,"
+    pub fn declarations(&self) -> &[String] {
+        &self.declarations
+    }
+}
+
+/// Replaces mustache placeholders and recurring strings with references to
+/// template parameters when content is written out as Jsonnet.
+#[derive(Default)]
+pub(crate) struct Substitutions {
+    /// Pairs of (value, parameter name), sorted so that longer values are
+    /// matched first.
+    strings: Vec<(String, String)>,
+}
+
+impl Substitutions {
+    pub fn add_string(&mut self, value: impl Into<String>, parameter: impl Into<String>) {
+        let value = value.into();
+        if value.is_empty() {
+            return;
+        }
+
+        self.strings.push((value, parameter.into()));
+        self.strings.sort_by_key(|(value, _)| Reverse(value.len()));
+    }
+
+    /// Escape the content as a Jsonnet string, replacing any substitutions
+    /// with the parameter they refer to.
+    ///
+    /// If there are substitutions, the string literals and parameter names
+    /// are joined with the given separator (for example, `" + "` for string
+    /// concatenation or `", "` for items in an array of formatted content).
+    pub fn escape_and_substitute(&self, content: &str, separator: &str) -> String {
+        let segments = self.split(content);
+        match segments.as_slice() {
+            [] => escape_string(""),
+            [Segment::Literal(literal)] => escape_string(literal),
+            _ => segments
+                .iter()
+                .map(|segment| match segment {
+                    Segment::Literal(literal) => escape_string(literal),
+                    Segment::Parameter(name) => name.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(separator),
+        }
+    }
+
+    /// Split the content into string literals and parameter references.
+    fn split<'a>(&'a self, content: &'a str) -> Vec<Segment<'a>> {
+        let mut segments = Vec::new();
+        let mut index = 0;
+
+        while index < content.len() {
+            let mustache = MUSTACHE_SUBSTITUTION
+                .captures(&content[index..])
+                .and_then(|captures| Some((captures.get(0)?, captures.get(1)?)))
+                .map(|(whole, name)| (index + whole.start(), index + whole.end(), name.as_str()));
+            let string = self
+                .strings
+                .iter()
+                .filter_map(|(value, parameter)| {
+                    content[index..].find(value.as_str()).map(|start| {
+                        let start = index + start;
+                        (start, start + value.len(), parameter.as_str())
+                    })
+                })
+                // Strings are sorted by length, so the first match at the
+                // earliest position is also the longest one
+                .min_by_key(|(start, _, _)| *start);
+
+            let next = match (mustache, string) {
+                (Some(mustache), Some(string)) if string.0 < mustache.0 => Some(string),
+                (Some(mustache), _) => Some(mustache),
+                (None, string) => string,
+            };
+
+            match next {
+                Some((start, end, parameter)) => {
+                    if start > index {
+                        segments.push(Segment::Literal(&content[index..start]));
+                    }
+                    segments.push(Segment::Parameter(parameter));
+                    index = end;
+                }
+                None => {
+                    segments.push(Segment::Literal(&content[index..]));
+                    index = content.len();
+                }
+            }
+        }
+
+        segments
+    }
+}
+
+enum Segment<'a> {
+    Literal(&'a str),
+    Parameter(&'a str),
+}
+
+/// Return the names used in mustache substitutions, in the order in which
+/// they first appear.
+pub(crate) fn mustache_parameter_names<'a>(
+    content: impl Iterator<Item = &'a str>,
+) -> impl Iterator<Item = &'a str> {
+    content
+        .flat_map(|c| MUSTACHE_SUBSTITUTION.captures_iter(c))
+        .flat_map(|c| c.get(1))
+        .map(|name| name.as_str())
+}
+
+/// Convert an arbitrary string (such as a label key) into a camelCase
+/// Jsonnet identifier.
+fn to_identifier(name: &str) -> String {
+    let mut identifier = String::with_capacity(name.len());
+    for (index, word) in name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .enumerate()
+    {
+        if index == 0 {
+            identifier.push_str(word);
+        } else {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                identifier.push(first.to_ascii_uppercase());
+                identifier.push_str(chars.as_str());
+            }
+        }
+    }
+
+    match identifier.chars().next() {
+        None => "param".to_string(),
+        Some(first) if first.is_ascii_digit() => format!("_{identifier}"),
+        Some(_) => identifier,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_names_to_identifiers() {
+        assert_eq!(to_identifier("label service.name"), "labelServiceName");
+        assert_eq!(
+            to_identifier("prometheus DataSource"),
+            "prometheusDataSource"
+        );
+        assert_eq!(to_identifier("1st"), "_1st");
+        assert_eq!(to_identifier("🦀"), "param");
+    }
+
+    #[test]
+    fn declares_unique_names() {
+        let mut parameters = TemplateParameters::default();
+        assert!(parameters.declare_exact("service", "'{{service}}'".to_string()));
+        assert_eq!(
+            parameters.declare_unique("service", "'a'".to_string()),
+            "service2"
+        );
+        assert_eq!(
+            parameters.declare_unique("service", "'b'".to_string()),
+            "service3"
+        );
+        assert_eq!(
+            parameters.declare_unique("local", "'c'".to_string()),
+            "local2"
+        );
+        assert_eq!(
+            parameters.declarations(),
+            &[
+                "service='{{service}}',",
+                "service2='a',",
+                "service3='b',",
+                "local2='c',"
+            ]
+        );
+    }
+
+    #[test]
+    fn substitutes_strings_and_mustaches() {
+        let mut substitutions = Substitutions::default();
+        substitutions.add_string("api", "service");
+        substitutions.add_string("api-gateway", "gateway");
+
+        assert_eq!(
+            substitutions.escape_and_substitute("no substitutions", " + "),
+            "'no substitutions'"
+        );
+        assert_eq!(substitutions.escape_and_substitute("api", " + "), "service");
+        assert_eq!(
+            substitutions.escape_and_substitute("rate(http{job=\"api\"}[5m])", " + "),
+            "'rate(http{job=\"' + service + '\"}[5m])'"
+        );
+        assert_eq!(
+            substitutions.escape_and_substitute("{{greeting}} api-gateway and api", ", "),
+            "greeting, ' ', gateway, ' and ', service"
+        );
+    }
+}
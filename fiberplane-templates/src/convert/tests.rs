@@ -1,6 +1,11 @@
+use super::parameters::Substitutions;
 use super::*;
 use fiberplane_models::formatting::Mention;
-use fiberplane_models::notebooks::{DividerCell, Label, ProviderCell, TextCell};
+use fiberplane_models::formatting::RichText;
+use fiberplane_models::notebooks::{
+    DividerCell, GraphCell, Label, LogCell, ProviderCell, TableCell, TableColumnDefinition,
+    TableRow, TextCell,
+};
 use fiberplane_models::timestamps::Timestamp;
 
 #[test]
@@ -12,7 +17,7 @@ fn formatting_basic() {
         AnnotationWithOffset::new(24, Annotation::StartItalics),
         AnnotationWithOffset::new(43, Annotation::EndItalics),
     ];
-    let actual = format_content(content, &formatting);
+    let actual = format_content(content, &formatting, &Substitutions::default());
     // alternative: "fmt.raw('some normal, ').bold('some bold, ').italics('and some italicized text')"
     assert_eq!(
         actual,
@@ -29,7 +34,7 @@ fn formatting_nested() {
         AnnotationWithOffset::new(48, Annotation::EndItalics),
         AnnotationWithOffset::new(48, Annotation::EndBold),
     ];
-    let actual = format_content(content, &formatting);
+    let actual = format_content(content, &formatting, &Substitutions::default());
     assert_eq!(actual, "['some normal, ', fmt.bold(['some bold, ', fmt.italics(['and some bold italicized'])]), ' text']");
 }
 
@@ -45,7 +50,7 @@ fn format_link() {
         ),
         AnnotationWithOffset::new(8, Annotation::EndLink),
     ];
-    let actual = format_content(content, &formatting);
+    let actual = format_content(content, &formatting, &Substitutions::default());
     assert_eq!(
         actual,
         "['see ', fmt.link(url='https://example.com/more', content=['here']), ' for more']"
@@ -56,7 +61,7 @@ fn format_link() {
 fn format_unclosed() {
     let content = "some normal, some bold";
     let formatting = vec![AnnotationWithOffset::new(13, Annotation::StartBold)];
-    let actual = format_content(content, &formatting);
+    let actual = format_content(content, &formatting, &Substitutions::default());
     assert_eq!(actual, "['some normal, ', fmt.bold(['some bold'])]");
 }
 
@@ -72,7 +77,7 @@ fn format_mention() {
                 .build(),
         ),
     )];
-    let actual = format_content(content, &formatting);
+    let actual = format_content(content, &formatting, &Substitutions::default());
     assert_eq!(
         actual,
         "['hi ', fmt.mention('Bob Bobsen', '1234'), ' mention']"
@@ -88,7 +93,7 @@ fn format_timestamp() {
             timestamp: Timestamp::parse("2020-01-01T00:00:00Z").unwrap(),
         },
     )];
-    let actual = format_content(content, &formatting);
+    let actual = format_content(content, &formatting, &Substitutions::default());
     assert_eq!(
         actual,
         "['hi ', fmt.timestamp('2020-01-01T00:00:00Z'), ' timestamp']"
@@ -102,7 +107,7 @@ fn format_label() {
         3,
         Annotation::Label(Label::new("foo", "bar")),
     )];
-    let actual = format_content(content, &formatting);
+    let actual = format_content(content, &formatting, &Substitutions::default());
     assert_eq!(actual, "['hi ', fmt.label('foo', 'bar'), ' label']");
 }
 
@@ -117,6 +122,8 @@ fn print_text_cell() {
                 .content("I'm a text cell")
                 .build(),
         ),
+        &Substitutions::default(),
+        &CellIds::default(),
     );
     assert_eq!(writer.to_string(), "c.text(\"I'm a text cell\"),\n");
 }
@@ -127,6 +134,8 @@ fn print_divider_cell() {
     print_cell(
         &mut writer,
         &Cell::Divider(DividerCell::builder().id("c2".to_owned()).build()),
+        &Substitutions::default(),
+        &CellIds::default(),
     );
     assert_eq!(writer.to_string(), "c.divider(),\n");
 }
@@ -139,7 +148,12 @@ fn print_cell_handles_unicode() {
             .content("👀 I'm a text cell with unicode 🦀")
             .build(),
     );
-    print_cell(&mut writer, &cell);
+    print_cell(
+        &mut writer,
+        &cell,
+        &Substitutions::default(),
+        &CellIds::default(),
+    );
     assert_eq!(
         writer.to_string(),
         "c.text(\"👀 I'm a text cell with unicode 🦀\"),\n"
@@ -158,7 +172,12 @@ fn print_cell_handles_formatted_unicode() {
             ])
             .build(),
     );
-    print_cell(&mut writer, &cell);
+    print_cell(
+        &mut writer,
+        &cell,
+        &Substitutions::default(),
+        &CellIds::default(),
+    );
     assert_eq!(writer.to_string(), "c.text([fmt.highlight(['👀'])]),\n");
 }
 
@@ -172,7 +191,12 @@ fn decodes_well_known_provider_cell_data() {
                               .build()
     );
 
-    print_cell(&mut writer, &cell);
+    print_cell(
+        &mut writer,
+        &cell,
+        &Substitutions::default(),
+        &CellIds::default(),
+    );
     assert_eq!(
         writer.to_string(),
         "c.prometheus('apiserver_audit_event_total{job=\"test\"}'),
@@ -190,7 +214,12 @@ fn decodes_arbitrary_provider_cell_data() {
                               .build()
     );
 
-    print_cell(&mut writer, &cell);
+    print_cell(
+        &mut writer,
+        &cell,
+        &Substitutions::default(),
+        &CellIds::default(),
+    );
     assert_eq!(
         writer.to_string(),
         "c.provider(
@@ -200,3 +229,116 @@ fn decodes_arbitrary_provider_cell_data() {
 "
     );
 }
+
+#[test]
+fn print_table_cell() {
+    let mut writer = CodeWriter::new();
+    let cell = Cell::Table(
+        TableCell::builder()
+            .id("c1")
+            .column_defs(vec![
+                TableColumnDefinition::builder()
+                    .id("col1".parse().unwrap())
+                    .title("Service")
+                    .build(),
+                TableColumnDefinition::builder()
+                    .id("status".parse().unwrap())
+                    .title("Status")
+                    .build(),
+            ])
+            .rows(vec![
+                TableRow::builder()
+                    .id("row1".parse().unwrap())
+                    .values(vec![
+                        TableRowValue::Text(RichText::new("api", vec![])),
+                        TableRowValue::Text(RichText::new(
+                            "down",
+                            vec![
+                                AnnotationWithOffset::new(0, Annotation::StartBold),
+                                AnnotationWithOffset::new(4, Annotation::EndBold),
+                            ],
+                        )),
+                    ])
+                    .build(),
+                TableRow::builder()
+                    .id("abcdef".parse().unwrap())
+                    .values(vec![
+                        TableRowValue::Text(RichText::new("web", vec![])),
+                        TableRowValue::Text(RichText::new("up", vec![])),
+                    ])
+                    .build(),
+            ])
+            .build(),
+    );
+
+    print_cell(
+        &mut writer,
+        &cell,
+        &Substitutions::default(),
+        &CellIds::default(),
+    );
+    assert_eq!(
+        writer.to_string(),
+        "c.table(
+  columns=['Service', { id: 'status', title: 'Status' }],
+  rows=[['api', [fmt.bold(['down'])]], { id: 'abcdef', values: ['web', 'up'] }],
+),
+"
+    );
+}
+
+#[test]
+fn print_graph_and_log_cells_with_renumbered_data_links() {
+    let cells = vec![
+        Cell::Provider(
+            ProviderCell::builder()
+                .id("prometheus-cell")
+                .intent("prometheus,timeseries")
+                .build(),
+        ),
+        Cell::Graph(
+            GraphCell::builder()
+                .id("graph-cell")
+                .data_links(vec![
+                    "cell-data:application/vnd.fiberplane.timeseries+json,prometheus-cell"
+                        .to_string(),
+                ])
+                .graph_type(GraphType::Bar)
+                .stacking_type(StackingType::Percentage)
+                .build(),
+        ),
+        Cell::Log(
+            LogCell::builder()
+                .id("log-cell")
+                .data_links(vec![
+                    "cell-data:application/vnd.fiberplane.events+json,self".to_string(),
+                ])
+                .visibility_filter(LogVisibilityFilter::Selected)
+                .selected_indices(vec![LogRecordIndex::builder()
+                    .link_index(0)
+                    .record_index(3)
+                    .build()])
+                .build(),
+        ),
+    ];
+
+    let mut writer = CodeWriter::new();
+    let cell_ids = CellIds::new(&cells);
+    for cell in &cells[1..] {
+        print_cell(&mut writer, cell, &Substitutions::default(), &cell_ids);
+    }
+    assert_eq!(
+        writer.to_string(),
+        "c.graph(
+  dataLinks=['cell-data:application/vnd.fiberplane.timeseries+json,1'],
+  graphType='bar',
+  stackingType='percentage',
+),
+c.log(
+  dataLinks=['cell-data:application/vnd.fiberplane.events+json,self'],
+  visibilityFilter='selected',
+  selectedIndices=[{ linkIndex: 0, recordIndex: 3 }],
+),
+"
+    );
+}
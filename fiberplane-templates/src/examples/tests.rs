@@ -1,5 +1,5 @@
 use super::{INCIDENT_ANALYSIS, INCIDENT_RESPONSE, MEETING_NOTES, ROOT_CAUSE_ANALYSIS};
-use crate::{expand_template, TemplateExpander, EMPTY_ARGS};
+use crate::{expand_template, TemplateExpander, TemplateTestRunner, TestOutcome, EMPTY_ARGS};
 use fiberplane_models::notebooks::{Cell, Label};
 use serde_json::Value;
 
@@ -128,3 +128,19 @@ pub fn meeting_notes() {
         "Meeting Notes: A Very Important Project - 1/1/1970"
     );
 }
+
+#[test]
+pub fn snapshots() {
+    let examples_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/examples");
+    let results = TemplateTestRunner::new(examples_dir).run().unwrap();
+
+    assert_eq!(results.len(), 4);
+    for result in results {
+        assert_eq!(
+            result.outcome,
+            TestOutcome::Passed,
+            "{} does not match its snapshot",
+            result.template
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use self::resolver::Includes;
 use crate::FIBERPLANE_LIBRARY_PATH;
 use fiberplane_models::notebooks::{Cell, NewNotebook};
 use fiberplane_models::templates::{TemplateParameter, TemplateParameterType};
@@ -7,12 +8,16 @@ use jrsonnet_evaluator::{EvaluationState, FuncVal, IStr, ImportResolver, Manifes
 use jrsonnet_types::ValType;
 use serde_json::{Number, Value};
 use std::collections::HashSet;
+use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::{any::Any, convert::AsRef, iter::IntoIterator, rc::Rc};
 
+mod resolver;
 #[cfg(test)]
 mod tests;
 
+pub use resolver::TemplateResolver;
+
 static FIBERPLANE_LIBRARY: &str = include_str!("../../fiberplane.libsonnet");
 
 /// This can be passed to `expand_template` as the `args` parameter.
@@ -55,10 +60,19 @@ pub fn expand_snippet(snippet: impl AsRef<str>) -> Result<Vec<Cell>, Error> {
     TemplateExpander::default().expand_snippet(snippet)
 }
 
+/// Evaluate the snippet with the given top-level arguments.
+pub fn expand_snippet_with_args(
+    snippet: impl AsRef<str>,
+    args: impl IntoIterator<Item = (impl AsRef<str>, impl Into<Value>)>,
+) -> Result<Vec<Cell>, Error> {
+    TemplateExpander::default().expand_snippet_with_args(snippet, args)
+}
+
 #[derive(Default)]
 pub struct TemplateExpander {
     max_stack: Option<usize>,
     explaining_traces: bool,
+    resolver: Option<Rc<dyn TemplateResolver>>,
 }
 
 impl TemplateExpander {
@@ -66,9 +80,16 @@ impl TemplateExpander {
         Self {
             max_stack,
             explaining_traces: false,
+            resolver: None,
         }
     }
 
+    /// Set the resolver that provides the snippets and templates that can be
+    /// included with `fp.useSnippet` and `fp.useTemplate`
+    pub fn set_resolver(&mut self, resolver: impl TemplateResolver + 'static) {
+        self.resolver = Some(Rc::new(resolver));
+    }
+
     /// Display detailed rustc-style error messages that explain
     /// the problem (defaults to false)
     pub fn set_explaining_traces(&mut self, explaining_traces: bool) {
@@ -84,7 +105,7 @@ impl TemplateExpander {
         template: impl AsRef<str>,
         args: impl IntoIterator<Item = (impl AsRef<str>, impl Into<Value>)>,
     ) -> Result<NewNotebook, Error> {
-        let string = self.expand_template_to_string(template, args, false)?;
+        let string = self.expand_to_string(template, args, false)?;
         let mut notebook: NewNotebook = serde_json::from_str(&string)?;
 
         // Filter out any invalid labels
@@ -95,35 +116,62 @@ impl TemplateExpander {
 
     /// Expand the given snippet into an array of cells
     ///
-    /// Note that snippets, unlike templates, do not support top-level arguments
-    /// so the value returned must be the array of cells rather than a function.
+    /// If the snippet exports a function, it is called without arguments, so
+    /// all of its parameters need to have default values.
     pub fn expand_snippet(&self, snippet: impl AsRef<str>) -> Result<Vec<Cell>, Error> {
-        let string = self.expand_snippet_to_string(snippet, false)?;
+        self.expand_snippet_with_args(snippet, EMPTY_ARGS)
+    }
+
+    /// Expand the given snippet into an array of cells, passing the given
+    /// top-level arguments if the snippet exports a function.
+    pub fn expand_snippet_with_args(
+        &self,
+        snippet: impl AsRef<str>,
+        args: impl IntoIterator<Item = (impl AsRef<str>, impl Into<Value>)>,
+    ) -> Result<Vec<Cell>, Error> {
+        let string = self.expand_to_string(snippet, args, false)?;
         let cells: Vec<Cell> = serde_json::from_str(&string)?;
 
         Ok(cells)
     }
 
-    /// Evaluate the template with the given top-level arguments.
+    /// Evaluate the template or snippet with the given top-level arguments,
+    /// and return the JSON it produces.
     ///
     /// Note this method is private so that we can ensure that the
-    /// notebook created by the template is valid before returning it.
-    pub(crate) fn expand_template_to_string(
+    /// notebook or cells created by the template are valid before returning
+    /// them.
+    pub(crate) fn expand_to_string(
         &self,
-        template: impl AsRef<str>,
+        source: impl AsRef<str>,
         args: impl IntoIterator<Item = (impl AsRef<str>, impl Into<Value>)>,
         pretty_print: bool,
     ) -> Result<String, Error> {
-        let (state, result) = self.expand_template_inner(template)?;
+        let (state, result) = self.expand_template_inner(source)?;
 
         let num_spaces = if pretty_print { 2 } else { 0 };
         state.set_manifest_format(ManifestFormat::Json(num_spaces));
 
-        // If the top-level return is a function, add the top level arguments
-        // that correspond to parameters of that function.
+        let result = self.call_with_args(&state, result, args)?;
+
+        Ok(state
+            .manifest(result)
+            .map_err(|err| self.format_trace(&state, err))?
+            .to_string())
+    }
+
+    /// If the top-level return is a function, call it with the top-level
+    /// arguments that correspond to parameters of that function.
+    fn call_with_args(
+        &self,
+        state: &EvaluationState,
+        result: Val,
+        args: impl IntoIterator<Item = (impl AsRef<str>, impl Into<Value>)>,
+    ) -> Result<Val, Error> {
+        // Add the top level arguments that correspond to parameters of the function.
         // (If the function does not accept a parameter that we were given,
         // we will not pass it in rather than letting the function fail)
-        let result = if let Val::Func(func) = &result {
+        Ok(if let Val::Func(func) = &result {
             if let FuncVal::Normal(func) = func.as_ref() {
                 let params: HashSet<&str> = func.params.0.iter().map(|p| &*p.0).collect();
 
@@ -140,7 +188,7 @@ impl TemplateExpander {
                                     name.into(),
                                     serde_json::to_string(&value)?.as_str().into(),
                                 )
-                                .map_err(|err| self.format_trace(&state, err))?;
+                                .map_err(|err| self.format_trace(state, err))?;
                         }
                     }
                 }
@@ -153,36 +201,12 @@ impl TemplateExpander {
                 {
                     Error::MissingArgument(param.to_string())
                 } else {
-                    self.format_trace(&state, err)
+                    self.format_trace(state, err)
                 }
             })?
         } else {
             result
-        };
-
-        Ok(state
-            .manifest(result)
-            .map_err(|err| self.format_trace(&state, err))?
-            .to_string())
-    }
-
-    /// Expand the given snippet into a JSON stringified array of cells
-    ///
-    /// Note that snippets, unlike templates, do not support top-level arguments.
-    pub(crate) fn expand_snippet_to_string(
-        &self,
-        snippet: impl AsRef<str>,
-        pretty_print: bool,
-    ) -> Result<String, Error> {
-        let (state, result) = self.expand_template_inner(snippet)?;
-
-        let num_spaces = if pretty_print { 2 } else { 0 };
-        state.set_manifest_format(ManifestFormat::Json(num_spaces));
-
-        Ok(state
-            .manifest(result)
-            .map_err(|err| self.format_trace(&state, err))?
-            .to_string())
+        })
     }
 
     /// Extract the template parameters (if the template exports a top-level function)
@@ -233,11 +257,8 @@ impl TemplateExpander {
     /// Inject the external variables and evaluate the template.
     /// If the template exports a function, the returned Val will be a FuncVal
     /// and it will need to be evaluated again against the Top-Level Arguments.
-    fn expand_template_inner(
-        &self,
-        template: impl AsRef<str>,
-    ) -> Result<(EvaluationState, Val), Error> {
-        let state = EvaluationState::default();
+    fn expand_template_inner(&self, template: impl AsRef<str>) -> Result<(Evaluation, Val), Error> {
+        let state = Evaluation::new(self.resolver.clone());
         state.with_stdlib();
         state.set_import_resolver(Box::new(YouCanCheckOutAnyLibYouWantButOnlyFiberNet));
         if let Some(stack_size) = self.max_stack {
@@ -275,6 +296,31 @@ impl TemplateExpander {
     }
 }
 
+/// Evaluation state that can be used to include snippets and templates.
+struct Evaluation {
+    state: EvaluationState,
+    _includes: Includes,
+}
+
+impl Evaluation {
+    fn new(resolver: Option<Rc<dyn TemplateResolver>>) -> Self {
+        let state = EvaluationState::default();
+        let includes = Includes::register(&state, resolver);
+        Self {
+            state,
+            _includes: includes,
+        }
+    }
+}
+
+impl Deref for Evaluation {
+    type Target = EvaluationState;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
+
 /// This is an import resolver that only loads the fiberplane library.
 ///
 /// It works for imports of any path that ends with the filename "fiberplane.libsonnet".
@@ -0,0 +1,233 @@
+use jrsonnet_evaluator::error::{Error as JsonnetError, LocError};
+use jrsonnet_evaluator::native::{NativeCallback, NativeCallbackHandler};
+use jrsonnet_evaluator::{EvaluationState, FuncVal, IStr, Val};
+use jrsonnet_gc::{unsafe_empty_trace, Finalize, Gc, Trace};
+use jrsonnet_parser::{Param, ParamsDesc};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Name of the native function that is called by `fp.useSnippet`
+const USE_SNIPPET: &str = "fiberplane.useSnippet";
+/// Name of the native function that is called by `fp.useTemplate`
+const USE_TEMPLATE: &str = "fiberplane.useTemplate";
+
+/// Provides the sources of the snippets and templates that a template can
+/// include by name, using `fp.useSnippet(name, args)` and
+/// `fp.useTemplate(name, args)`.
+///
+/// This is implemented for closures that resolve snippet names, so a
+/// `HashMap` of snippets can be used like this:
+///
+/// ```rust
+/// # use fiberplane_templates::TemplateExpander;
+/// # use std::collections::HashMap;
+/// let snippets = HashMap::from([(
+///     "oncall-checklist".to_string(),
+///     "local fp = import 'fiberplane.libsonnet';
+///     function(service) fp.snippet([fp.cell.checkbox('Page the ' + service + ' on-call')])"
+///         .to_string(),
+/// )]);
+///
+/// let mut expander = TemplateExpander::default();
+/// expander.set_resolver(move |name: &str| snippets.get(name).cloned());
+/// ```
+pub trait TemplateResolver {
+    /// Return the source of the snippet with the given name, if it exists.
+    fn resolve_snippet(&self, name: &str) -> Option<String>;
+
+    /// Return the source of the template with the given name, if it exists.
+    fn resolve_template(&self, _name: &str) -> Option<String> {
+        None
+    }
+}
+
+impl<F> TemplateResolver for F
+where
+    F: Fn(&str) -> Option<String>,
+{
+    fn resolve_snippet(&self, name: &str) -> Option<String> {
+        self(name)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SourceKind {
+    Snippet,
+    Template,
+}
+
+impl SourceKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SourceKind::Snippet => "snippet",
+            SourceKind::Template => "template",
+        }
+    }
+}
+
+/// Keeps the native functions used by `fp.useSnippet` and `fp.useTemplate`
+/// registered in an evaluation state.
+///
+/// The handlers need a reference to the evaluation state, so that the included
+/// sources are evaluated in the same state as the template that uses them.
+/// That reference is released again when this is dropped, so that the state
+/// is never dropped by the garbage collector (which would panic).
+pub(crate) struct Includes {
+    state: EvaluationState,
+    shared_state: Rc<RefCell<Option<EvaluationState>>>,
+}
+
+impl Includes {
+    pub fn register(state: &EvaluationState, resolver: Option<Rc<dyn TemplateResolver>>) -> Self {
+        let shared_state = Rc::new(RefCell::new(Some(state.clone())));
+        register_natives(state, resolver, &shared_state);
+        Self {
+            state: state.clone(),
+            shared_state,
+        }
+    }
+}
+
+impl Drop for Includes {
+    fn drop(&mut self) {
+        self.shared_state.borrow_mut().take();
+
+        let mut settings = self.state.settings_mut();
+        settings.ext_natives.remove(&IStr::from(USE_SNIPPET));
+        settings.ext_natives.remove(&IStr::from(USE_TEMPLATE));
+    }
+}
+
+fn register_natives(
+    state: &EvaluationState,
+    resolver: Option<Rc<dyn TemplateResolver>>,
+    shared_state: &Rc<RefCell<Option<EvaluationState>>>,
+) {
+    let in_progress = Rc::new(RefCell::new(HashSet::new()));
+    for (name, kind) in [
+        (USE_SNIPPET, SourceKind::Snippet),
+        (USE_TEMPLATE, SourceKind::Template),
+    ] {
+        let handler = UseSourceHandler {
+            kind,
+            resolver: resolver.clone(),
+            state: shared_state.clone(),
+            in_progress: in_progress.clone(),
+        };
+        state.add_native(
+            name.into(),
+            Gc::new(NativeCallback::new(
+                ParamsDesc(Rc::new(vec![
+                    Param("name".into(), None),
+                    Param("args".into(), None),
+                ])),
+                Box::new(handler),
+            )),
+        );
+    }
+}
+
+struct UseSourceHandler {
+    kind: SourceKind,
+    resolver: Option<Rc<dyn TemplateResolver>>,
+    /// The state in which the template is evaluated, until it is dropped
+    state: Rc<RefCell<Option<EvaluationState>>>,
+    /// Names of the snippets and templates that are currently being
+    /// evaluated, used to detect circular includes
+    in_progress: Rc<RefCell<HashSet<(&'static str, String)>>>,
+}
+
+impl Finalize for UseSourceHandler {}
+
+// SAFETY: tracing only needs to reach the `Gc` pointers a value owns, and
+// none of the handler's fields own one: the kind, the resolver and the set of
+// names in progress contain no garbage-collected values at all. The
+// evaluation state does, but those values are rooted by the state itself for
+// as long as it exists, and the handler's reference to the state is released
+// (see `Includes::drop`) before the state can be dropped. An empty trace
+// therefore never causes a reachable value to be collected.
+unsafe impl Trace for UseSourceHandler {
+    unsafe_empty_trace!();
+}
+
+impl NativeCallbackHandler for UseSourceHandler {
+    fn call(&self, _from: Option<Rc<Path>>, args: &[Val]) -> Result<Val, LocError> {
+        let kind = self.kind.as_str();
+        let name = match args.first() {
+            Some(Val::Str(name)) => name.to_string(),
+            _ => return Err(runtime_error(format!("{kind} name must be a string"))),
+        };
+        let tlas = match args.get(1) {
+            Some(Val::Obj(obj)) => obj
+                .fields()
+                .into_iter()
+                .filter_map(|key| match obj.get(key.clone()) {
+                    Ok(Some(value)) => Some(Ok((key, value))),
+                    Ok(None) => None,
+                    Err(err) => Some(Err(err)),
+                })
+                .collect::<Result<HashMap<IStr, Val>, LocError>>()?,
+            Some(Val::Null) | None => HashMap::new(),
+            _ => return Err(runtime_error(format!("{kind} arguments must be an object"))),
+        };
+
+        let source = self
+            .resolver
+            .as_ref()
+            .and_then(|resolver| match self.kind {
+                SourceKind::Snippet => resolver.resolve_snippet(&name),
+                SourceKind::Template => resolver.resolve_template(&name),
+            })
+            .ok_or_else(|| runtime_error(format!("unknown {kind}: {name}")))?;
+
+        let key = (kind, name);
+        if !self.in_progress.borrow_mut().insert(key.clone()) {
+            return Err(runtime_error(format!("{kind} includes itself: {}", key.1)));
+        }
+        let result = self.evaluate(&key.1, source, &tlas);
+        self.in_progress.borrow_mut().remove(&key);
+        result
+    }
+}
+
+impl UseSourceHandler {
+    fn evaluate(
+        &self,
+        name: &str,
+        source: String,
+        tlas: &HashMap<IStr, Val>,
+    ) -> Result<Val, LocError> {
+        let state =
+            self.state.borrow().clone().ok_or_else(|| {
+                runtime_error("template is no longer being evaluated".to_string())
+            })?;
+        let path = PathBuf::from(format!("{}:{name}", self.kind.as_str()));
+        let result = state.evaluate_snippet_raw(path.into(), source.into())?;
+
+        // Like the top-level arguments of a template, only pass the arguments
+        // that correspond to parameters of the function
+        match result {
+            Val::Func(func) => {
+                let tlas = match func.as_ref() {
+                    FuncVal::Normal(normal) => {
+                        let params: HashSet<&str> = normal.params.0.iter().map(|p| &*p.0).collect();
+                        tlas.iter()
+                            .filter(|(name, _)| params.contains(&***name))
+                            .map(|(name, value)| (name.clone(), value.clone()))
+                            .collect()
+                    }
+                    _ => tlas.clone(),
+                };
+                state
+                    .run_in_state(|| func.evaluate_map(state.create_default_context(), &tlas, true))
+            }
+            result => Ok(result),
+        }
+    }
+}
+
+fn runtime_error(message: String) -> LocError {
+    LocError::new(JsonnetError::RuntimeError(message.into()))
+}
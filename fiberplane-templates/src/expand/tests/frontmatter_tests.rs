@@ -265,3 +265,91 @@ fn expands_add_frontmatter_user() {
     .into();
     assert_eq!(output.front_matter, expected_front_matter);
 }
+
+// Test turning front matter values into parameters when converting a notebook
+#[test]
+fn parameterizes_frontmatter_in_template() {
+    let notebook = NewNotebook::builder()
+        .title("Incident")
+        .time_range(NewTimeRange::Relative(RelativeTimeRange::from_minutes(-60)))
+        .cells(vec![Cell::Text(
+            TextCell::builder().id("1").content("Status").build(),
+        )])
+        .front_matter_schema(FrontMatterSchema::from(vec![
+            FrontMatterSchemaEntry::builder()
+                .key("impact")
+                .schema(
+                    FrontMatterNumberSchema::builder()
+                        .display_name("Impact")
+                        .build(),
+                )
+                .build(),
+            FrontMatterSchemaEntry::builder()
+                .key("status")
+                .schema(
+                    FrontMatterStringSchema::builder()
+                        .display_name("Status")
+                        .build(),
+                )
+                .build(),
+        ]))
+        .front_matter(
+            [
+                ("impact".to_string(), json!(3).into()),
+                ("status".to_string(), json!("open").into()),
+            ]
+            .into(),
+        )
+        .build();
+    let options = NotebookToTemplateOptions {
+        parameterize_front_matter: true,
+        ..Default::default()
+    };
+    let template = notebook_to_template_with_options(notebook.clone(), &options);
+
+    let params = extract_template_parameters(&template).unwrap();
+    assert_eq!(
+        params,
+        [
+            TemplateParameter::builder()
+                .name("frontMatterImpact".to_string())
+                .default_value(json!(3.0))
+                .ty(TemplateParameterType::Number)
+                .build(),
+            TemplateParameter::builder()
+                .name("frontMatterStatus".to_string())
+                .default_value(json!("open"))
+                .ty(TemplateParameterType::String)
+                .build(),
+        ]
+    );
+    for substitution in [
+        "'impact': frontMatterImpact,",
+        "'status': frontMatterStatus,",
+    ] {
+        assert!(
+            template.contains(substitution),
+            "unexpected template: {template}"
+        );
+    }
+
+    // The default values reproduce the original notebook
+    let actual = expand_template(&template, ARGS).unwrap();
+    assert_eq!(actual, notebook);
+
+    let actual = expand_template(
+        &template,
+        [
+            ("frontMatterImpact", json!(1)),
+            ("frontMatterStatus", json!("resolved")),
+        ],
+    )
+    .unwrap();
+    let expected_front_matter: BTreeMap<String, FrontMatterValue> = [
+        ("impact".to_string(), json!(1).into()),
+        ("status".to_string(), json!("resolved").into()),
+    ]
+    .into();
+    assert_eq!(actual.front_matter, expected_front_matter);
+    assert_eq!(actual.front_matter_schema, notebook.front_matter_schema);
+}
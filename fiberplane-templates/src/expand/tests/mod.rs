@@ -4,7 +4,9 @@ use super::*;
 use crate::types::{TemplateParameter, TemplateParameterType};
 use crate::*;
 use fiberplane_models::data_sources::SelectedDataSource;
-use fiberplane_models::formatting::{Annotation, AnnotationWithOffset, Formatting, Mention};
+use fiberplane_models::formatting::{
+    Annotation, AnnotationWithOffset, Formatting, Mention, RichText,
+};
 use fiberplane_models::names::Name;
 use fiberplane_models::notebooks::*;
 use fiberplane_models::timestamps::{NewTimeRange, RelativeTimeRange, Timestamp};
@@ -177,7 +179,7 @@ fn expands_without_top_level_function() {
     let template = "{title: 'hello'}";
     let expander = TemplateExpander::default();
     let output = expander
-        .expand_template_to_string(
+        .expand_to_string(
             template,
             [("not used".to_string(), Value::String("value".to_string()))],
             false,
@@ -192,18 +194,14 @@ fn accepts_map_or_hashmap() {
     let expander = TemplateExpander::default();
     let args: HashMap<_, _> =
         HashMap::from_iter([("title", Value::String("my title".to_string()))]);
-    let output = expander
-        .expand_template_to_string(template, args, false)
-        .unwrap();
+    let output = expander.expand_to_string(template, args, false).unwrap();
     assert_eq!(output, "{\"title\": \"my title\"}");
 
     let args = Map::from_iter([(
         "title".to_string(),
         Value::String("other title".to_string()),
     )]);
-    let output = expander
-        .expand_template_to_string(template, args, false)
-        .unwrap();
+    let output = expander.expand_to_string(template, args, false).unwrap();
     assert_eq!(output, "{\"title\": \"other title\"}");
 }
 
@@ -212,9 +210,7 @@ fn accepts_non_value_args() {
     let template = "function(title) { title: title }";
     let expander = TemplateExpander::default();
     let args: HashMap<_, _> = HashMap::from_iter([("title", "my title".to_string())]);
-    let output = expander
-        .expand_template_to_string(template, args, false)
-        .unwrap();
+    let output = expander.expand_to_string(template, args, false).unwrap();
     assert_eq!(output, "{\"title\": \"my title\"}");
 }
 
@@ -223,7 +219,7 @@ fn expands_if_tlas_not_used() {
     let template = "function(title='hello') {title: title}";
     let expander = TemplateExpander::default();
     let output = expander
-        .expand_template_to_string(
+        .expand_to_string(
             template,
             [
                 ("not used".to_string(), Value::String("value".to_string())),
@@ -566,7 +562,7 @@ fn formatting_basic() {
         ]
     );
 
-    assert_eq!(cells[7].content, "@Bob");
+    assert_eq!(cells[7].content, "@Bob Bobsen");
     assert_eq!(
         &cells[7].formatting,
         &[AnnotationWithOffset::new(
@@ -794,3 +790,356 @@ fn export_cells_to_snippet_and_back() {
     let actual = expand_snippet(snippet).unwrap();
     assert_eq!(actual, *CELLS);
 }
+
+#[test]
+fn export_notebook_to_template_with_options() {
+    let notebook = NewNotebook::builder()
+        .title("Incident: checkout latency")
+        .time_range(NewTimeRange::Relative(RelativeTimeRange::from_minutes(-60)))
+        .selected_data_sources(BTreeMap::from_iter([(
+            "prometheus".to_string(),
+            SelectedDataSource::builder()
+                .name(Name::from_static("prometheus"))
+                .proxy_name(Name::from_static("dev"))
+                .build(),
+        )]))
+        .labels(vec![
+            Label::new("service", "checkout"),
+            Label::new("severity", "high"),
+            Label::new("triage", ""),
+        ])
+        .cells(vec![
+            Cell::Text(
+                TextCell::builder()
+                    .id("1")
+                    .content("The checkout service is slow")
+                    .build(),
+            ),
+            Cell::Provider(
+                ProviderCell::builder()
+                    .id("2")
+                    .intent("prometheus,timeseries")
+                    .query_data("application/x-www-form-urlencoded,query=http_request_duration_seconds%7Bjob%3D%22checkout%22%7D")
+                    .build(),
+            ),
+        ])
+        .build();
+    let options = NotebookToTemplateOptions {
+        parameterize_labels: true,
+        parameterize_data_sources: true,
+        string_parameters: vec![("serviceName".to_string(), "checkout".to_string())],
+        ..Default::default()
+    };
+    let template = notebook_to_template_with_options(notebook.clone(), &options);
+
+    let params = extract_template_parameters(&template).unwrap();
+    let names: Vec<&str> = params.iter().map(|param| param.name.as_str()).collect();
+    assert_eq!(
+        names,
+        &[
+            "serviceName",
+            "labelService",
+            "labelSeverity",
+            "prometheusDataSource",
+            "prometheusProxy"
+        ]
+    );
+
+    // The default values reproduce the original notebook
+    let actual = expand_template(&template, EMPTY_ARGS).unwrap();
+    assert_eq!(actual, notebook);
+
+    let actual = expand_template(
+        &template,
+        [
+            ("serviceName", "payments"),
+            ("labelService", "payments"),
+            ("labelSeverity", "low"),
+            ("prometheusDataSource", "prometheus-prod"),
+            ("prometheusProxy", "prod"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(actual.title, "Incident: payments latency");
+    assert_eq!(
+        actual.labels,
+        &[
+            Label::new("service", "payments"),
+            Label::new("severity", "low"),
+            Label::new("triage", ""),
+        ]
+    );
+    assert_eq!(
+        actual.selected_data_sources.get("prometheus"),
+        Some(
+            &SelectedDataSource::builder()
+                .name(Name::from_static("prometheus-prod"))
+                .proxy_name(Name::from_static("prod"))
+                .build()
+        )
+    );
+    if let Cell::Text(cell) = &actual.cells[0] {
+        assert_eq!(cell.content, "The payments service is slow");
+    } else {
+        panic!("wrong cell type");
+    }
+    if let Cell::Provider(cell) = &actual.cells[1] {
+        assert_eq!(
+            cell.query_data.as_deref(),
+            Some("application/x-www-form-urlencoded,query=http_request_duration_seconds%7Bjob%3D%22payments%22%7D")
+        );
+    } else {
+        panic!("wrong cell type");
+    }
+}
+
+struct Library(HashMap<&'static str, &'static str>);
+
+impl TemplateResolver for Library {
+    fn resolve_snippet(&self, name: &str) -> Option<String> {
+        self.0.get(name).map(|source| source.to_string())
+    }
+
+    fn resolve_template(&self, name: &str) -> Option<String> {
+        self.resolve_snippet(name)
+    }
+}
+
+fn expander_with_library(sources: &[(&'static str, &'static str)]) -> TemplateExpander {
+    let mut expander = TemplateExpander::default();
+    expander.set_resolver(Library(sources.iter().cloned().collect()));
+    expander
+}
+
+static CHECKLIST_SNIPPET: &str = "local fp = import 'fiberplane.libsonnet';
+    function(service, team='on-call') fp.snippet([
+        fp.cell.h2('Checklist for ' + service),
+        fp.cell.checkbox('Page the ' + team + ' team'),
+    ])";
+
+#[test]
+fn use_snippet_with_args() {
+    let expander = expander_with_library(&[("checklist", CHECKLIST_SNIPPET)]);
+    let template = "local fp = import 'fiberplane.libsonnet';
+        function(service='api')
+        fp.notebook.new('Incident')
+        .addCell(fp.cell.text('Intro'))
+        .addCells(fp.useSnippet('checklist', { service: service, unused: true }))";
+    let notebook = expander
+        .expand_template(template, [("service", "checkout")])
+        .unwrap();
+
+    let ids: Vec<&str> = notebook.cells.iter().map(|cell| cell.id()).collect();
+    assert_eq!(ids, ["1", "2", "3"]);
+    assert_eq!(notebook.cells[1].text(), Some("Checklist for checkout"));
+    assert_eq!(notebook.cells[2].text(), Some("Page the on-call team"));
+}
+
+#[test]
+fn use_exported_snippet_in_notebook_with_cells() {
+    let snippet = cells_to_snippet(&[
+        Cell::Provider(
+            ProviderCell::builder()
+                .id("prometheus-cell")
+                .intent("prometheus,timeseries")
+                .query_data("application/x-www-form-urlencoded,query=up")
+                .build(),
+        ),
+        Cell::Graph(
+            GraphCell::builder()
+                .id("graph-cell")
+                .data_links(vec![
+                    "cell-data:application/vnd.fiberplane.timeseries+json,prometheus-cell"
+                        .to_string(),
+                ])
+                .graph_type(GraphType::Line)
+                .stacking_type(StackingType::None)
+                .build(),
+        ),
+    ]);
+    let mut expander = TemplateExpander::default();
+    expander.set_resolver(move |name: &str| (name == "graph").then(|| snippet.clone()));
+    let template = "local fp = import 'fiberplane.libsonnet';
+        fp.notebook.new('Incident')
+        .addCell(fp.cell.prometheus('rate(errors[5m])'))
+        .addCells(fp.useSnippet('graph'))";
+    let notebook = expander.expand_template(template, EMPTY_ARGS).unwrap();
+
+    let ids: Vec<&str> = notebook.cells.iter().map(|cell| cell.id()).collect();
+    assert_eq!(ids, ["1", "2", "3"]);
+
+    // The link must not be rewritten to point at the cell that was already
+    // in the notebook
+    let Cell::Graph(graph) = &notebook.cells[2] else {
+        panic!("expected graph cell");
+    };
+    assert_eq!(
+        graph.data_links,
+        ["cell-data:application/vnd.fiberplane.timeseries+json,prometheus-cell"]
+    );
+}
+
+#[test]
+fn use_template_cells() {
+    let expander = expander_with_library(&[(
+        "incident",
+        "local fp = import 'fiberplane.libsonnet';
+        function(service) fp.notebook.new('Incident: ' + service)
+          .addCell(fp.cell.text('Investigating ' + service))",
+    )]);
+    let template = "local fp = import 'fiberplane.libsonnet';
+        local incident = fp.useTemplate('incident', { service: 'api' });
+        fp.notebook.new(incident.title + ' (follow-up)')
+        .addCells(incident.cells)";
+    let notebook = expander.expand_template(template, EMPTY_ARGS).unwrap();
+
+    assert_eq!(notebook.title, "Incident: api (follow-up)");
+    assert_eq!(notebook.cells[0].text(), Some("Investigating api"));
+}
+
+#[test]
+fn use_unknown_snippet() {
+    let expander = expander_with_library(&[]);
+    let template = "local fp = import 'fiberplane.libsonnet';
+        fp.notebook.new('Incident').addCells(fp.useSnippet('missing'))";
+    let error = expander
+        .expand_template(template, EMPTY_ARGS)
+        .unwrap_err()
+        .to_string();
+
+    assert!(
+        error.contains("unknown snippet: missing"),
+        "unexpected error: {error}"
+    );
+
+    // Without a resolver, no snippets are available at all
+    assert!(expand_template(template, EMPTY_ARGS).is_err());
+}
+
+#[test]
+fn use_snippet_recursively() {
+    let expander = expander_with_library(&[(
+        "loop",
+        "local fp = import 'fiberplane.libsonnet';
+        fp.snippet(fp.useSnippet('loop'))",
+    )]);
+    let template = "local fp = import 'fiberplane.libsonnet';
+        fp.notebook.new('Incident').addCells(fp.useSnippet('loop'))";
+    let error = expander
+        .expand_template(template, EMPTY_ARGS)
+        .unwrap_err()
+        .to_string();
+
+    assert!(
+        error.contains("snippet includes itself: loop"),
+        "unexpected error: {error}"
+    );
+}
+
+#[test]
+fn expand_snippet_with_args() {
+    let cells = TemplateExpander::default()
+        .expand_snippet_with_args(CHECKLIST_SNIPPET, [("service", "api"), ("team", "SRE")])
+        .unwrap();
+
+    assert_eq!(cells.len(), 2);
+    assert_eq!(cells[0].text(), Some("Checklist for api"));
+    assert_eq!(cells[1].text(), Some("Page the SRE team"));
+}
+
+#[test]
+fn data_cells() {
+    let template = "local fp = import 'fiberplane.libsonnet';
+        local c = fp.cell;
+        local fmt = fp.format;
+
+        fp.notebook.new('title')
+        .addCells([
+          c.prometheus('up'),
+          c.graph(['cell-data:application/vnd.fiberplane.timeseries+json,1'], stackingType='stacked'),
+          c.log(visibilityFilter='highlighted', highlightedIndices=[{ linkIndex: 0, recordIndex: 2 }]),
+          c.timeline(),
+          c.discussion('thread1'),
+          c.table(columns=['Service', { id: 'status', title: 'Status' }], rows=[
+            ['api', fmt.bold('down')],
+            { id: 'web1', values: ['web'] },
+          ]),
+        ])";
+    let notebook = expand_template(template, EMPTY_ARGS).unwrap();
+
+    assert_eq!(
+        notebook.cells[1],
+        Cell::Graph(
+            GraphCell::builder()
+                .id("2")
+                .data_links(vec![
+                    "cell-data:application/vnd.fiberplane.timeseries+json,1".to_string()
+                ])
+                .graph_type(GraphType::Line)
+                .stacking_type(StackingType::Stacked)
+                .build()
+        )
+    );
+    assert_eq!(
+        notebook.cells[2],
+        Cell::Log(
+            LogCell::builder()
+                .id("3")
+                .visibility_filter(LogVisibilityFilter::Highlighted)
+                .highlighted_indices(vec![LogRecordIndex::builder()
+                    .link_index(0)
+                    .record_index(2)
+                    .build()])
+                .build()
+        )
+    );
+    assert_eq!(
+        notebook.cells[3],
+        Cell::Timeline(TimelineCell::builder().id("4").build())
+    );
+    assert_eq!(
+        notebook.cells[4],
+        Cell::Discussion(
+            DiscussionCell::builder()
+                .id("5")
+                .thread_id("thread1")
+                .build()
+        )
+    );
+
+    let Cell::Table(table) = &notebook.cells[5] else {
+        panic!("expected table cell");
+    };
+    let column_ids: Vec<String> = table.column_defs.iter().map(|c| c.id.to_string()).collect();
+    assert_eq!(column_ids, ["col1", "status"]);
+    let row_ids: Vec<String> = table.rows.iter().map(|r| r.id.to_string()).collect();
+    assert_eq!(row_ids, ["row1", "web1"]);
+    assert_eq!(
+        table.rows[0].values[1],
+        TableRowValue::Text(RichText::new(
+            "down",
+            vec![
+                AnnotationWithOffset::new(0, Annotation::StartBold),
+                AnnotationWithOffset::new(4, Annotation::EndBold),
+            ]
+        ))
+    );
+    assert_eq!(
+        table.rows[1].values,
+        [TableRowValue::Text(RichText::new("web", vec![]))]
+    );
+}
+
+#[test]
+fn data_cells_validate_options() {
+    let template = "local fp = import 'fiberplane.libsonnet';
+        fp.notebook.new('title').addCell(fp.cell.graph(stackingType='sideways'))";
+    let error = expand_template(template, EMPTY_ARGS)
+        .unwrap_err()
+        .to_string();
+
+    assert!(
+        error.contains("expected stackingType to be one of: none, stacked, percentage"),
+        "unexpected error: {error}"
+    );
+}
@@ -0,0 +1,44 @@
+use serde_json::Value;
+
+/// Return the path and values of the first difference between the two values.
+pub(crate) fn first_difference(
+    expected: &Value,
+    actual: &Value,
+    path: String,
+) -> Option<(String, Value, Value)> {
+    match (expected, actual) {
+        (Value::Object(expected_map), Value::Object(actual_map)) => {
+            let mut keys: Vec<&String> = expected_map.keys().chain(actual_map.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            keys.into_iter().find_map(|key| {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                first_difference(
+                    expected_map.get(key).unwrap_or(&Value::Null),
+                    actual_map.get(key).unwrap_or(&Value::Null),
+                    path,
+                )
+            })
+        }
+        (Value::Array(expected_items), Value::Array(actual_items)) => {
+            let difference = expected_items
+                .iter()
+                .zip(actual_items)
+                .enumerate()
+                .find_map(|(index, (expected, actual))| {
+                    first_difference(expected, actual, format!("{path}[{index}]"))
+                });
+            if difference.is_none() && expected_items.len() != actual_items.len() {
+                Some((path, expected.clone(), actual.clone()))
+            } else {
+                difference
+            }
+        }
+        (expected, actual) if expected != actual => Some((path, expected.clone(), actual.clone())),
+        _ => None,
+    }
+}
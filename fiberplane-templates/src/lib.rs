@@ -68,6 +68,42 @@ fp.snippet([
 ])
 ```
 
+Snippets can also accept parameters by exporting a function, and templates can
+include snippets (or other templates) by name with `fp.useSnippet` and
+`fp.useTemplate`. The application that expands the template decides which
+snippets and templates are available, by setting a `TemplateResolver` on the
+`TemplateExpander`.
+
+```jsonnet
+local fp = import 'fiberplane.libsonnet';
+
+function(service='api')
+  fp.notebook.new('Incident: ' + service)
+  .addCells(fp.useSnippet('oncall-checklist', { service: service }))
+```
+
+## Testing Templates
+
+Templates can be tested by expanding them with sets of arguments and comparing
+the resulting notebooks against stored snapshots. Put every template in its own
+directory, with the arguments to test it with in a `fixtures` directory:
+
+```text
+templates/
+├── incident-response/
+│   ├── template.jsonnet
+│   ├── fixtures/
+│   │   └── high-severity.json
+│   └── snapshots/
+│       └── high-severity.json
+└── snippets/
+    └── oncall-checklist.jsonnet
+```
+
+Then run the `fiberplane-template-test` binary (which requires the `cli`
+feature) on that directory, or use `TemplateTestRunner` from your own tests.
+Pass `--update` to write the snapshots that are missing or have changed.
+
 */
 
 #[cfg(feature = "convert")]
@@ -76,6 +112,12 @@ mod convert;
 pub mod examples;
 #[cfg(feature = "expand")]
 mod expand;
+#[cfg(feature = "expand")]
+mod json_diff;
+#[cfg(all(feature = "convert", feature = "expand"))]
+mod roundtrip;
+#[cfg(feature = "expand")]
+mod test_runner;
 #[cfg(feature = "types")]
 mod types;
 
@@ -85,5 +127,9 @@ pub static FIBERPLANE_LIBRARY_PATH: &str = "fiberplane.libsonnet";
 pub use convert::*;
 #[cfg(feature = "expand")]
 pub use expand::*;
+#[cfg(all(feature = "convert", feature = "expand"))]
+pub use roundtrip::*;
+#[cfg(feature = "expand")]
+pub use test_runner::*;
 #[cfg(feature = "types")]
 pub use types::*;
@@ -0,0 +1,98 @@
+use crate::json_diff::first_difference;
+use crate::{expand_template, notebook_to_template, Error, EMPTY_ARGS};
+use fiberplane_models::notebooks::NewNotebook;
+use fiberplane_models::timestamps::{NewTimeRange, RelativeTimeRange, TimeRange};
+use serde_json::Value;
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(thiserror::Error, Debug)]
+pub enum RoundtripError {
+    #[error("template could not be expanded: {source}")]
+    Expansion {
+        source: Error,
+        /// The template that was generated from the notebook
+        template: String,
+    },
+
+    #[error("template round-trip changed `{path}`: expected {expected}, got {actual}")]
+    Mismatch {
+        /// Path to the first value that differs, such as `cells[2].formatting[0].offset`
+        path: String,
+        expected: Value,
+        actual: Value,
+        /// The template that was generated from the notebook
+        template: String,
+    },
+}
+
+/// Convert the notebook into a template, expand the template again and check
+/// that the result is structurally equal to the original notebook.
+///
+/// The following differences are expected and therefore ignored:
+///
+/// - Cell IDs, which are assigned by the template library. Data links that
+///   refer to other cells are compared by the position of the cell instead.
+/// - The time range, of which only the duration (in whole minutes) is kept.
+/// - The `output` and `response` of provider cells, which are the result of
+///   running the query rather than part of the notebook's structure.
+pub fn roundtrip_check(notebook: impl Into<NewNotebook>) -> Result<(), RoundtripError> {
+    let expected = notebook.into();
+    let template = notebook_to_template(expected.clone());
+    let actual = match expand_template(&template, EMPTY_ARGS) {
+        Ok(actual) => actual,
+        Err(source) => return Err(RoundtripError::Expansion { source, template }),
+    };
+
+    match first_difference(&normalize(expected), &normalize(actual), String::new()) {
+        Some((path, expected, actual)) => Err(RoundtripError::Mismatch {
+            path,
+            expected,
+            actual,
+            template,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Serialize the notebook, leaving out the parts that are not expected to
+/// survive a round-trip.
+fn normalize(mut notebook: NewNotebook) -> Value {
+    let time_range = TimeRange::from(notebook.time_range);
+    let minutes = ((time_range.to - time_range.from).as_seconds_f64() / 60.0).round() as i32;
+    notebook.time_range = NewTimeRange::Relative(RelativeTimeRange::from_minutes(-minutes));
+
+    let cell_positions: HashMap<String, String> = notebook
+        .cells
+        .iter()
+        .enumerate()
+        .map(|(index, cell)| (cell.id().to_string(), index.to_string()))
+        .collect();
+
+    let mut value = serde_json::to_value(notebook).expect("A notebook is always serializable");
+    if let Some(Value::Array(cells)) = value.get_mut("cells") {
+        for cell in cells {
+            if let Value::Object(cell) = cell {
+                cell.remove("id");
+                if let Some(Value::Array(data_links)) = cell.get_mut("dataLinks") {
+                    for data_link in data_links {
+                        if let Value::String(data_link) = data_link {
+                            if let Some((prefix, cell_id)) = data_link.rsplit_once(',') {
+                                if let Some(position) = cell_positions.get(cell_id) {
+                                    *data_link = format!("{prefix},#{position}");
+                                }
+                            }
+                        }
+                    }
+                }
+                if cell.get("type").and_then(Value::as_str) == Some("provider") {
+                    cell.remove("output");
+                    cell.remove("response");
+                }
+            }
+        }
+    }
+    value
+}
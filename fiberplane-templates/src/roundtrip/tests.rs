@@ -0,0 +1,604 @@
+use super::*;
+use base64uuid::Base64Uuid;
+use fiberplane_models::data_sources::SelectedDataSource;
+use fiberplane_models::formatting::{
+    Annotation, AnnotationWithOffset, Formatting, Mention, RichText,
+};
+use fiberplane_models::labels::Label;
+use fiberplane_models::names::Name;
+use fiberplane_models::notebooks::front_matter::*;
+use fiberplane_models::notebooks::*;
+use fiberplane_models::timestamps::Timestamp;
+use fiberplane_models::utils::char_count;
+use proptest::prelude::*;
+use serde_json::json;
+use time::OffsetDateTime;
+
+/// Plain text, including characters that need escaping and multi-byte characters.
+fn text() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9 '\"\\\\\n{}.:/_-]{0,12}|[a-z ]{0,4}(🦀|👀|é|ß)[a-z ]{0,4}"
+}
+
+fn non_empty_text() -> impl Strategy<Value = String> {
+    text().prop_filter("text must not be empty", |text| !text.is_empty())
+}
+
+fn label() -> impl Strategy<Value = Label> {
+    (
+        "[a-z0-9]([a-z0-9._-]{0,8}[a-z0-9])?",
+        "|[a-zA-Z0-9]([a-zA-Z0-9._-]{0,8}[a-zA-Z0-9])?",
+    )
+        .prop_map(|(key, value)| Label::new(key, value))
+}
+
+fn timestamp() -> impl Strategy<Value = Timestamp> {
+    (0i64..4_000_000_000, 0i64..1000).prop_map(|(seconds, millis)| {
+        OffsetDateTime::from_unix_timestamp(seconds)
+            .unwrap()
+            .replace_millisecond(millis as u16)
+            .unwrap()
+            .into()
+    })
+}
+
+/// Form-encode the value the same way as `encodeFormComponent` in the
+/// Jsonnet library, so the query data survives the round-trip unchanged.
+fn encode_form_component(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            ' ' => "+".to_string(),
+            '\u{8}' | '\t' | '\n' | '\u{c}' | '\r' | '!' | '"' | '#' | '$' | ',' | '^' | '&'
+            | '\'' | '(' | ')' | '+' | '/' | ':' | ';' | '=' | '?' | '@' | '[' | '\\' | ']'
+            | '`' | '{' | '|' | '}' | '~' | '%' => format!("%{:02X}", c as u32),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+enum Format {
+    Bold,
+    Code,
+    Highlight,
+    Italics,
+    Link(String),
+    Strikethrough,
+    Underline,
+}
+
+impl Format {
+    fn annotations(&self) -> (Annotation, Annotation) {
+        match self {
+            Format::Bold => (Annotation::StartBold, Annotation::EndBold),
+            Format::Code => (Annotation::StartCode, Annotation::EndCode),
+            Format::Highlight => (Annotation::StartHighlight, Annotation::EndHighlight),
+            Format::Italics => (Annotation::StartItalics, Annotation::EndItalics),
+            Format::Link(url) => (
+                Annotation::StartLink { url: url.clone() },
+                Annotation::EndLink,
+            ),
+            Format::Strikethrough => (Annotation::StartStrikethrough, Annotation::EndStrikethrough),
+            Format::Underline => (Annotation::StartUnderline, Annotation::EndUnderline),
+        }
+    }
+}
+
+/// A piece of formatted content.
+#[derive(Clone, Debug)]
+enum Segment {
+    /// Text with zero or more (nested) formats applied to it
+    Text(String, Vec<Format>),
+    Mention(Mention),
+    Timestamp(Timestamp),
+    Label(Label),
+}
+
+fn format() -> impl Strategy<Value = Format> {
+    prop_oneof![
+        Just(Format::Bold),
+        Just(Format::Code),
+        Just(Format::Highlight),
+        Just(Format::Italics),
+        "https://example\\.com/[a-z']{0,8}".prop_map(Format::Link),
+        Just(Format::Strikethrough),
+        Just(Format::Underline),
+    ]
+}
+
+fn segment() -> impl Strategy<Value = Segment> {
+    prop_oneof![
+        4 => (non_empty_text(), prop::collection::vec(format(), 0..3))
+            .prop_map(|(text, formats)| Segment::Text(text, formats)),
+        1 => ("[A-Z][a-z' ]{0,8}", "[a-zA-Z0-9]{4,8}").prop_map(|(name, user_id)| {
+            Segment::Mention(Mention::builder().name(name).user_id(user_id).build())
+        }),
+        1 => timestamp().prop_map(Segment::Timestamp),
+        1 => label().prop_map(Segment::Label),
+    ]
+}
+
+/// Content and formatting, built from segments so the annotations are always
+/// well-nested and match the text they apply to.
+fn formatted_content() -> impl Strategy<Value = (String, Formatting)> {
+    prop::collection::vec(segment(), 0..5).prop_map(|segments| {
+        let mut content = String::new();
+        let mut formatting = Formatting::new();
+        for segment in segments {
+            let offset = char_count(&content);
+            match segment {
+                Segment::Text(text, formats) => {
+                    for format in &formats {
+                        formatting.push(AnnotationWithOffset::new(offset, format.annotations().0));
+                    }
+                    content.push_str(&text);
+                    let end = char_count(&content);
+                    for format in formats.iter().rev() {
+                        formatting.push(AnnotationWithOffset::new(end, format.annotations().1));
+                    }
+                }
+                Segment::Mention(mention) => {
+                    content.push('@');
+                    content.push_str(&mention.name);
+                    formatting.push(AnnotationWithOffset::new(
+                        offset,
+                        Annotation::Mention(mention),
+                    ));
+                }
+                Segment::Timestamp(timestamp) => {
+                    content.push_str(&timestamp.to_string());
+                    formatting.push(AnnotationWithOffset::new(
+                        offset,
+                        Annotation::Timestamp { timestamp },
+                    ));
+                }
+                Segment::Label(label) => {
+                    content.push_str(&label.key);
+                    if !label.value.is_empty() {
+                        content.push(':');
+                        content.push_str(&label.value);
+                    }
+                    formatting.push(AnnotationWithOffset::new(offset, Annotation::Label(label)));
+                }
+            }
+        }
+        (content, formatting)
+    })
+}
+
+fn read_only() -> impl Strategy<Value = Option<bool>> {
+    prop_oneof![Just(None), Just(Some(true))]
+}
+
+/// Data links either refer to the cell itself or to one of the cells in the
+/// notebook (see [notebook]), which may not exist.
+fn data_links() -> impl Strategy<Value = Vec<String>> {
+    prop::collection::vec(
+        (
+            prop_oneof![
+                Just("application/vnd.fiberplane.timeseries+json"),
+                Just("application/vnd.fiberplane.events+json"),
+            ],
+            prop_oneof![Just("self".to_string()), "cell-[0-9]"],
+        )
+            .prop_map(|(mime_type, cell_id)| format!("cell-data:{mime_type},{cell_id}")),
+        0..3,
+    )
+}
+
+fn log_record_indices() -> impl Strategy<Value = Option<Vec<LogRecordIndex>>> {
+    prop::option::of(prop::collection::vec(
+        (0u8..4, 0u32..1000).prop_map(|(link_index, record_index)| {
+            LogRecordIndex::builder()
+                .link_index(link_index)
+                .record_index(record_index)
+                .build()
+        }),
+        1..3,
+    ))
+}
+
+fn table_cell() -> impl Strategy<Value = TableCell> {
+    // Columns and rows either use the IDs generated by the template library,
+    // or explicit ones
+    let id = || prop::option::of("[a-zA-Z0-9]{4,8}");
+    let id_or_generated = |id: Option<String>, prefix: &str, index: usize| {
+        id.unwrap_or(format!("{prefix}{}", index + 1))
+    };
+    (0usize..4, read_only())
+        .prop_flat_map(move |(num_columns, read_only)| {
+            (
+                prop::collection::vec((id(), text()), num_columns),
+                prop::collection::vec(
+                    (
+                        id(),
+                        prop::collection::vec(formatted_content(), num_columns),
+                    ),
+                    0..3,
+                ),
+                Just(read_only),
+            )
+        })
+        .prop_map(move |(columns, rows, read_only)| {
+            let mut cell = TableCell::builder()
+                .id("")
+                .column_defs(
+                    columns
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, (id, title))| {
+                            TableColumnDefinition::builder()
+                                .id(id_or_generated(id, "col", index).parse().unwrap())
+                                .title(title)
+                                .build()
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .rows(
+                    rows.into_iter()
+                        .enumerate()
+                        .map(|(index, (id, values))| {
+                            TableRow::builder()
+                                .id(id_or_generated(id, "row", index).parse().unwrap())
+                                .values(
+                                    values
+                                        .into_iter()
+                                        .map(|(text, formatting)| {
+                                            TableRowValue::Text(RichText::new(text, formatting))
+                                        })
+                                        .collect::<Vec<_>>(),
+                                )
+                                .build()
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .build();
+            cell.read_only = read_only;
+            cell
+        })
+}
+
+fn cell() -> impl Strategy<Value = Cell> {
+    prop_oneof![
+        (
+            formatted_content(),
+            any::<bool>(),
+            prop::option::of(1u8..4),
+            read_only()
+        )
+            .prop_map(|((content, formatting), checked, level, read_only)| {
+                let mut cell = CheckboxCell::builder()
+                    .content(content)
+                    .formatting(formatting)
+                    .checked(checked)
+                    .build();
+                cell.level = level;
+                cell.read_only = read_only;
+                Cell::Checkbox(cell)
+            }),
+        (text(), prop::option::of("[a-z]{1,8}"), read_only()).prop_map(
+            |(content, syntax, read_only)| {
+                let mut cell = CodeCell::builder().content(content).build();
+                cell.syntax = syntax;
+                cell.read_only = read_only;
+                Cell::Code(cell)
+            }
+        ),
+        read_only().prop_map(|read_only| {
+            let mut cell = DividerCell::builder().build();
+            cell.read_only = read_only;
+            Cell::Divider(cell)
+        }),
+        (
+            formatted_content(),
+            prop_oneof![
+                Just(HeadingType::H1),
+                Just(HeadingType::H2),
+                Just(HeadingType::H3)
+            ],
+            read_only()
+        )
+            .prop_map(|((content, formatting), heading_type, read_only)| {
+                let mut cell = HeadingCell::builder()
+                    .content(content)
+                    .formatting(formatting)
+                    .heading_type(heading_type)
+                    .build();
+                cell.read_only = read_only;
+                Cell::Heading(cell)
+            }),
+        (
+            prop::option::of("https://example\\.com/[a-z']{0,8}\\.png"),
+            read_only()
+        )
+            .prop_map(|(url, read_only)| {
+                let mut cell = ImageCell::builder().build();
+                cell.url = url;
+                cell.read_only = read_only;
+                Cell::Image(cell)
+            }),
+        (
+            formatted_content(),
+            prop_oneof![Just(ListType::Ordered), Just(ListType::Unordered)],
+            prop::option::of(1u8..4),
+            prop::option::of(1u16..100),
+            read_only()
+        )
+            .prop_map(
+                |((content, formatting), list_type, level, start_number, read_only)| {
+                    let mut cell = ListItemCell::builder()
+                        .content(content)
+                        .formatting(formatting)
+                        .list_type(list_type)
+                        .build();
+                    cell.level = level;
+                    cell.start_number = start_number;
+                    cell.read_only = read_only;
+                    Cell::ListItem(cell)
+                }
+            ),
+        (
+            prop_oneof![
+                Just("prometheus,timeseries"),
+                Just("elasticsearch,events"),
+                Just("loki,events"),
+                Just("cloudwatch,x-list-metrics"),
+            ],
+            prop::option::of(non_empty_text()),
+            read_only()
+        )
+            .prop_map(|(intent, query, read_only)| {
+                let mut cell = ProviderCell::builder().intent(intent).build();
+                cell.query_data = query.map(|query| {
+                    format!(
+                        "application/x-www-form-urlencoded,query={}",
+                        encode_form_component(&query)
+                    )
+                });
+                cell.read_only = read_only;
+                Cell::Provider(cell)
+            }),
+        (formatted_content(), read_only()).prop_map(|((content, formatting), read_only)| {
+            let mut cell = TextCell::builder()
+                .content(content)
+                .formatting(formatting)
+                .build();
+            cell.read_only = read_only;
+            Cell::Text(cell)
+        }),
+        ("[a-zA-Z0-9]{0,12}", read_only()).prop_map(|(thread_id, read_only)| {
+            let mut cell = DiscussionCell::builder().thread_id(thread_id).build();
+            cell.read_only = read_only;
+            Cell::Discussion(cell)
+        }),
+        (
+            data_links(),
+            prop_oneof![Just(GraphType::Bar), Just(GraphType::Line)],
+            prop_oneof![
+                Just(StackingType::None),
+                Just(StackingType::Stacked),
+                Just(StackingType::Percentage)
+            ],
+            read_only()
+        )
+            .prop_map(|(data_links, graph_type, stacking_type, read_only)| {
+                let mut cell = GraphCell::builder()
+                    .data_links(data_links)
+                    .graph_type(graph_type)
+                    .stacking_type(stacking_type)
+                    .build();
+                cell.read_only = read_only;
+                Cell::Graph(cell)
+            }),
+        (
+            data_links(),
+            prop::option::of(prop::collection::vec("[a-z_.]{1,8}", 1..3)),
+            prop::option::of(any::<bool>()),
+            prop::option::of(prop_oneof![
+                Just(LogVisibilityFilter::All),
+                Just(LogVisibilityFilter::Selected),
+                Just(LogVisibilityFilter::Highlighted)
+            ]),
+            (
+                log_record_indices(),
+                log_record_indices(),
+                log_record_indices()
+            ),
+            read_only()
+        )
+            .prop_map(
+                |(
+                    data_links,
+                    display_fields,
+                    hide_similar_values,
+                    visibility_filter,
+                    (expanded_indices, selected_indices, highlighted_indices),
+                    read_only,
+                )| {
+                    let mut cell = LogCell::builder().data_links(data_links).build();
+                    cell.display_fields = display_fields;
+                    cell.hide_similar_values = hide_similar_values;
+                    cell.visibility_filter = visibility_filter;
+                    cell.expanded_indices = expanded_indices;
+                    cell.selected_indices = selected_indices;
+                    cell.highlighted_indices = highlighted_indices;
+                    cell.read_only = read_only;
+                    Cell::Log(cell)
+                }
+            ),
+        table_cell().prop_map(Cell::Table),
+        (data_links(), read_only()).prop_map(|(data_links, read_only)| {
+            let mut cell = TimelineCell::builder().data_links(data_links).build();
+            cell.read_only = read_only;
+            Cell::Timeline(cell)
+        }),
+    ]
+}
+
+fn front_matter_value() -> impl Strategy<Value = FrontMatterValue> {
+    let number = || (-1_000_000i64..1_000_000).prop_map(|n| n as f64 / 8.0);
+    let string = || "[a-zA-Z0-9 '\"]{0,12}";
+    let user = || {
+        // The last character of a Base64Uuid only encodes two bits
+        ("[a-zA-Z0-9_-]{21}[AQgw]", "[a-zA-Z ]{1,12}").prop_map(
+            |(id, name)| json!({ "id": Base64Uuid::parse_str(&id).unwrap(), "name": name }),
+        )
+    };
+    prop_oneof![
+        number().prop_map(|n| FrontMatterNumberValue::from(n).into()),
+        prop::collection::vec(number(), 0..3).prop_map(|n| FrontMatterNumberList::try_from(json!(
+            n
+        ))
+        .unwrap()
+        .into()),
+        string().prop_map(|s| FrontMatterStringValue::from(s).into()),
+        prop::collection::vec(string(), 0..3).prop_map(|s| FrontMatterStringList::try_from(json!(
+            s
+        ))
+        .unwrap()
+        .into()),
+        timestamp().prop_map(|t| FrontMatterDateTimeValue::from(t).into()),
+        prop::collection::vec(timestamp(), 0..3).prop_map(|t| FrontMatterDateTimeList::try_from(
+            json!(t)
+        )
+        .unwrap()
+        .into()),
+        user().prop_map(|u| FrontMatterUserValue::try_from(u).unwrap().into()),
+        prop::collection::vec(user(), 0..3)
+            .prop_map(|u| FrontMatterUserList::try_from(json!(u)).unwrap().into()),
+        (string(), 1u64..1000, timestamp()).prop_map(|(title, number, created_at)| {
+            FrontMatterPagerDutyIncident::try_from(json!({
+                "incidentId": "Q1W2E3R4",
+                "number": number,
+                "title": title,
+                "status": "triggered",
+                "createdAt": created_at,
+                "updatedAt": created_at,
+                "apiUrl": "https://api.pagerduty.com/incidents/Q1W2E3R4",
+                "htmlUrl": "https://example.pagerduty.com/incidents/Q1W2E3R4",
+                "incidentKey": "key",
+                "assignees": [],
+                "teams": [],
+            }))
+            .unwrap()
+            .into()
+        }),
+        (string(), 1u64..1000, any::<bool>(), timestamp()).prop_map(
+            |(title, number, draft, created_at)| {
+                FrontMatterGitHubPullRequest::try_from(json!({
+                    "htmlUrl": "https://github.com/fiberplane/fiberplane/pull/1",
+                    "id": 1,
+                    "repoOwner": "fiberplane",
+                    "repoName": "fiberplane",
+                    "number": number,
+                    "title": title,
+                    "branch": "main",
+                    "commits": 3,
+                    "author": "octocat",
+                    "authorAvatarUrl": "https://example.com/avatar.png",
+                    "assignee": null,
+                    "assigneeAvatarUrl": null,
+                    "labels": ["bug"],
+                    "reviewers": [],
+                    "state": "open",
+                    "draft": draft,
+                    "merged": false,
+                    "createdAt": created_at,
+                    "updatedAt": created_at,
+                }))
+                .unwrap()
+                .into()
+            }
+        ),
+    ]
+}
+
+fn selected_data_source() -> impl Strategy<Value = SelectedDataSource> {
+    (
+        "[a-z][a-z0-9-]{0,8}[a-z0-9]",
+        prop::option::of("[a-z][a-z0-9-]{0,8}[a-z0-9]"),
+    )
+        .prop_map(|(name, proxy_name)| {
+            let mut data_source = SelectedDataSource::builder()
+                .name(Name::new(name).unwrap())
+                .build();
+            data_source.proxy_name = proxy_name.map(|proxy_name| Name::new(proxy_name).unwrap());
+            data_source
+        })
+}
+
+fn notebook() -> impl Strategy<Value = NewNotebook> {
+    (
+        text(),
+        1i32..10_000,
+        prop::collection::vec(label(), 0..4),
+        prop::collection::btree_map("[a-z][a-z_-]{0,8}", front_matter_value(), 0..4),
+        prop::collection::btree_map("[a-z][a-z_-]{0,8}", selected_data_source(), 0..3),
+        prop::collection::vec(cell(), 0..8),
+    )
+        .prop_map(
+            |(title, minutes, mut labels, front_matter, selected_data_sources, mut cells)| {
+                // Label keys are unique within a notebook
+                labels.sort_by(|a, b| a.key.cmp(&b.key));
+                labels.dedup_by(|a, b| a.key == b.key);
+
+                // Give the cells IDs that data links can refer to
+                for (index, cell) in cells.iter_mut().enumerate() {
+                    *cell = cell.with_id(&format!("cell-{index}"));
+                }
+
+                NewNotebook::builder()
+                    .title(title)
+                    .time_range(NewTimeRange::Relative(RelativeTimeRange::from_minutes(
+                        -minutes,
+                    )))
+                    .labels(labels)
+                    .front_matter(front_matter)
+                    .selected_data_sources(selected_data_sources)
+                    .cells(cells)
+                    .build()
+            },
+        )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn notebooks_survive_roundtrip(notebook in notebook()) {
+        if let Err(err) = roundtrip_check(notebook) {
+            let template = match &err {
+                RoundtripError::Expansion { template, .. } => template,
+                RoundtripError::Mismatch { template, .. } => template,
+            };
+            return Err(TestCaseError::fail(format!("{err}\n\n{template}")));
+        }
+    }
+}
+
+#[test]
+fn reports_path_of_mismatch() {
+    let notebook = NewNotebook::builder()
+        .title("Invalid labels are filtered out")
+        .time_range(NewTimeRange::Relative(RelativeTimeRange::from_minutes(-60)))
+        .labels(vec![Label::new("valid", ""), Label::new("-invalid", "")])
+        .build();
+    match roundtrip_check(notebook) {
+        Err(RoundtripError::Mismatch { path, .. }) => assert_eq!(path, "labels"),
+        other => panic!("expected a mismatch, got: {other:?}"),
+    }
+}
+
+#[test]
+fn ignores_cell_ids_and_absolute_time_ranges() {
+    let from = Timestamp::parse("2023-01-01T00:00:00Z").unwrap();
+    let to = Timestamp::parse("2023-01-01T02:00:00Z").unwrap();
+    let notebook = NewNotebook::builder()
+        .title("Absolute")
+        .time_range(NewTimeRange::Absolute(TimeRange { from, to }))
+        .cells(vec![Cell::Text(
+            TextCell::builder().id("some-id").content("text").build(),
+        )])
+        .build();
+    roundtrip_check(notebook).unwrap();
+}
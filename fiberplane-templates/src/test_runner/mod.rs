@@ -0,0 +1,326 @@
+use crate::json_diff::first_difference;
+use crate::{TemplateExpander, TemplateResolver};
+use serde_json::{Map, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+mod tests;
+
+/// Name of the file that contains the template, inside the template's directory
+pub const TEMPLATE_FILE_NAME: &str = "template.jsonnet";
+/// Directory (inside the template's directory) with the argument sets to test
+/// the template with, as JSON objects
+pub const FIXTURES_DIR: &str = "fixtures";
+/// Directory (inside the template's directory) with the expected notebooks,
+/// one for every fixture
+pub const SNAPSHOTS_DIR: &str = "snapshots";
+/// Directory (inside the root directory) with the snippets that templates can
+/// include using `fp.useSnippet`
+pub const SNIPPETS_DIR: &str = "snippets";
+/// Name of the fixture that is used for templates without any fixtures
+const DEFAULT_FIXTURE: &str = "default";
+
+#[derive(thiserror::Error, Debug)]
+pub enum TestRunnerError {
+    #[error("could not access {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+
+    #[error("{path} does not contain valid JSON: {source}")]
+    InvalidJson {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+
+    #[error("fixture {path} must contain a JSON object with the template arguments")]
+    InvalidFixture { path: PathBuf },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TestOutcome {
+    /// The expanded notebook matches the snapshot
+    Passed,
+
+    /// The snapshot was missing or did not match, and has been written
+    SnapshotWritten,
+
+    /// There is no snapshot for this fixture yet
+    MissingSnapshot,
+
+    /// The expanded notebook does not match the snapshot
+    Mismatch {
+        /// Path to the first value that differs, such as `cells[2].content`
+        path: String,
+        expected: Value,
+        actual: Value,
+    },
+
+    /// The template could not be expanded with the arguments of the fixture
+    ExpansionFailed(String),
+}
+
+impl TestOutcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Passed | Self::SnapshotWritten)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestResult {
+    /// Name of the template's directory
+    pub template: String,
+    /// Name of the fixture, which is the file name without the extension
+    pub fixture: String,
+    /// Path to the snapshot for this fixture
+    pub snapshot: PathBuf,
+    pub outcome: TestOutcome,
+}
+
+/// Expands a directory of templates with fixture arguments and compares the
+/// resulting notebooks against stored snapshots.
+///
+/// Every subdirectory of the root directory that contains a
+/// `template.jsonnet` file is a template. The directory layout is:
+///
+/// ```text
+/// templates/
+/// ├── incident-response/
+/// │   ├── template.jsonnet
+/// │   ├── fixtures/
+/// │   │   └── high-severity.json    <- arguments, such as {"severity": "high"}
+/// │   └── snapshots/
+/// │       └── high-severity.json    <- the expected `NewNotebook`
+/// └── snippets/
+///     └── oncall-checklist.jsonnet  <- available as fp.useSnippet('oncall-checklist')
+/// ```
+///
+/// Templates without fixtures are expanded once without arguments, using the
+/// snapshot `snapshots/default.json`.
+pub struct TemplateTestRunner {
+    root: PathBuf,
+    expander: TemplateExpander,
+    update_snapshots: bool,
+}
+
+impl TemplateTestRunner {
+    /// Create a runner for the templates in the given directory.
+    ///
+    /// Templates can include the snippets in the `snippets` directory and the
+    /// other templates by their directory name.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let mut expander = TemplateExpander::default();
+        expander.set_resolver(DirectoryResolver::new(&root));
+        Self {
+            root,
+            expander,
+            update_snapshots: false,
+        }
+    }
+
+    /// Use the given expander, instead of one that resolves snippets and
+    /// templates from the root directory.
+    pub fn with_expander(mut self, expander: TemplateExpander) -> Self {
+        self.expander = expander;
+        self
+    }
+
+    /// Write the snapshots that are missing or do not match, instead of
+    /// reporting them as failures.
+    pub fn update_snapshots(mut self, update_snapshots: bool) -> Self {
+        self.update_snapshots = update_snapshots;
+        self
+    }
+
+    /// Run all the templates with all their fixtures.
+    ///
+    /// Only problems with reading the directories and fixtures are returned
+    /// as errors. Failing templates are reported in the results.
+    pub fn run(&self) -> Result<Vec<TestResult>, TestRunnerError> {
+        let mut results = Vec::new();
+        for dir in self.template_dirs()? {
+            let template_path = dir.join(TEMPLATE_FILE_NAME);
+            let template = read_to_string(&template_path)?;
+            let name = dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+
+            for (fixture, args) in fixtures(&dir)? {
+                let snapshot = dir.join(SNAPSHOTS_DIR).join(format!("{fixture}.json"));
+                let outcome = self.run_fixture(&template, args, &snapshot)?;
+                results.push(TestResult {
+                    template: name.clone(),
+                    fixture,
+                    snapshot,
+                    outcome,
+                });
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Return the directories that contain a template, sorted by name.
+    ///
+    /// If the root directory itself contains a template, only that one is
+    /// returned.
+    fn template_dirs(&self) -> Result<Vec<PathBuf>, TestRunnerError> {
+        if self.root.join(TEMPLATE_FILE_NAME).is_file() {
+            return Ok(vec![self.root.clone()]);
+        }
+
+        let mut dirs: Vec<PathBuf> = read_dir(&self.root)?
+            .into_iter()
+            .filter(|path| path.join(TEMPLATE_FILE_NAME).is_file())
+            .collect();
+        dirs.sort();
+        Ok(dirs)
+    }
+
+    fn run_fixture(
+        &self,
+        template: &str,
+        args: Map<String, Value>,
+        snapshot: &Path,
+    ) -> Result<TestOutcome, TestRunnerError> {
+        let notebook = match self.expander.expand_template(template, args) {
+            Ok(notebook) => notebook,
+            Err(err) => return Ok(TestOutcome::ExpansionFailed(err.to_string())),
+        };
+        let actual = serde_json::to_value(notebook).expect("A notebook is always serializable");
+
+        let outcome = if snapshot.is_file() {
+            let expected = read_json(snapshot)?;
+            match first_difference(&expected, &actual, String::new()) {
+                Some((path, expected, actual)) => TestOutcome::Mismatch {
+                    path,
+                    expected,
+                    actual,
+                },
+                None => TestOutcome::Passed,
+            }
+        } else {
+            TestOutcome::MissingSnapshot
+        };
+
+        if self.update_snapshots && !outcome.is_success() {
+            write_snapshot(snapshot, &actual)?;
+            Ok(TestOutcome::SnapshotWritten)
+        } else {
+            Ok(outcome)
+        }
+    }
+}
+
+/// Resolves snippets from the `snippets` directory and templates from their
+/// own directory, using the layout described in [TemplateTestRunner].
+pub struct DirectoryResolver {
+    root: PathBuf,
+}
+
+impl DirectoryResolver {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl TemplateResolver for DirectoryResolver {
+    fn resolve_snippet(&self, name: &str) -> Option<String> {
+        if !is_valid_name(name) {
+            return None;
+        }
+
+        let path = self.root.join(SNIPPETS_DIR).join(format!("{name}.jsonnet"));
+        fs::read_to_string(path).ok()
+    }
+
+    fn resolve_template(&self, name: &str) -> Option<String> {
+        if !is_valid_name(name) {
+            return None;
+        }
+
+        let path = self.root.join(name).join(TEMPLATE_FILE_NAME);
+        fs::read_to_string(path).ok()
+    }
+}
+
+/// Only allow names that refer to a file inside the expected directory.
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Name of a fixture and the template arguments it contains
+type Fixture = (String, Map<String, Value>);
+
+/// Return the fixtures of the template in the given directory, sorted by name.
+fn fixtures(dir: &Path) -> Result<Vec<Fixture>, TestRunnerError> {
+    let fixtures_dir = dir.join(FIXTURES_DIR);
+    if !fixtures_dir.is_dir() {
+        return Ok(vec![(DEFAULT_FIXTURE.to_owned(), Map::new())]);
+    }
+
+    let mut paths: Vec<PathBuf> = read_dir(&fixtures_dir)?
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let name = path
+                .file_stem()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            match read_json(&path)? {
+                Value::Object(args) => Ok((name, args)),
+                _ => Err(TestRunnerError::InvalidFixture { path }),
+            }
+        })
+        .collect()
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>, TestRunnerError> {
+    let io_error = |source| TestRunnerError::Io {
+        path: dir.to_owned(),
+        source,
+    };
+    fs::read_dir(dir)
+        .map_err(io_error)?
+        .map(|entry| entry.map(|entry| entry.path()).map_err(io_error))
+        .collect()
+}
+
+fn read_to_string(path: &Path) -> Result<String, TestRunnerError> {
+    fs::read_to_string(path).map_err(|source| TestRunnerError::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+fn read_json(path: &Path) -> Result<Value, TestRunnerError> {
+    serde_json::from_str(&read_to_string(path)?).map_err(|source| TestRunnerError::InvalidJson {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+fn write_snapshot(path: &Path, notebook: &Value) -> Result<(), TestRunnerError> {
+    let io_error = |source| TestRunnerError::Io {
+        path: path.to_owned(),
+        source,
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io_error)?;
+    }
+
+    let mut json = serde_json::to_string_pretty(notebook).expect("JSON values are serializable");
+    json.push('\n');
+    fs::write(path, json).map_err(io_error)
+}
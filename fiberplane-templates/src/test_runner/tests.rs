@@ -0,0 +1,150 @@
+use super::*;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tempfile::TempDir;
+
+static TEMPLATE: &str = "local fp = import 'fiberplane.libsonnet';
+    function(service='api')
+      fp.notebook.new('Incident: ' + service)
+      .addCells(fp.useSnippet('checklist', { service: service }))";
+
+static CHECKLIST_SNIPPET: &str = "local fp = import 'fiberplane.libsonnet';
+    function(service) fp.snippet([fp.cell.checkbox('Page the ' + service + ' on-call')])";
+
+fn write(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+/// Create a directory with one template that has two fixtures, and a snippet.
+fn templates_dir() -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let template_dir = dir.path().join("incident");
+    write(&template_dir.join(TEMPLATE_FILE_NAME), TEMPLATE);
+    write(
+        &template_dir.join("fixtures/checkout.json"),
+        r#"{"service": "checkout"}"#,
+    );
+    write(&template_dir.join("fixtures/empty.json"), "{}");
+    write(
+        &dir.path().join("snippets/checklist.jsonnet"),
+        CHECKLIST_SNIPPET,
+    );
+    dir
+}
+
+fn outcomes(results: &[TestResult]) -> Vec<(&str, &str, &TestOutcome)> {
+    results
+        .iter()
+        .map(|result| {
+            (
+                result.template.as_str(),
+                result.fixture.as_str(),
+                &result.outcome,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn writes_and_compares_snapshots() {
+    let dir = templates_dir();
+
+    let results = TemplateTestRunner::new(dir.path()).run().unwrap();
+    assert_eq!(
+        outcomes(&results),
+        [
+            ("incident", "checkout", &TestOutcome::MissingSnapshot),
+            ("incident", "empty", &TestOutcome::MissingSnapshot),
+        ]
+    );
+
+    let results = TemplateTestRunner::new(dir.path())
+        .update_snapshots(true)
+        .run()
+        .unwrap();
+    assert_eq!(
+        outcomes(&results),
+        [
+            ("incident", "checkout", &TestOutcome::SnapshotWritten),
+            ("incident", "empty", &TestOutcome::SnapshotWritten),
+        ]
+    );
+
+    let snapshot: Value = serde_json::from_str(
+        &fs::read_to_string(dir.path().join("incident/snapshots/checkout.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(snapshot["title"], json!("Incident: checkout"));
+    assert_eq!(
+        snapshot["cells"][0]["content"],
+        json!("Page the checkout on-call")
+    );
+
+    let results = TemplateTestRunner::new(dir.path()).run().unwrap();
+    assert!(results
+        .iter()
+        .all(|result| result.outcome == TestOutcome::Passed));
+}
+
+#[test]
+fn reports_mismatches() {
+    let dir = templates_dir();
+    TemplateTestRunner::new(dir.path())
+        .update_snapshots(true)
+        .run()
+        .unwrap();
+
+    write(
+        &dir.path().join("snippets/checklist.jsonnet"),
+        &CHECKLIST_SNIPPET.replace("Page", "Call"),
+    );
+    let results = TemplateTestRunner::new(dir.path()).run().unwrap();
+    assert_eq!(
+        results[0].outcome,
+        TestOutcome::Mismatch {
+            path: "cells[0].content".to_owned(),
+            expected: json!("Page the checkout on-call"),
+            actual: json!("Call the checkout on-call"),
+        }
+    );
+}
+
+#[test]
+fn reports_expansion_failures() {
+    let dir = tempfile::tempdir().unwrap();
+    write(
+        &dir.path().join(TEMPLATE_FILE_NAME),
+        "local fp = import 'fiberplane.libsonnet'; function(service) fp.notebook.new(service)",
+    );
+
+    // The root directory is a template itself, without any fixtures
+    let results = TemplateTestRunner::new(dir.path()).run().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].fixture, "default");
+    assert_eq!(
+        results[0].outcome,
+        TestOutcome::ExpansionFailed("missing required argument: service".to_owned())
+    );
+}
+
+#[test]
+fn rejects_invalid_fixtures() {
+    let dir = templates_dir();
+    write(&dir.path().join("incident/fixtures/list.json"), "[]");
+
+    let result = TemplateTestRunner::new(dir.path()).run();
+    assert!(matches!(
+        result,
+        Err(TestRunnerError::InvalidFixture { path }) if path.ends_with("fixtures/list.json")
+    ));
+}
+
+#[test]
+fn resolver_stays_inside_root() {
+    let dir = templates_dir();
+    let resolver = DirectoryResolver::new(dir.path().join("incident"));
+
+    assert_eq!(resolver.resolve_snippet("../snippets/checklist"), None);
+    assert_eq!(resolver.resolve_template(".."), None);
+}
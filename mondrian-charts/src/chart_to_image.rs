@@ -34,6 +34,9 @@ pub struct ImageOptions {
 
     /// Background color to render the chart on.
     ///
+    /// This would usually be the `background_color` of the
+    /// [ChartTheme](crate::ChartTheme) that is used for rendering the chart.
+    ///
     /// Must be a valid CSS color string.
     pub background_color: String,
 }
@@ -0,0 +1,168 @@
+use crate::{chart_to_svg, ChartOptions, MondrianChart};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref};
+use thiserror::Error;
+use usvg::{fontdb, TreeParsing, TreeTextToPath};
+
+/// Spacing between charts that are rendered in a grid, in points.
+const GRID_SPACING: f32 = 16.;
+
+#[derive(Debug, Error)]
+pub enum PdfRenderingError {
+    #[error("Invalid color string")]
+    InvalidColor { color: String },
+    #[error("Invalid number of columns for the given charts")]
+    InvalidColumns,
+    #[error("SVG representation was invalid")]
+    InvalidSvg(String),
+}
+
+/// Options for generating a PDF document from Mondrian charts.
+pub struct PdfOptions {
+    /// The font database to be used for rendering ticks and labels.
+    ///
+    /// Text is embedded in the document as vector outlines of the glyphs from
+    /// these fonts, so the document renders the same everywhere, regardless of
+    /// the fonts that are installed on the viewer's system.
+    ///
+    /// Currently, only the font family of the [ChartTheme](crate::ChartTheme)
+    /// that is used for rendering the charts needs to be initialized.
+    pub fonts: fontdb::Database,
+
+    /// Optional background color to render the page on.
+    ///
+    /// If `None`, the page is left blank, which is usually what you want when
+    /// the charts are rendered using a light theme and the document gets
+    /// printed.
+    ///
+    /// Must be a valid CSS color string. Transparency is ignored.
+    pub background_color: Option<String>,
+}
+
+/// Renders a chart to a single-page PDF document.
+///
+/// The page has the same size as the chart, where every pixel of the chart
+/// corresponds to one point on the page.
+pub fn chart_to_pdf<'a, S, P>(
+    chart: &'a MondrianChart<S, P>,
+    chart_options: &ChartOptions<'a, S>,
+    pdf_options: &PdfOptions,
+) -> Result<Vec<u8>, PdfRenderingError> {
+    charts_to_pdf(&[(chart, chart_options)], 1, pdf_options)
+}
+
+/// Renders multiple charts in a grid on a single-page PDF document.
+///
+/// Charts are placed from left to right and top to bottom, in the given number
+/// of columns. Every cell in the grid is as large as the largest chart, and
+/// the page is sized to fit the entire grid.
+pub fn charts_to_pdf<'a, S, P>(
+    charts: &[(&'a MondrianChart<S, P>, &ChartOptions<'a, S>)],
+    columns: usize,
+    pdf_options: &PdfOptions,
+) -> Result<Vec<u8>, PdfRenderingError> {
+    if charts.is_empty() || columns == 0 {
+        return Err(PdfRenderingError::InvalidColumns);
+    }
+
+    let columns = columns.min(charts.len());
+    let rows = (charts.len() + columns - 1) / columns;
+
+    let cell_width = max_dimension(charts, |options| options.width);
+    let cell_height = max_dimension(charts, |options| options.height);
+    let page_width = columns as f32 * (cell_width + GRID_SPACING) - GRID_SPACING;
+    let page_height = rows as f32 * (cell_height + GRID_SPACING) - GRID_SPACING;
+
+    let catalog_ref = Ref::new(1);
+    let page_tree_ref = Ref::new(2);
+    let page_ref = Ref::new(3);
+    let content_ref = Ref::new(4);
+    let mut next_ref = Ref::new(5);
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_ref).pages(page_tree_ref);
+    pdf.pages(page_tree_ref).kids([page_ref]).count(1);
+
+    let mut content = Content::new();
+    if let Some(background_color) = &pdf_options.background_color {
+        let [r, g, b] = parse_color(background_color)?;
+        content
+            .set_fill_rgb(r, g, b)
+            .rect(0., 0., page_width, page_height)
+            .fill_nonzero();
+    }
+
+    let mut x_objects = Vec::with_capacity(charts.len());
+    for (index, (chart, chart_options)) in charts.iter().enumerate() {
+        let tree = render_chart_to_tree(chart, chart_options, &pdf_options.fonts)?;
+
+        let x_object_ref = next_ref;
+        next_ref =
+            svg2pdf::convert_tree_into(&tree, svg2pdf::Options::default(), &mut pdf, x_object_ref);
+
+        let name = format!("C{index}");
+        let width = chart_options.width as f32;
+        let height = chart_options.height as f32;
+        let x = (index % columns) as f32 * (cell_width + GRID_SPACING);
+        // PDF coordinates start at the bottom of the page.
+        let y = page_height - (index / columns) as f32 * (cell_height + GRID_SPACING) - height;
+
+        content
+            .save_state()
+            .transform([width, 0., 0., height, x, y])
+            .x_object(Name(name.as_bytes()))
+            .restore_state();
+        x_objects.push((name, x_object_ref));
+    }
+
+    let mut page = pdf.page(page_ref);
+    page.media_box(Rect::new(0., 0., page_width, page_height));
+    page.parent(page_tree_ref);
+    page.contents(content_ref);
+
+    let mut resources = page.resources();
+    let mut resource_x_objects = resources.x_objects();
+    for (name, x_object_ref) in &x_objects {
+        resource_x_objects.pair(Name(name.as_bytes()), *x_object_ref);
+    }
+    resource_x_objects.finish();
+    resources.finish();
+    page.finish();
+
+    pdf.stream(content_ref, &content.finish());
+
+    Ok(pdf.finish())
+}
+
+fn render_chart_to_tree<'a, S, P>(
+    chart: &'a MondrianChart<S, P>,
+    chart_options: &ChartOptions<'a, S>,
+    fonts: &fontdb::Database,
+) -> Result<usvg::Tree, PdfRenderingError> {
+    let svg = chart_to_svg(chart, chart_options);
+
+    let mut tree = usvg::Tree::from_data(svg.as_bytes(), &usvg::Options::default())
+        .map_err(|error| PdfRenderingError::InvalidSvg(error.to_string()))?;
+
+    tree.convert_text(fonts);
+
+    Ok(tree)
+}
+
+fn max_dimension<'a, S, P>(
+    charts: &[(&'a MondrianChart<S, P>, &ChartOptions<'a, S>)],
+    dimension: impl Fn(&ChartOptions<'a, S>) -> u16,
+) -> f32 {
+    charts
+        .iter()
+        .map(|(_, options)| dimension(options))
+        .max()
+        .unwrap_or_default() as f32
+}
+
+fn parse_color(string: &str) -> Result<[f32; 3], PdfRenderingError> {
+    csscolorparser::parse(string)
+        .map(|csscolorparser::Color { r, g, b, .. }| [r as f32, g as f32, b as f32])
+        .map_err(|_| PdfRenderingError::InvalidColor {
+            color: string.to_owned(),
+        })
+}
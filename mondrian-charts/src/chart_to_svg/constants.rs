@@ -12,10 +12,35 @@ pub(super) const POINT_RADIUS_FOCUSED: u16 = 4;
 // This overflow margin ensures that the point is still visible.
 pub(super) const CHART_SHAPE_OVERFLOW_MARGIN: u16 = POINT_RADIUS_FOCUSED;
 
-// Stroke width of chart content lines
-pub(super) const SHAPE_STROKE_WIDTH: f32 = 1.0;
-
-pub(super) const TICK_FONT_FAMILY: &str = "sans-serif";
 pub(super) const TICK_FONT_SIZE: u16 = 20;
 pub(super) const TICK_FONT_WEIGHT: u16 = 400;
 pub(super) const TICK_LABEL_OFFSET: u16 = 16;
+
+// Title rendered above the chart
+pub(super) const TITLE_FONT_SIZE: u16 = 24;
+pub(super) const TITLE_FONT_WEIGHT: u16 = 600;
+pub(super) const TITLE_MARGIN_BOTTOM: u16 = 16;
+
+// Labels rendered along the axes
+pub(super) const AXIS_LABEL_FONT_SIZE: u16 = 20;
+pub(super) const AXIS_LABEL_FONT_WEIGHT: u16 = 600;
+pub(super) const AXIS_LABEL_MARGIN: u16 = 8;
+
+// Legend rendered below the chart
+pub(super) const LEGEND_FONT_SIZE: u16 = 18;
+pub(super) const LEGEND_FONT_WEIGHT: u16 = 400;
+pub(super) const LEGEND_MARGIN_TOP: u16 = 8;
+pub(super) const LEGEND_ROW_HEIGHT: u16 = 24;
+pub(super) const LEGEND_SWATCH_SIZE: u16 = 12;
+pub(super) const LEGEND_SWATCH_SPACING: u16 = 6;
+pub(super) const LEGEND_ITEM_SPACING: u16 = 20;
+
+// We don't have access to font metrics when generating SVG, so text widths
+// are estimated using the average width of a character relative to the font
+// size.
+pub(super) const AVERAGE_CHAR_WIDTH: f64 = 0.6;
+
+// Labels rendered inside the chart, alongside annotations
+pub(super) const SHAPE_LABEL_FONT_SIZE: u16 = 14;
+pub(super) const SHAPE_LABEL_FONT_WEIGHT: u16 = 400;
+pub(super) const SHAPE_LABEL_PADDING: u16 = 4;
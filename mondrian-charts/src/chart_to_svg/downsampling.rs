@@ -0,0 +1,237 @@
+use super::Scales;
+use crate::types::Rectangle;
+use serde::{Deserialize, Serialize};
+
+/// Options for reducing the number of shapes that get rendered, so that large
+/// timeseries don't result in huge SVGs that are slow to render.
+///
+/// The amount of downsampling is driven by the width of the rendered chart:
+///
+/// * Lines and areas are downsampled to roughly one point per pixel, using the
+///   Largest-Triangle-Three-Buckets (LTTB) algorithm.
+/// * Rectangles in the same shape list that fall within the same pixel column,
+///   such as bars, are aggregated into a single rectangle.
+/// * Points in the same shape list that fall on the same pixel are only
+///   rendered once.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Downsampling {
+    /// The aggregation to use for rectangles that fall within the same pixel
+    /// column.
+    pub bar_aggregation: BarAggregation,
+}
+
+/// The aggregation to use for rectangles that are combined while
+/// downsampling.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BarAggregation {
+    /// Renders the tallest of the rectangles.
+    #[default]
+    Max,
+
+    /// Renders the shortest of the rectangles.
+    Min,
+
+    /// Renders a rectangle with the average position, height and intensity of
+    /// the rectangles.
+    Average,
+}
+
+/// Downsamples the given data points to at most one point per pixel between
+/// the first and the last point, using the Largest-Triangle-Three-Buckets
+/// algorithm.
+///
+/// `x`, `y` return the normalized coordinates of a data point.
+pub(super) fn downsample_points<'a, T>(
+    data: &'a [T],
+    scales: &Scales,
+    x: impl Fn(&T) -> f64,
+    y: impl Fn(&T) -> f64,
+) -> Vec<&'a T> {
+    let (Some(first), Some(last)) = (data.first(), data.last()) else {
+        return Vec::new();
+    };
+
+    let threshold = scales.x(x(last) - x(first)).abs().ceil() as usize + 1;
+    largest_triangle_three_buckets(data, threshold, x, y)
+}
+
+/// Selects `threshold` data points that retain the visual shape of the data.
+///
+/// The first and last data points are always selected. The remaining data
+/// points are divided into buckets, from which the point that forms the
+/// largest triangle with the previously selected point and the average of the
+/// next bucket is selected.
+///
+/// See also: https://skemman.is/bitstream/1946/15343/3/SS_MSthesis.pdf
+fn largest_triangle_three_buckets<T>(
+    data: &[T],
+    threshold: usize,
+    x: impl Fn(&T) -> f64,
+    y: impl Fn(&T) -> f64,
+) -> Vec<&T> {
+    let len = data.len();
+    if threshold < 3 || threshold >= len {
+        return data.iter().collect();
+    }
+
+    let bucket_size = (len - 2) as f64 / (threshold - 2) as f64;
+    let bucket_start = |bucket: usize| (bucket as f64 * bucket_size) as usize + 1;
+
+    let mut sampled = Vec::with_capacity(threshold);
+    sampled.push(&data[0]);
+
+    let mut selected_index = 0;
+    for bucket in 0..threshold - 2 {
+        let next_bucket = &data[bucket_start(bucket + 1)..bucket_start(bucket + 2).min(len)];
+        let (average_x, average_y) = if next_bucket.is_empty() {
+            (x(&data[len - 1]), y(&data[len - 1]))
+        } else {
+            let count = next_bucket.len() as f64;
+            let (sum_x, sum_y) = next_bucket.iter().fold((0., 0.), |(sum_x, sum_y), point| {
+                (sum_x + x(point), sum_y + y(point))
+            });
+            (sum_x / count, sum_y / count)
+        };
+
+        let selected_x = x(&data[selected_index]);
+        let selected_y = y(&data[selected_index]);
+
+        let start = bucket_start(bucket);
+        let mut max_area = -1.;
+        for (offset, point) in data[start..bucket_start(bucket + 1)].iter().enumerate() {
+            let area = ((selected_x - average_x) * (y(point) - selected_y)
+                - (selected_x - x(point)) * (average_y - selected_y))
+                .abs();
+            if area > max_area {
+                max_area = area;
+                selected_index = start + offset;
+            }
+        }
+
+        sampled.push(&data[selected_index]);
+    }
+
+    sampled.push(&data[len - 1]);
+    sampled
+}
+
+/// Returns the pixel column in which the center of the rectangle is rendered.
+pub(super) fn get_pixel_column<P>(rectangle: &Rectangle<P>, scales: &Scales) -> i64 {
+    scales.x(rectangle.x + 0.5 * rectangle.width).floor() as i64
+}
+
+/// Aggregates rectangles that fall within the same pixel column into a single
+/// rectangle that spans all of them.
+///
+/// The source of the aggregated rectangle is discarded, since it may represent
+/// multiple data points.
+pub(super) fn aggregate_rectangles<P>(
+    rectangles: &[&Rectangle<P>],
+    aggregation: BarAggregation,
+) -> Option<Rectangle<()>> {
+    let first = rectangles.first()?;
+    let last = rectangles.last()?;
+
+    let x = first.x;
+    let width = last.x + last.width - first.x;
+
+    let representative = |rectangle: &Rectangle<P>| Rectangle {
+        x,
+        y: rectangle.y,
+        width,
+        height: rectangle.height,
+        intensity: rectangle.intensity,
+        source: (),
+    };
+
+    let rectangle = match aggregation {
+        BarAggregation::Max => representative(
+            rectangles
+                .iter()
+                .max_by(|a, b| a.height.total_cmp(&b.height))?,
+        ),
+        BarAggregation::Min => representative(
+            rectangles
+                .iter()
+                .min_by(|a, b| a.height.total_cmp(&b.height))?,
+        ),
+        BarAggregation::Average => {
+            let count = rectangles.len() as f64;
+            let average = |value: fn(&Rectangle<P>) -> f64| {
+                rectangles
+                    .iter()
+                    .map(|rectangle| value(rectangle))
+                    .sum::<f64>()
+                    / count
+            };
+            let intensities: Option<Vec<f64>> = rectangles
+                .iter()
+                .map(|rectangle| rectangle.intensity)
+                .collect();
+
+            Rectangle {
+                x,
+                y: average(|rectangle| rectangle.y),
+                width,
+                height: average(|rectangle| rectangle.height),
+                intensity: intensities.map(|intensities| intensities.iter().sum::<f64>() / count),
+                source: (),
+            }
+        }
+    };
+
+    Some(rectangle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_largest_triangle_three_buckets() {
+        let data: Vec<(f64, f64)> = (0..100)
+            .map(|i| (i as f64, if i == 42 { 100. } else { (i % 2) as f64 }))
+            .collect();
+
+        let sampled = largest_triangle_three_buckets(&data, 10, |p| p.0, |p| p.1);
+        assert_eq!(sampled.len(), 10);
+        assert_eq!(sampled.first(), Some(&&(0., 0.)));
+        assert_eq!(sampled.last(), Some(&&(99., 1.)));
+
+        // The spike must be retained.
+        assert!(sampled.contains(&&(42., 100.)));
+
+        let sampled = largest_triangle_three_buckets(&data, 200, |p| p.0, |p| p.1);
+        assert_eq!(sampled.len(), 100);
+    }
+
+    #[test]
+    fn test_aggregate_rectangles() {
+        let rectangles: Vec<Rectangle<()>> = [0.2, 0.6, 0.4]
+            .iter()
+            .enumerate()
+            .map(|(i, &height)| Rectangle {
+                x: 0.1 * i as f64,
+                y: 0.,
+                width: 0.1,
+                height,
+                intensity: None,
+                source: (),
+            })
+            .collect();
+        let rectangles: Vec<_> = rectangles.iter().collect();
+
+        let max = aggregate_rectangles(&rectangles, BarAggregation::Max).unwrap();
+        assert_eq!(max.x, 0.);
+        assert!((max.width - 0.3).abs() < f64::EPSILON);
+        assert_eq!(max.height, 0.6);
+
+        let min = aggregate_rectangles(&rectangles, BarAggregation::Min).unwrap();
+        assert_eq!(min.height, 0.2);
+
+        let average = aggregate_rectangles(&rectangles, BarAggregation::Average).unwrap();
+        assert!((average.height - 0.4).abs() < f64::EPSILON);
+    }
+}
@@ -1,41 +1,101 @@
 use super::create_area_path_def::create_area_path_def;
 use super::create_line_path_def::create_line_path_def;
-use super::{ChartOptions, Scales, POINT_RADIUS, SHAPE_STROKE_WIDTH};
+use super::downsampling::{aggregate_rectangles, downsample_points, get_pixel_column};
+use super::{ChartOptions, Downsampling, Scales, POINT_RADIUS};
 use crate::types::{Area, Line, MondrianChart, Point, Rectangle, Shape};
-use itertools::join;
 use std::borrow::Cow;
+use std::collections::HashSet;
 
 pub(super) fn generate_chart_content_svg<'a, S, P>(
     chart: &'a MondrianChart<S, P>,
     scales: &Scales,
     options: &ChartOptions<'a, S>,
 ) -> String {
-    let shapes = chart
-        .shape_lists
-        .iter()
-        .enumerate()
-        .flat_map(|(index, shape_list)| {
-            shape_list.shapes.iter().map(move |shape| {
-                generate_shape_svg(
-                    shape,
-                    scales,
-                    ShapeOptions::from_chart_options_with_shape_list_index(
-                        &shape_list.source,
-                        options,
-                        index,
-                    ),
-                )
-            })
-        });
-
-    join(shapes, "")
+    let mut svg = String::new();
+
+    for (index, shape_list) in chart.shape_lists.iter().enumerate() {
+        let shape_options = ShapeOptions::from_chart_options_with_shape_list_index(
+            &shape_list.source,
+            options,
+            index,
+        );
+
+        match options.downsampling {
+            Some(downsampling) => write_downsampled_shapes_svg(
+                &mut svg,
+                &shape_list.shapes,
+                scales,
+                shape_options,
+                downsampling,
+            ),
+            None => {
+                for shape in &shape_list.shapes {
+                    svg.push_str(&generate_shape_svg(shape, scales, shape_options));
+                }
+            }
+        }
+    }
+
+    svg
+}
+
+/// Writes the shapes of a single shape list, while aggregating rectangles that
+/// fall within the same pixel column and skipping points that fall on a pixel
+/// that was already drawn.
+fn write_downsampled_shapes_svg<P>(
+    svg: &mut String,
+    shapes: &[Shape<P>],
+    scales: &Scales,
+    options: ShapeOptions,
+    downsampling: Downsampling,
+) {
+    let mut drawn_points = HashSet::new();
+    let mut rectangles: Vec<&Rectangle<P>> = Vec::new();
+
+    let flush_rectangles = |svg: &mut String, rectangles: &mut Vec<&Rectangle<P>>| {
+        if let [rectangle] = rectangles.as_slice() {
+            svg.push_str(&generate_rectangle_svg(rectangle, scales, options));
+        } else if let Some(rectangle) =
+            aggregate_rectangles(rectangles, downsampling.bar_aggregation)
+        {
+            svg.push_str(&generate_rectangle_svg(&rectangle, scales, options));
+        }
+        rectangles.clear();
+    };
+
+    for shape in shapes {
+        match shape {
+            Shape::Rectangle(rectangle) => {
+                if rectangles.last().is_some_and(|last| {
+                    get_pixel_column(last, scales) != get_pixel_column(rectangle, scales)
+                }) {
+                    flush_rectangles(svg, &mut rectangles);
+                }
+                rectangles.push(rectangle);
+            }
+            Shape::Point(point) => {
+                let pixel = (
+                    scales.x(point.x).round() as i64,
+                    scales.y(point.y).round() as i64,
+                );
+                if drawn_points.insert(pixel) {
+                    svg.push_str(&generate_point_svg(point, scales, options));
+                }
+            }
+            shape => svg.push_str(&generate_shape_svg(shape, scales, options)),
+        }
+    }
+
+    flush_rectangles(svg, &mut rectangles);
 }
 
+#[derive(Clone, Copy)]
 struct ShapeOptions<'a> {
     area_gradient_shown: bool,
     color: &'a str,
     stroke_width: f32,
     index: usize,
+    downsampled: bool,
 }
 
 impl<'a> ShapeOptions<'a> {
@@ -46,16 +106,17 @@ impl<'a> ShapeOptions<'a> {
     ) -> Self {
         let ChartOptions {
             area_gradient_shown,
-            shape_stroke_width,
             get_shape_list_color,
+            downsampling,
             ..
         } = options;
 
         Self {
             area_gradient_shown: *area_gradient_shown,
             color: get_shape_list_color(source, index),
-            stroke_width: shape_stroke_width.unwrap_or(SHAPE_STROKE_WIDTH),
+            stroke_width: options.theme.shape_stroke_width,
             index,
+            downsampled: downsampling.is_some(),
         }
     }
 }
@@ -75,8 +136,15 @@ fn generate_area_svg<P>(area: &Area<P>, scales: &Scales, options: ShapeOptions)
         color,
         stroke_width,
         index,
+        downsampled,
     } = options;
 
+    let points = if downsampled {
+        downsample_points(&area.points, scales, |point| point.x, |point| point.y_max)
+    } else {
+        area.points.iter().collect()
+    };
+
     let (defs, fill) = if area.area_gradient_shown.unwrap_or(area_gradient_shown) {
         let defs = format!(
             "<defs>\
@@ -93,7 +161,7 @@ fn generate_area_svg<P>(area: &Area<P>, scales: &Scales, options: ShapeOptions)
     };
 
     let path_def = create_area_path_def(
-        &area.points,
+        &points,
         |point| scales.x(point.x),
         |point| scales.y(point.y_min),
         |point| scales.y(point.y_max),
@@ -110,11 +178,18 @@ fn generate_line_svg<P>(line: &Line<P>, scales: &Scales, options: ShapeOptions)
         color,
         stroke_width,
         index,
+        downsampled,
     } = options;
 
+    let points = if downsampled {
+        downsample_points(&line.points, scales, |point| point.x, |point| point.y)
+    } else {
+        line.points.iter().collect()
+    };
+
     let gradient = if line.area_gradient_shown.unwrap_or(area_gradient_shown) {
         let path_def = create_area_path_def(
-            &line.points,
+            &points,
             |point| scales.x(point.x),
             |point| scales.y(point.y),
             |_| scales.y(0.),
@@ -134,7 +209,7 @@ fn generate_line_svg<P>(line: &Line<P>, scales: &Scales, options: ShapeOptions)
     };
 
     let path_def = create_line_path_def(
-        &line.points,
+        &points,
         |point| scales.x(point.x),
         |point| scales.y(point.y),
     );
@@ -163,15 +238,20 @@ fn generate_rectangle_svg<P>(
     let height = rectangle.height * scales.y_max;
     let width = rectangle.width * scales.x_max;
 
+    let (stroke, fill_opacity) = match rectangle.intensity {
+        Some(intensity) => ("none", intensity),
+        None => (color, 0.1),
+    };
+
     format!(
         "<rect \
             x=\"{x:.1}\" \
             y=\"{y:.1}\" \
             width=\"{width:.1}\" \
             height=\"{height:.1}\" \
-            stroke=\"{color}\" \
+            stroke=\"{stroke}\" \
             fill=\"{color}\" \
-            fill-opacity=\"0.1\" \
+            fill-opacity=\"{fill_opacity}\" \
             opacity=\"1\" \
         />",
         x = scales.x(rectangle.x),
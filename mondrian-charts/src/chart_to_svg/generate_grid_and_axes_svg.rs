@@ -1,12 +1,18 @@
-use super::constants::{TICK_FONT_FAMILY, TICK_FONT_SIZE, TICK_FONT_WEIGHT, TICK_LABEL_OFFSET};
-use super::{ChartOptions, Scales};
+use super::constants::{TICK_FONT_SIZE, TICK_FONT_WEIGHT, TICK_LABEL_OFFSET};
+use super::{ChartOptions, FormatterKind, Scales};
 use crate::chart_to_svg::tick_formatters::get_formatter_for_axis;
-use crate::{Axis, MondrianChart};
+use crate::{Axis, AxisScale, MondrianChart};
 use itertools::join;
 use std::fmt::Write;
+use time::UtcOffset;
 
 pub(super) fn generate_grid_and_axes_svg<S, P>(
-    MondrianChart { x_axis, y_axis, .. }: &MondrianChart<S, P>,
+    MondrianChart {
+        x_axis,
+        y_axis,
+        secondary_y_axis,
+        ..
+    }: &MondrianChart<S, P>,
     scales: &Scales,
     options: &ChartOptions<S>,
 ) -> String {
@@ -17,8 +23,12 @@ pub(super) fn generate_grid_and_axes_svg<S, P>(
     let x_scale = |value| scales.x(value);
     let y_scale = |value| scales.y(value);
 
-    let x_ticks = get_ticks(x_axis, x_max, x_scale, 12, get_max_x_tick_value);
-    let y_ticks = get_ticks(y_axis, y_max, y_scale, 8, get_max_y_tick_value);
+    let x_ticks = if options.x_formatter == Some(FormatterKind::Time) {
+        get_time_ticks(x_axis, 12, options.time_format.utc_offset())
+    } else {
+        get_ticks(x_axis, x_max, x_scale, 12, get_max_x_tick_value)
+    };
+    let y_ticks = get_y_ticks(y_axis, y_max, y_scale);
 
     let mut svg = String::new();
 
@@ -38,6 +48,20 @@ pub(super) fn generate_grid_and_axes_svg<S, P>(
         write_y_axis_svg(&mut svg, scales, y_axis, &y_ticks, &stroke_attrs, options);
     }
 
+    if let Some((secondary_y_axis, formatter)) =
+        secondary_y_axis.as_ref().zip(options.secondary_y_formatter)
+    {
+        let secondary_y_ticks = get_y_ticks(secondary_y_axis, y_max, y_scale);
+        write_secondary_y_axis_svg(
+            &mut svg,
+            scales,
+            secondary_y_axis,
+            &secondary_y_ticks,
+            formatter,
+            options,
+        );
+    }
+
     svg
 }
 
@@ -52,7 +76,7 @@ fn write_grid_rows_svg(
     svg.push_str("<g>");
 
     for &value in y_ticks {
-        let y = y_scale((value - y_axis.min_value) / (y_axis.max_value - y_axis.min_value));
+        let y = y_scale(y_axis.normalize(value));
         write!(
             svg,
             "<line x1=\"0\" y1=\"{y:.1}\" x2=\"{x_max}\" y2=\"{y}\" {stroke_attrs} />"
@@ -74,7 +98,7 @@ fn write_grid_columns_svg(
     svg.push_str("<g>");
 
     for &value in x_ticks {
-        let x = x_scale((value - x_axis.min_value) / (x_axis.max_value - x_axis.min_value));
+        let x = x_scale(x_axis.normalize(value));
         write!(
             svg,
             "<line x1=\"{x:.1}\" y1=\"0\" x2=\"{x:.1}\" y2=\"{y_max}\" {stroke_attrs} />"
@@ -107,14 +131,15 @@ fn write_x_axis_svg<S>(
 
     if let Some(formatter) = options
         .x_formatter
-        .map(|formatter| get_formatter_for_axis(x_axis, formatter))
+        .map(|formatter| get_formatter_for_axis(x_axis, formatter, options.time_format))
     {
-        let tick_color = &options.tick_color;
+        let tick_color = &options.theme.tick_color;
+        let font_family = &options.theme.font_family;
         let tick_label_attrs = format!(
             "y=\"{TICK_FONT_SIZE}\" \
             dy=\"{TICK_LABEL_OFFSET}\" \
             fill=\"{tick_color}\" \
-            font-family=\"{TICK_FONT_FAMILY}\" \
+            font-family=\"{font_family}\" \
             font-size=\"{TICK_FONT_SIZE}\" \
             font-weight=\"{TICK_FONT_WEIGHT}\" \
             letter-spacing=\"0\" \
@@ -123,7 +148,7 @@ fn write_x_axis_svg<S>(
 
         for &value in x_ticks {
             let label = formatter.format(value);
-            let x = scales.x((value - x_axis.min_value) / (x_axis.max_value - x_axis.min_value));
+            let x = scales.x(x_axis.normalize(value));
 
             write!(svg, "<text x=\"{x:.1}\" {tick_label_attrs}>{label}</text>")
                 .expect("Could not write tick");
@@ -154,40 +179,96 @@ fn write_y_axis_svg<S>(
         .expect("Could not write axis line");
     }
 
-    if let Some(formatter) = options
-        .y_formatter
-        .map(|formatter| get_formatter_for_axis(y_axis, formatter))
-    {
-        let tick_color = &options.tick_color;
-        let tick_label_attrs = format!(
-            "dx=\"-0.45em\" \
-            dy=\"0.25em\" \
-            text-anchor=\"end\" \
-            font-family=\"{TICK_FONT_FAMILY}\" \
-            font-size=\"{TICK_FONT_SIZE}\" \
-            font-weight=\"{TICK_FONT_WEIGHT}\" \
-            letter-spacing=\"0\" \
-            fill=\"{tick_color}\""
-        );
+    if let Some(formatter) = options.y_formatter {
+        write_y_tick_labels_svg(svg, scales, y_axis, y_ticks, formatter, Side::Left, options);
+    }
 
-        let num_ticks = y_ticks.len();
-        for (index, &value) in y_ticks.iter().enumerate() {
-            if value == 0. || index == 0 || index >= num_ticks - 1 {
-                continue;
-            }
+    svg.push_str("</g>");
+}
 
-            let label = formatter.format(value);
-            let y = scales.y((value - y_axis.min_value) / (y_axis.max_value - y_axis.min_value));
+/// Writes the ticks for the secondary Y axis, along the right-hand side of the
+/// chart.
+fn write_secondary_y_axis_svg<S>(
+    svg: &mut String,
+    scales: &Scales,
+    y_axis: &Axis,
+    y_ticks: &[f64],
+    formatter: FormatterKind,
+    options: &ChartOptions<S>,
+) {
+    svg.push_str("<g>");
+    write_y_tick_labels_svg(
+        svg,
+        scales,
+        y_axis,
+        y_ticks,
+        formatter,
+        Side::Right,
+        options,
+    );
+    svg.push_str("</g>");
+}
+
+/// Side of the chart along which a Y axis is rendered.
+#[derive(Clone, Copy)]
+enum Side {
+    Left,
+    Right,
+}
 
-            write!(
-                svg,
-                "<text x=\"0\" y=\"{y:.1}\" {tick_label_attrs}>{label}</text>"
-            )
-            .expect("Could not write tick");
+fn write_y_tick_labels_svg<S>(
+    svg: &mut String,
+    scales: &Scales,
+    y_axis: &Axis,
+    y_ticks: &[f64],
+    formatter: FormatterKind,
+    side: Side,
+    options: &ChartOptions<S>,
+) {
+    let formatter = get_formatter_for_axis(y_axis, formatter, options.time_format);
+    let (x, dx, text_anchor) = match side {
+        Side::Left => (0., "-0.45em", "end"),
+        Side::Right => (scales.x_max, "0.45em", "start"),
+    };
+
+    let tick_color = &options.theme.tick_color;
+    let font_family = &options.theme.font_family;
+    let tick_label_attrs = format!(
+        "dx=\"{dx}\" \
+        dy=\"0.25em\" \
+        text-anchor=\"{text_anchor}\" \
+        font-family=\"{font_family}\" \
+        font-size=\"{TICK_FONT_SIZE}\" \
+        font-weight=\"{TICK_FONT_WEIGHT}\" \
+        letter-spacing=\"0\" \
+        fill=\"{tick_color}\""
+    );
+
+    let num_ticks = y_ticks.len();
+    for (index, &value) in y_ticks.iter().enumerate() {
+        if value == 0. || index == 0 || index >= num_ticks - 1 {
+            continue;
         }
+
+        let label = formatter.format(value);
+        let y = scales.y(y_axis.normalize(value));
+
+        write!(
+            svg,
+            "<text x=\"{x}\" y=\"{y:.1}\" {tick_label_attrs}>{label}</text>"
+        )
+        .expect("Could not write tick");
     }
+}
 
-    svg.push_str("</g>");
+fn get_y_ticks(y_axis: &Axis, y_max: f64, y_scale: impl Fn(f64) -> f64) -> Vec<f64> {
+    let mut y_ticks = get_ticks(y_axis, y_max, y_scale, 8, get_max_y_tick_value);
+
+    // Ticks outside the range of the Y axis would end up below the plot area,
+    // in the space that is used for the X axis ticks, labels and legend.
+    y_ticks.retain(|&value| value >= y_axis.min_value && value <= y_axis.max_value);
+
+    y_ticks
 }
 
 fn get_ticks(
@@ -197,6 +278,10 @@ fn get_ticks(
     num_ticks: usize,
     get_max_allowed_tick: impl Fn(&[f64], f64) -> f64,
 ) -> Vec<f64> {
+    if axis.scale == AxisScale::Log {
+        return get_log_ticks(axis, num_ticks);
+    }
+
     let (mut ticks, interval) = if let Some(suggestions) = &axis.tick_suggestions {
         get_ticks_and_interval_from_suggestions(axis, suggestions, num_ticks)
     } else {
@@ -212,6 +297,84 @@ fn get_ticks(
     ticks
 }
 
+/// Intervals at which ticks can be placed along a time axis, in seconds.
+const TIME_TICK_INTERVALS: &[f64] = &[
+    0.001, 0.002, 0.005, 0.01, 0.02, 0.05, 0.1, 0.2, 0.5, // Milliseconds
+    1., 2., 5., 10., 15., 30., // Seconds
+    60., 120., 300., 600., 900., 1800., // Minutes
+    3600., 7200., 10800., 21600., 43200., // Hours
+    86400., 172800., 604800., // Days
+];
+
+/// Returns the ticks for a time axis, of which the values are expressed as
+/// seconds since the UNIX epoch.
+///
+/// Ticks are placed at round times in the time zone with the given offset,
+/// such as whole hours or midnight. If the axis has tick suggestions, ticks are
+/// never placed closer together than the suggestions are, so that bucketed
+/// data doesn't get ticks in between buckets. Axes that span more than a week
+/// fall back to multiples of a week.
+fn get_time_ticks(axis: &Axis, num_ticks: usize, utc_offset: UtcOffset) -> Vec<f64> {
+    let range = axis.max_value - axis.min_value;
+    if !range.is_finite() || range <= 0. {
+        return vec![axis.min_value];
+    }
+
+    let suggestion_interval = match axis.tick_suggestions.as_deref() {
+        Some([first, second, ..]) => second - first,
+        _ => 0.,
+    };
+    let min_interval = (range / num_ticks as f64).max(suggestion_interval);
+    let interval = TIME_TICK_INTERVALS
+        .iter()
+        .copied()
+        .find(|&interval| interval >= min_interval)
+        .unwrap_or_else(|| (min_interval / 604800.).ceil() * 604800.);
+
+    let offset = utc_offset.whole_seconds() as f64;
+    let first_tick = ((axis.min_value + offset) / interval).ceil() * interval - offset;
+
+    let mut ticks: Vec<f64> = (0..)
+        .map(|index| first_tick + index as f64 * interval)
+        .take_while(|&tick| tick <= axis.max_value)
+        .collect();
+
+    remove_last_tick_if_too_close_to_max(&mut ticks, axis.max_value, get_max_x_tick_value);
+
+    ticks
+}
+
+/// Returns the ticks for a logarithmic axis.
+///
+/// Ticks are placed at powers of ten. If the axis spans only a few orders of
+/// magnitude, ticks at two and five times those powers are added, while for
+/// axes that span many orders of magnitude, some of the powers are skipped.
+fn get_log_ticks(axis: &Axis, num_ticks: usize) -> Vec<f64> {
+    if axis.min_value <= 0. || axis.max_value <= axis.min_value {
+        return Vec::new();
+    }
+
+    let min_exponent = axis.min_value.log10().floor() as i32;
+    let max_exponent = axis.max_value.log10().ceil() as i32;
+    let num_decades = (max_exponent - min_exponent) as usize;
+
+    let multipliers: &[f64] = if 3 * num_decades <= num_ticks {
+        &[1., 2., 5.]
+    } else {
+        &[1.]
+    };
+    let step = ((num_decades + num_ticks - 1) / num_ticks).max(1);
+
+    (min_exponent..=max_exponent)
+        .step_by(step)
+        .flat_map(|exponent| {
+            let power = 10_f64.powf(exponent as f64);
+            multipliers.iter().map(move |multiplier| multiplier * power)
+        })
+        .filter(|&value| value >= axis.min_value && value <= axis.max_value)
+        .collect()
+}
+
 fn get_ticks_and_interval_from_range(
     min_value: f64,
     max_value: f64,
@@ -278,7 +441,7 @@ fn extend_ticks_to_fit_axis(
     max_ticks: usize,
     interval: f64,
 ) {
-    let scale_to_axis = |value| scale((value - axis.min_value) / (axis.max_value - axis.min_value));
+    let scale_to_axis = |value| scale(axis.normalize(value));
 
     // Trim ticks from the start if the user has dragged them beyond the Y axis.
     while !ticks.is_empty() && scale_to_axis(ticks[0]) < 0. {
@@ -304,13 +467,10 @@ fn extend_ticks_to_fit_axis(
     }
 }
 
-fn format_stroke_attrs<S>(
-    ChartOptions {
-        grid_stroke_color,
-        grid_stroke_dasharray,
-        ..
-    }: &ChartOptions<S>,
-) -> String {
+fn format_stroke_attrs<S>(options: &ChartOptions<S>) -> String {
+    let grid_stroke_color = &options.theme.grid_stroke_color;
+    let grid_stroke_dasharray = &options.theme.grid_stroke_dasharray;
+
     if grid_stroke_dasharray.is_empty() {
         format!("stroke=\"{grid_stroke_color}\"")
     } else {
@@ -374,3 +534,31 @@ fn get_max_y_tick_value(ticks: &[f64], max_value: f64) -> f64 {
     let interval = ticks[1] - ticks[0];
     max_value - interval / 3.
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart_to_svg::TimeFormatOptions;
+
+    #[test]
+    fn test_time_ticks_ignore_invalid_utc_offset() {
+        let axis = Axis {
+            min_value: 1_700_000_000.,
+            max_value: 1_700_000_000. + 6. * 86400.,
+            ..Default::default()
+        };
+
+        let ticks = |utc_offset_seconds| {
+            let options = TimeFormatOptions {
+                utc_offset_seconds,
+                ..Default::default()
+            };
+            get_time_ticks(&axis, 12, options.utc_offset())
+        };
+
+        let utc_ticks = ticks(0);
+        assert!(utc_ticks.iter().all(|tick| tick % 43200. == 0.));
+        assert_eq!(ticks(30 * 3600), utc_ticks);
+        assert_ne!(ticks(3600), utc_ticks);
+    }
+}
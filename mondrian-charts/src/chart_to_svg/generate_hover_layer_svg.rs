@@ -0,0 +1,221 @@
+use super::constants::POINT_RADIUS_FOCUSED;
+use super::{escape_text, ChartOptions, Scales};
+use crate::types::{MondrianChart, Shape};
+use std::fmt::Write;
+
+/// Callbacks for rendering an interactive chart.
+///
+/// Interactive charts contain an additional layer with a hover target for
+/// every data point. Every hover target carries data attributes describing the
+/// data point and a `<title>` element that browsers display as a tooltip.
+/// Hovering a target highlights it and shows a crosshair, using only inline
+/// CSS.
+pub struct Interactivity<'a, S, P> {
+    /// Callback to determine the data attributes for a given shape list.
+    ///
+    /// Shape lists are identified the same way as for
+    /// [`ChartOptions::get_shape_list_color`]. The attributes are rendered on
+    /// the group that contains the hover targets of the shape list, with their
+    /// names prefixed by `data-`.
+    pub get_shape_list_attributes: &'a dyn Fn(&'a S, usize) -> Vec<(String, String)>,
+
+    /// Callback to determine the data attributes for the source of a given
+    /// data point.
+    ///
+    /// The attributes are rendered on the hover target of the data point,
+    /// with their names prefixed by `data-`.
+    pub get_point_attributes: &'a dyn Fn(&'a P) -> Vec<(String, String)>,
+
+    /// Callback to determine the tooltip for a given data point, based on the
+    /// source of its shape list and its own source.
+    ///
+    /// If `None` is returned, the data point has no tooltip.
+    pub get_point_title: &'a dyn Fn(&'a S, &'a P) -> Option<String>,
+
+    /// Whether a crosshair should be shown when hovering a data point.
+    pub crosshair_shown: bool,
+}
+
+/// Inline styles for the hover layer.
+const HOVER_LAYER_STYLE: &str = ".mondrian-hover-target { fill: currentColor; fill-opacity: 0; } \
+    .mondrian-hover:hover .mondrian-hover-target { fill-opacity: 0.3; } \
+    .mondrian-crosshair { visibility: hidden; pointer-events: none; } \
+    .mondrian-hover:hover .mondrian-crosshair { visibility: visible; }";
+
+/// Generates the `<style>` element for the hover layer.
+pub(super) fn generate_hover_layer_style() -> String {
+    format!("<style>{HOVER_LAYER_STYLE}</style>")
+}
+
+/// Generates the hover layer, with a hover target for every data point in the
+/// chart.
+pub(super) fn generate_hover_layer_svg<'a, S, P>(
+    chart: &'a MondrianChart<S, P>,
+    scales: &Scales,
+    options: &ChartOptions<'a, S>,
+    interactivity: &Interactivity<'a, S, P>,
+) -> String {
+    let mut svg = String::new();
+
+    for (index, shape_list) in chart.shape_lists.iter().enumerate() {
+        let source = &shape_list.source;
+        let color = (options.get_shape_list_color)(source, index);
+        let attributes =
+            format_data_attributes((interactivity.get_shape_list_attributes)(source, index));
+        write!(
+            svg,
+            "<g class=\"mondrian-series\" color=\"{color}\"{attributes}>"
+        )
+        .expect("Could not write series group");
+
+        for shape in &shape_list.shapes {
+            for target in get_hover_targets(shape, scales) {
+                write_hover_target_svg(&mut svg, source, target, scales, options, interactivity);
+            }
+        }
+
+        svg.push_str("</g>");
+    }
+
+    svg
+}
+
+/// Area on the chart that can be hovered to reveal a data point.
+enum HoverTarget<'a, P> {
+    Circle {
+        x: f64,
+        y: f64,
+        source: &'a P,
+    },
+    Rectangle {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        source: &'a P,
+    },
+}
+
+impl<'a, P> HoverTarget<'a, P> {
+    fn source(&self) -> &'a P {
+        match self {
+            Self::Circle { source, .. } | Self::Rectangle { source, .. } => source,
+        }
+    }
+
+    /// Returns the X coordinate at which the crosshair is drawn.
+    fn crosshair_x(&self) -> f64 {
+        match self {
+            Self::Circle { x, .. } => *x,
+            Self::Rectangle { x, width, .. } => x + 0.5 * width,
+        }
+    }
+}
+
+fn get_hover_targets<'a, P>(shape: &'a Shape<P>, scales: &Scales) -> Vec<HoverTarget<'a, P>> {
+    match shape {
+        Shape::Area(area) => area
+            .points
+            .iter()
+            .map(|point| HoverTarget::Circle {
+                x: scales.x(point.x),
+                y: scales.y(point.y_max),
+                source: &point.source,
+            })
+            .collect(),
+        Shape::Line(line) => line
+            .points
+            .iter()
+            .map(|point| HoverTarget::Circle {
+                x: scales.x(point.x),
+                y: scales.y(point.y),
+                source: &point.source,
+            })
+            .collect(),
+        Shape::Point(point) => vec![HoverTarget::Circle {
+            x: scales.x(point.x),
+            y: scales.y(point.y),
+            source: &point.source,
+        }],
+        Shape::Rectangle(rectangle) => {
+            let height = rectangle.height * scales.y_max;
+            vec![HoverTarget::Rectangle {
+                x: scales.x(rectangle.x),
+                y: scales.y(rectangle.y) - height,
+                width: rectangle.width * scales.x_max,
+                height,
+                source: &rectangle.source,
+            }]
+        }
+    }
+}
+
+fn write_hover_target_svg<'a, S, P>(
+    svg: &mut String,
+    series_source: &'a S,
+    target: HoverTarget<'a, P>,
+    scales: &Scales,
+    options: &ChartOptions<'a, S>,
+    interactivity: &Interactivity<'a, S, P>,
+) {
+    let source = target.source();
+    let attributes = format_data_attributes((interactivity.get_point_attributes)(source));
+    write!(svg, "<g class=\"mondrian-hover\"{attributes}>").expect("Could not write hover group");
+
+    if interactivity.crosshair_shown {
+        let x = target.crosshair_x();
+        let y_max = scales.y_max;
+        let stroke_color = &options.theme.grid_stroke_color;
+        write!(
+            svg,
+            "<line class=\"mondrian-crosshair\" x1=\"{x:.1}\" y1=\"0\" x2=\"{x:.1}\" y2=\"{y_max}\" stroke=\"{stroke_color}\" />"
+        )
+        .expect("Could not write crosshair");
+    }
+
+    let title = (interactivity.get_point_title)(series_source, source)
+        .map(|title| format!("<title>{}</title>", escape_text(&title)))
+        .unwrap_or_default();
+
+    match target {
+        HoverTarget::Circle { x, y, .. } => write!(
+            svg,
+            "<circle class=\"mondrian-hover-target\" cx=\"{x:.1}\" cy=\"{y:.1}\" r=\"{POINT_RADIUS_FOCUSED}\">{title}</circle>"
+        ),
+        HoverTarget::Rectangle {
+            x,
+            y,
+            width,
+            height,
+            ..
+        } => write!(
+            svg,
+            "<rect class=\"mondrian-hover-target\" x=\"{x:.1}\" y=\"{y:.1}\" width=\"{width:.1}\" height=\"{height:.1}\">{title}</rect>"
+        ),
+    }
+    .expect("Could not write hover target");
+
+    svg.push_str("</g>");
+}
+
+/// Formats the given attributes as `data-` attributes, including a leading
+/// space.
+///
+/// Attribute names are lowercased, and any characters that are not allowed in
+/// attribute names are replaced with dashes.
+fn format_data_attributes(attributes: Vec<(String, String)>) -> String {
+    let mut formatted = String::new();
+    for (name, value) in attributes {
+        let name: String = name
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | '0'..='9' | '-' | '_' => c,
+                'A'..='Z' => c.to_ascii_lowercase(),
+                _ => '-',
+            })
+            .collect();
+        write!(formatted, " data-{name}=\"{}\"", escape_text(&value))
+            .expect("Could not write data attribute");
+    }
+    formatted
+}
@@ -0,0 +1,87 @@
+use super::constants::*;
+use super::{escape_text, ChartOptions, Layout};
+use std::fmt::Write;
+
+/// Generates the title and the axis labels, positioned in the margins around
+/// the chart.
+pub(super) fn generate_labels_svg<S>(layout: &Layout, options: &ChartOptions<S>) -> String {
+    let tick_color = &options.theme.tick_color;
+    let font_family = &options.theme.font_family;
+    let Layout {
+        margin_left,
+        margin_top,
+        plot_width,
+        plot_height,
+        secondary_y_axis_shown,
+        ..
+    } = *layout;
+
+    let mut svg = String::new();
+
+    if let Some(title) = options.title {
+        let x = 0.5 * options.width as f64;
+        write!(
+            svg,
+            "<text x=\"{x:.1}\" y=\"{TITLE_FONT_SIZE}\" \
+                fill=\"{tick_color}\" \
+                font-family=\"{font_family}\" \
+                font-size=\"{TITLE_FONT_SIZE}\" \
+                font-weight=\"{TITLE_FONT_WEIGHT}\" \
+                letter-spacing=\"0\" \
+                text-anchor=\"middle\">{}</text>",
+            escape_text(title)
+        )
+        .expect("Could not write title");
+    }
+
+    let label_attrs = format!(
+        "fill=\"{tick_color}\" \
+        font-family=\"{font_family}\" \
+        font-size=\"{AXIS_LABEL_FONT_SIZE}\" \
+        font-weight=\"{AXIS_LABEL_FONT_WEIGHT}\" \
+        letter-spacing=\"0\" \
+        text-anchor=\"middle\""
+    );
+
+    if let Some(label) = options.x_axis_label {
+        let x = margin_left as f64 + 0.5 * plot_width;
+        let y = margin_top as f64 + plot_height + (MARGIN_BOTTOM + AXIS_LABEL_FONT_SIZE) as f64;
+        write!(
+            svg,
+            "<text x=\"{x:.1}\" y=\"{y:.1}\" {label_attrs}>{}</text>",
+            escape_text(label)
+        )
+        .expect("Could not write X axis label");
+    }
+
+    if let Some(label) = options.y_axis_label {
+        // The label is rotated counter-clockwise, so its glyphs extend to the
+        // left of the baseline.
+        let x = AXIS_LABEL_FONT_SIZE as f64;
+        let y = margin_top as f64 + 0.5 * plot_height;
+        write!(
+            svg,
+            "<text x=\"{x:.1}\" y=\"{y:.1}\" transform=\"rotate(-90, {x:.1}, {y:.1})\" {label_attrs}>{}</text>",
+            escape_text(label)
+        )
+        .expect("Could not write Y axis label");
+    }
+
+    if let Some(label) = options
+        .secondary_y_axis_label
+        .filter(|_| secondary_y_axis_shown)
+    {
+        // The label is rotated clockwise, so its glyphs extend to the right of
+        // the baseline.
+        let x = options.width.saturating_sub(AXIS_LABEL_FONT_SIZE) as f64;
+        let y = margin_top as f64 + 0.5 * plot_height;
+        write!(
+            svg,
+            "<text x=\"{x:.1}\" y=\"{y:.1}\" transform=\"rotate(90, {x:.1}, {y:.1})\" {label_attrs}>{}</text>",
+            escape_text(label)
+        )
+        .expect("Could not write secondary Y axis label");
+    }
+
+    svg
+}
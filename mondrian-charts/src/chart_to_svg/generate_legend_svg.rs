@@ -0,0 +1,94 @@
+use super::constants::*;
+use super::{escape_text, estimate_text_width, ChartOptions};
+use crate::MondrianChart;
+use std::fmt::Write;
+
+/// A single item in the legend, corresponding to a single shape list.
+struct LegendItem<'a> {
+    name: String,
+    color: &'a str,
+    x: f64,
+}
+
+/// Legend with its items laid out in rows that fit within the available
+/// width.
+pub(super) struct Legend<'a> {
+    rows: Vec<Vec<LegendItem<'a>>>,
+}
+
+impl<'a> Legend<'a> {
+    /// Lays out the legend for the given chart.
+    ///
+    /// Returns `None` if no legend should be rendered.
+    pub fn new<S, P>(
+        chart: &'a MondrianChart<S, P>,
+        options: &ChartOptions<'a, S>,
+        max_width: f64,
+    ) -> Option<Self> {
+        let get_shape_list_name = options.get_shape_list_name?;
+        if chart.shape_lists.is_empty() {
+            return None;
+        }
+
+        let mut rows = vec![Vec::new()];
+        let mut x = 0.;
+        for (index, shape_list) in chart.shape_lists.iter().enumerate() {
+            let name = get_shape_list_name(&shape_list.source, index);
+            let color = (options.get_shape_list_color)(&shape_list.source, index);
+            let item_width = (LEGEND_SWATCH_SIZE + LEGEND_SWATCH_SPACING) as f64
+                + estimate_text_width(&name, LEGEND_FONT_SIZE);
+
+            if x > 0. && x + item_width > max_width {
+                rows.push(Vec::new());
+                x = 0.;
+            }
+
+            rows.last_mut()
+                .expect("There is always at least one row")
+                .push(LegendItem { name, color, x });
+            x += item_width + LEGEND_ITEM_SPACING as f64;
+        }
+
+        Some(Self { rows })
+    }
+
+    /// Returns the height of the legend, including its top margin.
+    pub fn height(&self) -> u16 {
+        LEGEND_MARGIN_TOP + self.rows.len() as u16 * LEGEND_ROW_HEIGHT
+    }
+
+    pub fn to_svg<S>(&self, options: &ChartOptions<S>) -> String {
+        let tick_color = &options.theme.tick_color;
+        let font_family = &options.theme.font_family;
+        let swatch_y = (LEGEND_ROW_HEIGHT - LEGEND_SWATCH_SIZE) / 2;
+        let label_x = LEGEND_SWATCH_SIZE + LEGEND_SWATCH_SPACING;
+        let label_y = LEGEND_ROW_HEIGHT / 2;
+        let label_attrs = format!(
+            "dy=\"0.35em\" \
+            fill=\"{tick_color}\" \
+            font-family=\"{font_family}\" \
+            font-size=\"{LEGEND_FONT_SIZE}\" \
+            font-weight=\"{LEGEND_FONT_WEIGHT}\" \
+            letter-spacing=\"0\""
+        );
+
+        let mut svg = format!("<g transform=\"translate(0, {LEGEND_MARGIN_TOP})\">");
+        for (row_index, row) in self.rows.iter().enumerate() {
+            let row_y = row_index as u16 * LEGEND_ROW_HEIGHT;
+            for LegendItem { name, color, x } in row {
+                write!(
+                    svg,
+                    "<g transform=\"translate({x:.1}, {row_y})\">\
+                        <rect x=\"0\" y=\"{swatch_y}\" width=\"{LEGEND_SWATCH_SIZE}\" height=\"{LEGEND_SWATCH_SIZE}\" fill=\"{color}\" />\
+                        <text x=\"{label_x}\" y=\"{label_y}\" {label_attrs}>{}</text>\
+                    </g>",
+                    escape_text(name)
+                )
+                .expect("Could not write legend item");
+            }
+        }
+        svg.push_str("</g>");
+
+        svg
+    }
+}
@@ -0,0 +1,80 @@
+use super::constants::*;
+use super::{escape_text, ChartOptions, Scales};
+use crate::types::{MondrianChart, Shape};
+use std::fmt::Write;
+
+/// Generates the labels for shape lists that have one, such as annotations.
+///
+/// Every label is rendered alongside the first shape in its shape list, using
+/// the color of the shape list.
+pub(super) fn generate_shape_labels_svg<'a, S, P>(
+    chart: &'a MondrianChart<S, P>,
+    scales: &Scales,
+    options: &ChartOptions<'a, S>,
+) -> String {
+    let font_family = &options.theme.font_family;
+    let mut svg = String::new();
+
+    for (index, shape_list) in chart.shape_lists.iter().enumerate() {
+        let (Some(label), Some(shape)) = (&shape_list.label, shape_list.shapes.first()) else {
+            continue;
+        };
+
+        let color = (options.get_shape_list_color)(&shape_list.source, index);
+        let (x, y, text_anchor) = get_label_position(shape, scales);
+
+        write!(
+            svg,
+            "<text x=\"{x:.1}\" y=\"{y:.1}\" \
+                fill=\"{color}\" \
+                font-family=\"{font_family}\" \
+                font-size=\"{SHAPE_LABEL_FONT_SIZE}\" \
+                font-weight=\"{SHAPE_LABEL_FONT_WEIGHT}\" \
+                letter-spacing=\"0\" \
+                text-anchor=\"{text_anchor}\">{}</text>",
+            escape_text(label)
+        )
+        .expect("Could not write shape label");
+    }
+
+    svg
+}
+
+/// Returns the position and text anchor for the label of the given shape.
+///
+/// Labels for lines are placed above the end of the line, while labels for
+/// other shapes are placed inside their top-left corner, within the bounds of
+/// the chart.
+fn get_label_position<P>(shape: &Shape<P>, scales: &Scales) -> (f64, f64, &'static str) {
+    let padding = SHAPE_LABEL_PADDING as f64;
+    let font_size = SHAPE_LABEL_FONT_SIZE as f64;
+
+    let top_left = |x: f64, y: f64| {
+        (
+            scales.x(x).max(0.) + padding,
+            scales.y(y).max(0.) + padding + font_size,
+            "start",
+        )
+    };
+
+    match shape {
+        Shape::Line(line) => match line.points.last() {
+            Some(point) => (
+                scales.x(point.x) - padding,
+                scales.y(point.y) - padding,
+                "end",
+            ),
+            None => top_left(0., 1.),
+        },
+        Shape::Area(area) => match area.points.first() {
+            Some(point) => top_left(point.x, point.y_max),
+            None => top_left(0., 1.),
+        },
+        Shape::Point(point) => (
+            scales.x(point.x) + padding,
+            scales.y(point.y) - padding,
+            "start",
+        ),
+        Shape::Rectangle(rectangle) => top_left(rectangle.x, rectangle.y + rectangle.height),
+    }
+}
@@ -0,0 +1,77 @@
+use super::constants::*;
+use super::generate_legend_svg::Legend;
+use super::ChartOptions;
+use crate::MondrianChart;
+
+/// Position and size of the plot area, which is what remains of the chart
+/// after reserving space for the ticks, the axis labels, the title and the
+/// legend.
+pub(super) struct Layout<'a> {
+    pub margin_left: u16,
+    pub margin_top: u16,
+    pub plot_width: f64,
+    pub plot_height: f64,
+
+    /// Whether space is reserved for a secondary Y axis on the right.
+    pub secondary_y_axis_shown: bool,
+
+    /// The legend to render below the chart, if any.
+    pub legend: Option<Legend<'a>>,
+
+    /// Vertical offset at which the legend starts.
+    pub legend_y: f64,
+}
+
+impl<'a> Layout<'a> {
+    pub fn new<S, P>(chart: &'a MondrianChart<S, P>, options: &ChartOptions<'a, S>) -> Self {
+        let mut margin_left = MARGIN_LEFT;
+        let mut margin_top = MARGIN_TOP;
+        let mut margin_right = MARGIN_RIGHT;
+        let mut margin_bottom = MARGIN_BOTTOM;
+
+        if options.title.is_some() {
+            margin_top += TITLE_FONT_SIZE + TITLE_MARGIN_BOTTOM;
+        }
+
+        if options.x_axis_label.is_some() {
+            margin_bottom += AXIS_LABEL_FONT_SIZE + AXIS_LABEL_MARGIN;
+        }
+
+        if options.y_axis_label.is_some() {
+            margin_left += AXIS_LABEL_FONT_SIZE + AXIS_LABEL_MARGIN;
+        }
+
+        if chart.secondary_y_axis.is_some() {
+            // The ticks along the secondary Y axis take up as much space as
+            // the ones along the primary Y axis.
+            if options.secondary_y_formatter.is_some() {
+                margin_right += MARGIN_LEFT;
+            }
+
+            if options.secondary_y_axis_label.is_some() {
+                margin_right += AXIS_LABEL_FONT_SIZE + AXIS_LABEL_MARGIN;
+            }
+        }
+
+        let plot_width = options.width.saturating_sub(margin_left + margin_right);
+
+        // The legend is aligned with the plot area, and takes up as many rows
+        // as necessary to fit its items within the same width.
+        let legend = Legend::new(chart, options, plot_width as f64);
+        let legend_height = legend.as_ref().map(Legend::height).unwrap_or_default();
+
+        let plot_height = options
+            .height
+            .saturating_sub(margin_top + margin_bottom + legend_height);
+
+        Self {
+            margin_left,
+            margin_top,
+            plot_width: plot_width as f64,
+            plot_height: plot_height as f64,
+            secondary_y_axis_shown: chart.secondary_y_axis.is_some(),
+            legend,
+            legend_y: (margin_top + plot_height + margin_bottom) as f64,
+        }
+    }
+}
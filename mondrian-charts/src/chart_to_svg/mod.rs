@@ -1,20 +1,48 @@
 mod constants;
 mod create_area_path_def;
 mod create_line_path_def;
+mod downsampling;
 mod generate_chart_content_svg;
 mod generate_grid_and_axes_svg;
+mod generate_hover_layer_svg;
+mod generate_labels_svg;
+mod generate_legend_svg;
+mod generate_shape_labels_svg;
+mod layout;
 mod scales;
+mod theme;
 mod tick_formatters;
 
 use crate::MondrianChart;
 use constants::*;
 use generate_chart_content_svg::generate_chart_content_svg;
 use generate_grid_and_axes_svg::generate_grid_and_axes_svg;
+use generate_hover_layer_svg::{generate_hover_layer_style, generate_hover_layer_svg};
+use generate_labels_svg::generate_labels_svg;
+use generate_shape_labels_svg::generate_shape_labels_svg;
+use layout::Layout;
 use scales::Scales;
+use std::sync::OnceLock;
 
-pub use self::tick_formatters::FormatterKind;
+pub use self::downsampling::{BarAggregation, Downsampling};
+pub use self::generate_hover_layer_svg::Interactivity;
+pub use self::theme::ChartTheme;
+pub use self::tick_formatters::{DateFormat, FormatterKind, HourCycle, TimeFormatOptions};
 
 /// Options used for rendering charts.
+///
+/// Use [`Default`] for the options that don't matter to you, so that adding
+/// new options doesn't break your code:
+///
+/// ```
+/// # use mondrian_charts::{ChartOptions, FormatterKind};
+/// let options: ChartOptions<()> = ChartOptions {
+///     width: 320,
+///     height: 240,
+///     y_formatter: Some(FormatterKind::Scientific),
+///     ..Default::default()
+/// };
+/// ```
 pub struct ChartOptions<'a, S> {
     /// The width the chart should be rendered as.
     pub width: u16,
@@ -35,16 +63,17 @@ pub struct ChartOptions<'a, S> {
     /// Whether grid lines should be rendered along the ticks on the Y axis.
     pub grid_rows_shown: bool,
 
-    /// The color to use for rendering grid lines along the ticks.
+    /// The theme to use for rendering the chart.
     ///
-    /// Must be a valid CSS color string.
-    pub grid_stroke_color: &'a str,
+    /// Note that the colors of shape lists are determined by
+    /// `get_shape_list_color`, which may use the palette of the theme.
+    pub theme: &'a ChartTheme,
 
-    /// Optional dasharray to apply to the grid lines.
-    pub grid_stroke_dasharray: &'a [f32],
-
-    /// Optional stroke width of the chart content lines.
-    pub shape_stroke_width: Option<f32>,
+    /// Optional downsampling to apply to the shapes, based on the width of the
+    /// chart.
+    ///
+    /// If `None`, every shape in the chart is rendered.
+    pub downsampling: Option<Downsampling>,
 
     /// Callback to determine the color that should be used for a given shape
     /// list.
@@ -55,10 +84,13 @@ pub struct ChartOptions<'a, S> {
     /// The returned color must be a valid CSS color string.
     pub get_shape_list_color: &'a dyn Fn(&'a S, usize) -> &'a str,
 
-    /// The color to use for rendering ticks along the axes.
+    /// Optional callback to determine the name that should be displayed in
+    /// the legend for a given shape list.
     ///
-    /// Must be a valid CSS color string.
-    pub tick_color: &'a str,
+    /// Shape lists are identified the same way as for `get_shape_list_color`.
+    /// If `None`, no legend is shown. Otherwise, the legend is rendered below
+    /// the chart, with one item for every shape list.
+    pub get_shape_list_name: Option<&'a dyn Fn(&'a S, usize) -> String>,
 
     /// The type of formatter to use for ticks along the X axis.
     ///
@@ -69,25 +101,140 @@ pub struct ChartOptions<'a, S> {
     ///
     /// If `None`, no ticks are shown along the Y axis.
     pub y_formatter: Option<FormatterKind>,
+
+    /// The type of formatter to use for ticks along the secondary Y axis.
+    ///
+    /// If `None`, or if the chart has no secondary Y axis, no ticks are shown
+    /// along the right-hand side of the chart.
+    pub secondary_y_formatter: Option<FormatterKind>,
+
+    /// Time zone and locale preferences for formatting ticks along an axis
+    /// that uses [`FormatterKind::Time`].
+    ///
+    /// Ticks along such an X axis are also aligned to round times, such as
+    /// whole hours or midnight, in the configured time zone.
+    pub time_format: TimeFormatOptions,
+
+    /// Optional title to render above the chart.
+    pub title: Option<&'a str>,
+
+    /// Optional label to render below the ticks along the X axis.
+    pub x_axis_label: Option<&'a str>,
+
+    /// Optional label to render to the left of the ticks along the Y axis.
+    pub y_axis_label: Option<&'a str>,
+
+    /// Optional label to render to the right of the ticks along the secondary
+    /// Y axis.
+    ///
+    /// Ignored if the chart has no secondary Y axis.
+    pub secondary_y_axis_label: Option<&'a str>,
+}
+
+/// Renders a 640x480 chart with the [light](ChartTheme::light) theme, without
+/// axis lines, grid lines, ticks, labels or legend. Shape lists are colored
+/// using the palette of the light theme.
+impl<'a, S> Default for ChartOptions<'a, S> {
+    fn default() -> Self {
+        Self {
+            width: 640,
+            height: 480,
+            area_gradient_shown: false,
+            axis_lines_shown: false,
+            grid_columns_shown: false,
+            grid_rows_shown: false,
+            theme: light_theme(),
+            downsampling: None,
+            get_shape_list_color: &get_palette_color,
+            get_shape_list_name: None,
+            x_formatter: None,
+            y_formatter: None,
+            secondary_y_formatter: None,
+            time_format: TimeFormatOptions::default(),
+            title: None,
+            x_axis_label: None,
+            y_axis_label: None,
+            secondary_y_axis_label: None,
+        }
+    }
+}
+
+fn light_theme() -> &'static ChartTheme {
+    static LIGHT_THEME: OnceLock<ChartTheme> = OnceLock::new();
+    LIGHT_THEME.get_or_init(ChartTheme::light)
+}
+
+fn get_palette_color<S>(_: &S, index: usize) -> &str {
+    light_theme().palette_color(index)
 }
 
 pub fn chart_to_svg<'a, S, P>(
     chart: &'a MondrianChart<S, P>,
     options: &ChartOptions<'a, S>,
+) -> String {
+    render_svg(chart, options, None)
+}
+
+/// Serializes the chart to an SVG string that shows the values of data points
+/// when they are hovered.
+///
+/// See [`Interactivity`] for details on what is rendered.
+pub fn chart_to_interactive_svg<'a, S, P>(
+    chart: &'a MondrianChart<S, P>,
+    options: &ChartOptions<'a, S>,
+    interactivity: &Interactivity<'a, S, P>,
+) -> String {
+    render_svg(chart, options, Some(interactivity))
+}
+
+fn render_svg<'a, S, P>(
+    chart: &'a MondrianChart<S, P>,
+    options: &ChartOptions<'a, S>,
+    interactivity: Option<&Interactivity<'a, S, P>>,
 ) -> String {
     let ChartOptions { width, height, .. } = options;
 
-    let x_max = width - MARGIN_LEFT - MARGIN_RIGHT;
-    let y_max = height - MARGIN_TOP - MARGIN_BOTTOM;
+    let layout = Layout::new(chart, options);
+    let &Layout {
+        margin_left,
+        margin_top,
+        plot_width: x_max,
+        plot_height: y_max,
+        ..
+    } = &layout;
 
-    let scales = Scales::new(x_max as f64, y_max as f64);
+    let scales = Scales::new(x_max, y_max);
 
     let clip_path_id = "clip-path";
     let clip_path_y_start = -(CHART_SHAPE_OVERFLOW_MARGIN as i32);
-    let clip_path_height = y_max + 2 * CHART_SHAPE_OVERFLOW_MARGIN;
+    let clip_path_height = y_max + 2. * CHART_SHAPE_OVERFLOW_MARGIN as f64;
 
     let grid_and_axes = generate_grid_and_axes_svg(chart, &scales, options);
     let chart_content = generate_chart_content_svg(chart, &scales, options);
+    let shape_labels = generate_shape_labels_svg(chart, &scales, options);
+    let (hover_layer_style, hover_layer) = interactivity
+        .map(|interactivity| {
+            (
+                generate_hover_layer_style(),
+                format!(
+                    "<g clip-path=\"url(#{clip_path_id})\">{}</g>",
+                    generate_hover_layer_svg(chart, &scales, options, interactivity)
+                ),
+            )
+        })
+        .unwrap_or_default();
+    let labels = generate_labels_svg(&layout, options);
+    let legend = layout
+        .legend
+        .as_ref()
+        .map(|legend| {
+            let legend_y = layout.legend_y;
+            format!(
+                "<g transform=\"translate({margin_left}, {legend_y})\">{}</g>",
+                legend.to_svg(options)
+            )
+        })
+        .unwrap_or_default();
 
     format!(
         "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"{width}\" height=\"{height}\">\
@@ -95,11 +242,37 @@ pub fn chart_to_svg<'a, S, P>(
                 <clipPath id=\"{clip_path_id}\">\
                   <rect x=\"0\" y=\"{clip_path_y_start}\" width=\"{x_max}\" height=\"{clip_path_height}\" />\
                 </clipPath>\
+                {hover_layer_style}\
             </defs>\
-            <g transform=\"translate({MARGIN_LEFT}, {MARGIN_TOP})\">\
+            {labels}\
+            <g transform=\"translate({margin_left}, {margin_top})\">\
               {grid_and_axes}\
               <g clip-path=\"url(#{clip_path_id})\">{chart_content}</g>\
+              {shape_labels}\
+              {hover_layer}\
             </g>\
+            {legend}\
         </svg>"
     )
 }
+
+/// Escapes text so it can be safely embedded inside an SVG text element.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Estimates the width of the given text when rendered at the given font size.
+fn estimate_text_width(text: &str, font_size: u16) -> f64 {
+    text.chars().count() as f64 * AVERAGE_CHAR_WIDTH * font_size as f64
+}
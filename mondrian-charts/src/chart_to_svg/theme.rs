@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+/// Colors, font and stroke widths used for rendering charts.
+///
+/// Themes can be (de)serialized, so they can be stored in configuration files.
+/// Any fields that are missing when deserializing a theme are taken from the
+/// [light](ChartTheme::light) theme.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ChartTheme {
+    /// The color of the background the chart is intended to be rendered on.
+    ///
+    /// SVG output is rendered with a transparent background, so it can be
+    /// embedded anywhere. Use this color as the background color when
+    /// rendering the chart to an image.
+    ///
+    /// Must be a valid CSS color string.
+    pub background_color: String,
+
+    /// The font family to use for all text in the chart.
+    pub font_family: String,
+
+    /// The color to use for rendering ticks along the axes.
+    ///
+    /// This color is also used for the title, the axis labels and the legend.
+    ///
+    /// Must be a valid CSS color string.
+    pub tick_color: String,
+
+    /// The color to use for rendering grid lines along the ticks.
+    ///
+    /// Must be a valid CSS color string.
+    pub grid_stroke_color: String,
+
+    /// Optional dasharray to apply to the grid lines.
+    pub grid_stroke_dasharray: Vec<f32>,
+
+    /// Stroke width of the chart content lines.
+    pub shape_stroke_width: f32,
+
+    /// Colors to use for shape lists, which can be looked up using
+    /// [`ChartTheme::palette_color()`].
+    ///
+    /// Must contain valid CSS color strings.
+    pub palette: Vec<String>,
+}
+
+impl ChartTheme {
+    /// Theme for rendering charts on a light background.
+    pub fn light() -> Self {
+        Self {
+            background_color: "#ffffff".to_owned(),
+            font_family: "sans-serif".to_owned(),
+            tick_color: "#a4a4a4".to_owned(),
+            grid_stroke_color: "#e7e7e7".to_owned(),
+            grid_stroke_dasharray: Vec::new(),
+            shape_stroke_width: 1.0,
+            palette: to_owned_strings(&[
+                "#c00eae", "#4c7aff", "#23b5a0", "#f7a325", "#e5383b", "#8f5dd7", "#1e88e5",
+                "#6d8b0f",
+            ]),
+        }
+    }
+
+    /// Theme for rendering charts on a dark background.
+    pub fn dark() -> Self {
+        Self {
+            background_color: "#1f2023".to_owned(),
+            font_family: "sans-serif".to_owned(),
+            tick_color: "#8c8c94".to_owned(),
+            grid_stroke_color: "#36373d".to_owned(),
+            grid_stroke_dasharray: Vec::new(),
+            shape_stroke_width: 1.0,
+            palette: to_owned_strings(&[
+                "#ff5ce6", "#7b9dff", "#63eaad", "#ffc05c", "#ff6b6e", "#b68cff", "#5cb3ff",
+                "#b5d65c",
+            ]),
+        }
+    }
+
+    /// Returns the palette color for the shape list with the given index.
+    ///
+    /// If there are more shape lists than colors in the palette, colors are
+    /// reused. If the palette is empty, the tick color is returned.
+    pub fn palette_color(&self, index: usize) -> &str {
+        if self.palette.is_empty() {
+            &self.tick_color
+        } else {
+            &self.palette[index % self.palette.len()]
+        }
+    }
+}
+
+impl Default for ChartTheme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+fn to_owned_strings(strings: &[&str]) -> Vec<String> {
+    strings.iter().map(|string| (*string).to_owned()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChartOptions;
+
+    #[test]
+    fn test_deserialize_partial_theme() {
+        let theme: ChartTheme =
+            serde_json::from_str(r##"{"tickColor": "#000000", "palette": ["red", "blue"]}"##)
+                .unwrap();
+
+        assert_eq!(theme.tick_color, "#000000");
+        assert_eq!(
+            theme.grid_stroke_color,
+            ChartTheme::light().grid_stroke_color
+        );
+        assert_eq!(theme.palette_color(0), "red");
+        assert_eq!(theme.palette_color(3), "blue");
+    }
+
+    #[test]
+    fn test_default_options_use_light_theme() {
+        let options: ChartOptions<()> = ChartOptions::default();
+
+        assert_eq!(options.theme.tick_color, ChartTheme::light().tick_color);
+        assert_eq!((options.get_shape_list_color)(&(), 1), "#4c7aff");
+    }
+}
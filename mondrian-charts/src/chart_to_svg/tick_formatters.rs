@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Write};
 use time::{
     format_description::{modifier, Component, FormatItem},
-    OffsetDateTime,
+    OffsetDateTime, UtcOffset,
 };
 
 #[derive(Clone, Copy, Default, Deserialize, Eq, PartialEq, Serialize)]
@@ -51,7 +51,8 @@ pub enum FormatterKind {
     /// For brevity, the formatter omits the most significant parts of the time
     /// that are constant across the axis.
     ///
-    /// Currently, only UTC formatting is supported.
+    /// Times are displayed in UTC, unless configured otherwise through the
+    /// [TimeFormatOptions].
     ///
     /// ## Examples
     ///
@@ -59,18 +60,81 @@ pub enum FormatterKind {
     Time,
 }
 
+/// Preferences for formatting ticks along a time axis.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TimeFormatOptions {
+    /// Offset from UTC of the time zone in which times are displayed, in
+    /// seconds.
+    ///
+    /// Ticks are aligned to round times in this time zone, such as whole hours
+    /// or midnight. For time zones that observe daylight saving time, use the
+    /// offset that applies to the displayed time range. Since the offset is
+    /// fixed, ticks at intervals of days or weeks stay at the same UTC time
+    /// across DST transitions, so they are an hour off midnight on one side of
+    /// the transition. Offsets outside the range of ±26 hours are ignored.
+    pub utc_offset_seconds: i32,
+
+    /// The clock to use for displaying hours.
+    pub hour_cycle: HourCycle,
+
+    /// The format to use for displaying dates.
+    pub date_format: DateFormat,
+}
+
+impl TimeFormatOptions {
+    pub(crate) fn utc_offset(&self) -> UtcOffset {
+        UtcOffset::from_whole_seconds(self.utc_offset_seconds).unwrap_or(UtcOffset::UTC)
+    }
+}
+
+/// The clock to use for displaying hours.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HourCycle {
+    /// 24-hour clock, such as `17:15`.
+    #[default]
+    H24,
+
+    /// 12-hour clock, such as `5:15pm`.
+    H12,
+}
+
+/// The format to use for displaying dates, which is used when an axis spans
+/// multiple weeks.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateFormat {
+    /// Weekday followed by the day of the month, such as `Fri 13`.
+    #[default]
+    WeekdayDay,
+
+    /// Day of the month followed by the month, such as `13 Oct`.
+    DayMonth,
+
+    /// Month followed by the day of the month, such as `Oct 13`.
+    MonthDay,
+
+    /// ISO 8601 month and day, such as `10-13`.
+    Iso,
+}
+
 pub trait TickFormatter {
     fn format(&self, value: f64) -> String;
 }
 
-pub fn get_formatter_for_axis(axis: &Axis, kind: FormatterKind) -> Box<dyn TickFormatter> {
+pub fn get_formatter_for_axis(
+    axis: &Axis,
+    kind: FormatterKind,
+    time_format: TimeFormatOptions,
+) -> Box<dyn TickFormatter> {
     match kind {
         FormatterKind::Bytes => Box::new(BytesFormatter::for_axis(axis)),
         FormatterKind::Duration => Box::new(DurationFormatter::for_axis(axis)),
         FormatterKind::Exponent => Box::new(ExponentFormatter::new()),
         FormatterKind::Percentage => Box::new(PercentageFormatter::new()),
         FormatterKind::Scientific => Box::new(ScientificFormatter::for_axis(axis)),
-        FormatterKind::Time => Box::new(TimeFormatter::for_axis(axis)),
+        FormatterKind::Time => Box::new(TimeFormatter::for_axis(axis, time_format)),
     }
 }
 
@@ -242,12 +306,14 @@ impl TickFormatter for ScientificFormatter {
 
 pub struct TimeFormatter {
     scale: TimeScale,
+    options: TimeFormatOptions,
 }
 
 impl TimeFormatter {
-    pub fn for_axis(axis: &Axis) -> Self {
+    pub fn for_axis(axis: &Axis, options: TimeFormatOptions) -> Self {
         Self {
             scale: TimeScale::for_axis(axis),
+            options,
         }
     }
 }
@@ -257,13 +323,49 @@ impl TickFormatter for TimeFormatter {
         let Ok(timestamp) = OffsetDateTime::from_unix_timestamp_nanos((value * 1e9) as i128) else {
             return "-".to_owned();
         };
+        let timestamp = timestamp.to_offset(self.options.utc_offset());
 
-        timestamp
-            .format(&self.scale.format_description())
-            .unwrap_or_else(|_| "-".to_owned())
+        match (self.scale, self.options.hour_cycle) {
+            (TimeScale::DayInMonth, _) if self.options.date_format != DateFormat::WeekdayDay => {
+                format_date(timestamp, self.options.date_format)
+            }
+            (TimeScale::DayInWeek | TimeScale::Hours | TimeScale::Minutes, HourCycle::H12) => {
+                format_12_hour_time(timestamp, self.scale)
+            }
+            _ => timestamp
+                .format(&self.scale.format_description())
+                .unwrap_or_else(|_| "-".to_owned()),
+        }
     }
 }
 
+fn format_date(timestamp: OffsetDateTime, date_format: DateFormat) -> String {
+    let day = timestamp.day();
+    let month = timestamp.month();
+    match date_format {
+        DateFormat::WeekdayDay => format!("{} {day}", abbreviate(timestamp.weekday())),
+        DateFormat::DayMonth => format!("{day} {}", abbreviate(month)),
+        DateFormat::MonthDay => format!("{} {day}", abbreviate(month)),
+        DateFormat::Iso => format!("{:02}-{day:02}", month as u8),
+    }
+}
+
+fn format_12_hour_time(timestamp: OffsetDateTime, scale: TimeScale) -> String {
+    let (hour, minute, second) = timestamp.to_hms();
+    let period = if hour < 12 { "am" } else { "pm" };
+    let hour = (hour + 11) % 12 + 1;
+    match scale {
+        TimeScale::DayInWeek => format!("{} {hour}{period}", abbreviate(timestamp.weekday())),
+        TimeScale::Hours => format!("{hour}:{minute:02}{period}"),
+        _ => format!("{hour}:{minute:02}:{second:02}{period}"),
+    }
+}
+
+/// Returns the first three characters of a weekday or month name.
+fn abbreviate(name: impl Display) -> String {
+    name.to_string().chars().take(3).collect()
+}
+
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 enum DurationUnit {
     Days,
@@ -519,6 +621,7 @@ mod tests {
             min_value: 0.,
             max_value: 123456789.,
             tick_suggestions: None,
+            ..Default::default()
         };
         let formatter = BytesFormatter::for_axis(&axis);
         assert_eq!(formatter.format(123456789.), "123MB");
@@ -543,6 +646,7 @@ mod tests {
             min_value: 0.,
             max_value: 123456789.,
             tick_suggestions: None,
+            ..Default::default()
         };
         let formatter = DurationFormatter::for_axis(&axis);
         assert_eq!(formatter.format(123456789.), "1429d");
@@ -564,6 +668,7 @@ mod tests {
             min_value: 0.,
             max_value: 123.456789,
             tick_suggestions: None,
+            ..Default::default()
         };
         let formatter = ScientificFormatter::for_axis(&axis);
         assert_eq!(formatter.format(123.456789), "123");
@@ -633,6 +738,7 @@ mod tests {
             min_value: 0.,
             max_value: 123456789.,
             tick_suggestions: None,
+            ..Default::default()
         };
         let formatter = ScientificFormatter::for_axis(&axis);
         assert_eq!(formatter.format(123456789.), "123M");
@@ -654,6 +760,7 @@ mod tests {
             min_value: 0.,
             max_value: 123.456789,
             tick_suggestions: None,
+            ..Default::default()
         };
         let formatter = ScientificFormatter::for_axis(&axis);
         assert_eq!(formatter.format(123.456789), "123");
@@ -676,48 +783,124 @@ mod tests {
             min_value: 1691496477.932,
             max_value: 1692446877.932,
             tick_suggestions: None,
+            ..Default::default()
         };
-        let formatter = TimeFormatter::for_axis(&axis);
+        let formatter = TimeFormatter::for_axis(&axis, TimeFormatOptions::default());
         assert_eq!(formatter.format(1691496477.932), "Tue 8");
 
         let axis = Axis {
             min_value: 1691496477.932,
             max_value: 1691928477.932,
             tick_suggestions: None,
+            ..Default::default()
         };
-        let formatter = TimeFormatter::for_axis(&axis);
+        let formatter = TimeFormatter::for_axis(&axis, TimeFormatOptions::default());
         assert_eq!(formatter.format(1691496477.932), "Tue 12h");
 
         let axis = Axis {
             min_value: 1691496477.932,
             max_value: 1691579277.932,
             tick_suggestions: None,
+            ..Default::default()
         };
-        let formatter = TimeFormatter::for_axis(&axis);
+        let formatter = TimeFormatter::for_axis(&axis, TimeFormatOptions::default());
         assert_eq!(formatter.format(1691496477.932), "12:07");
 
         let axis = Axis {
             min_value: 1691496477.932,
             max_value: 1691496837.932,
             tick_suggestions: None,
+            ..Default::default()
         };
-        let formatter = TimeFormatter::for_axis(&axis);
+        let formatter = TimeFormatter::for_axis(&axis, TimeFormatOptions::default());
         assert_eq!(formatter.format(1691496477.932), "12:07:57");
 
         let axis = Axis {
             min_value: 1691496477.932,
             max_value: 1691496489.932,
             tick_suggestions: None,
+            ..Default::default()
         };
-        let formatter = TimeFormatter::for_axis(&axis);
+        let formatter = TimeFormatter::for_axis(&axis, TimeFormatOptions::default());
         assert_eq!(formatter.format(1691496477.932), "57.932");
 
         let axis = Axis {
             min_value: 1691496477.932,
             max_value: 1691496478.932,
             tick_suggestions: None,
+            ..Default::default()
         };
-        let formatter = TimeFormatter::for_axis(&axis);
+        let formatter = TimeFormatter::for_axis(&axis, TimeFormatOptions::default());
         assert_eq!(formatter.format(1691496477.932), ".932");
     }
+
+    #[test]
+    fn test_time_formatter_with_options() {
+        let axis = Axis {
+            min_value: 1691496477.932,
+            max_value: 1691579277.932,
+            tick_suggestions: None,
+            ..Default::default()
+        };
+        let options = TimeFormatOptions {
+            utc_offset_seconds: 5 * 3600 + 1800,
+            ..Default::default()
+        };
+        let formatter = TimeFormatter::for_axis(&axis, options);
+        assert_eq!(formatter.format(1691496477.932), "17:37");
+
+        let options = TimeFormatOptions {
+            hour_cycle: HourCycle::H12,
+            ..Default::default()
+        };
+        let formatter = TimeFormatter::for_axis(&axis, options);
+        assert_eq!(formatter.format(1691496477.932), "12:07pm");
+
+        let options = TimeFormatOptions {
+            utc_offset_seconds: -8 * 3600,
+            hour_cycle: HourCycle::H12,
+            ..Default::default()
+        };
+        let formatter = TimeFormatter::for_axis(&axis, options);
+        assert_eq!(formatter.format(1691496477.932), "4:07am");
+
+        let axis = Axis {
+            min_value: 1691496477.932,
+            max_value: 1691928477.932,
+            tick_suggestions: None,
+            ..Default::default()
+        };
+        let formatter = TimeFormatter::for_axis(&axis, options);
+        assert_eq!(formatter.format(1691496477.932), "Tue 4am");
+
+        let axis = Axis {
+            min_value: 1691496477.932,
+            max_value: 1692446877.932,
+            tick_suggestions: None,
+            ..Default::default()
+        };
+        for (date_format, expected) in [
+            (DateFormat::WeekdayDay, "Tue 8"),
+            (DateFormat::DayMonth, "8 Aug"),
+            (DateFormat::MonthDay, "Aug 8"),
+            (DateFormat::Iso, "08-08"),
+        ] {
+            let options = TimeFormatOptions {
+                date_format,
+                ..Default::default()
+            };
+            let formatter = TimeFormatter::for_axis(&axis, options);
+            assert_eq!(formatter.format(1691496477.932), expected);
+        }
+    }
+
+    #[test]
+    fn test_deserialize_time_format_options() {
+        let options: TimeFormatOptions =
+            serde_json::from_str(r#"{"utcOffsetSeconds": 3600, "hourCycle": "h12"}"#).unwrap();
+
+        assert_eq!(options.utc_offset_seconds, 3600);
+        assert_eq!(options.hour_cycle, HourCycle::H12);
+        assert_eq!(options.date_format, DateFormat::WeekdayDay);
+    }
 }
@@ -1,2 +1,3 @@
 pub const BAR_PADDING: f64 = 0.2;
 pub const BAR_PLUS_PADDING: f64 = 1. + BAR_PADDING;
+pub const ANNOTATION_INTENSITY: f64 = 0.15;
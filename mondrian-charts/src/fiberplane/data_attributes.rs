@@ -0,0 +1,97 @@
+use crate::fiberplane::{Metric, PointSource, ProviderEvent, SeriesSource, Timeseries};
+
+/// Returns the data attributes that describe a timeseries, consisting of its
+/// name and its labels.
+///
+/// These can be used as attributes for the shape lists of an interactive
+/// chart.
+pub fn get_timeseries_attributes(timeseries: &Timeseries) -> Vec<(String, String)> {
+    std::iter::once(("name".to_owned(), timeseries.name.clone()))
+        .chain(
+            timeseries
+                .labels
+                .iter()
+                .map(|(key, value)| (format!("label-{key}"), value.clone())),
+        )
+        .collect()
+}
+
+/// Returns the data attributes that describe a metric, consisting of its
+/// timestamp and its value.
+pub fn get_metric_attributes(metric: &Metric) -> Vec<(String, String)> {
+    vec![
+        ("timestamp".to_owned(), metric.time.to_string()),
+        ("value".to_owned(), metric.value.to_string()),
+    ]
+}
+
+/// Returns a tooltip for a metric inside the given timeseries.
+pub fn get_metric_title(timeseries: &Timeseries, metric: &Metric) -> Option<String> {
+    Some(format!(
+        "{}\n{}: {}",
+        timeseries.name, metric.time, metric.value
+    ))
+}
+
+fn get_event_attributes(event: &ProviderEvent) -> Vec<(String, String)> {
+    let mut attributes = vec![
+        ("title".to_owned(), event.title.clone()),
+        ("timestamp".to_owned(), event.time.to_string()),
+    ];
+    if let Some(end_time) = event.end_time {
+        attributes.push(("end-timestamp".to_owned(), end_time.to_string()));
+    }
+    attributes
+}
+
+fn get_event_title(event: &ProviderEvent) -> String {
+    match event.end_time {
+        Some(end_time) => format!("{}\n{} - {}", event.title, event.time, end_time),
+        None => format!("{}\n{}", event.title, event.time),
+    }
+}
+
+impl SeriesSource<'_> {
+    /// Returns the data attributes that describe the series.
+    ///
+    /// See [get_timeseries_attributes()].
+    pub fn data_attributes(&self) -> Vec<(String, String)> {
+        match self {
+            Self::Timeseries(timeseries) => get_timeseries_attributes(timeseries),
+            Self::Events => vec![("type".to_owned(), "events".to_owned())],
+            Self::TargetLatency => vec![("type".to_owned(), "target_latency".to_owned())],
+            Self::Annotation(annotation) => {
+                let mut attributes = vec![("type".to_owned(), "annotation".to_owned())];
+                if let Some(label) = annotation.label() {
+                    attributes.push(("label".to_owned(), label.to_owned()));
+                }
+                attributes
+            }
+        }
+    }
+}
+
+impl PointSource<'_> {
+    /// Returns the data attributes that describe the point.
+    ///
+    /// See [get_metric_attributes()].
+    pub fn data_attributes(&self) -> Vec<(String, String)> {
+        match self {
+            Self::Metric(metric) => get_metric_attributes(metric),
+            Self::Event(event) => get_event_attributes(event),
+            Self::None => Vec::new(),
+        }
+    }
+
+    /// Returns a tooltip for the point inside the given series.
+    pub fn title(&self, series: &SeriesSource) -> Option<String> {
+        match (self, series) {
+            (Self::Metric(metric), SeriesSource::Timeseries(timeseries)) => {
+                get_metric_title(timeseries, metric)
+            }
+            (Self::Metric(metric), _) => Some(format!("{}: {}", metric.time, metric.value)),
+            (Self::Event(event), _) => Some(get_event_title(event)),
+            (Self::None, _) => None,
+        }
+    }
+}
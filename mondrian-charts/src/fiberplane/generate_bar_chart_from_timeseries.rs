@@ -1,32 +1,32 @@
 use super::utils::*;
 use crate::fiberplane::{Metric, MinMax, Timeseries, TimeseriesSourceData};
-use crate::types::{Axis, MondrianChart, Rectangle, Shape, ShapeList};
-use std::convert::identity;
+use crate::types::{Axis, MondrianChart, Rectangle, Shape, ShapeList, YAxis};
 
+/// Generates a bar chart, in which the bars of the secondary timeseries, if
+/// any, are placed next to those of the primary timeseries.
 pub(crate) fn generate_bar_chart_from_timeseries<'source>(
     input: TimeseriesSourceData<'source, '_, '_>,
 ) -> MondrianChart<&'source Timeseries, &'source Metric> {
-    let visible_timeseries_data: Vec<_> = input
-        .timeseries_data
+    let secondary_input = input.secondary();
+    let visible_timeseries_data = get_visible_timeseries(input.timeseries_data);
+    let visible_secondary_timeseries_data = get_visible_timeseries(input.secondary_timeseries_data);
+
+    let y_axis = calculate_y_axis(&input, &visible_timeseries_data);
+    let secondary_y_axis = (!input.secondary_timeseries_data.is_empty())
+        .then(|| calculate_y_axis(&secondary_input, &visible_secondary_timeseries_data));
+
+    // The buckets of both Y axes determine the width of the bars, so that the
+    // bars of all timeseries fit next to one another.
+    let all_visible_timeseries_data: Vec<_> = visible_timeseries_data
         .iter()
-        .filter(|timeseries| timeseries.visible)
+        .chain(&visible_secondary_timeseries_data)
         .cloned()
         .collect();
-
-    let buckets = create_metric_buckets(&visible_timeseries_data, |min_max, value| {
-        min_max
-            .map(|min_max: MinMax| min_max.extend_with_value(value))
-            .unwrap_or_else(|| MinMax::from_value(value))
-    });
+    let buckets = create_metric_buckets(&all_visible_timeseries_data, |_, _| ());
 
     let mut x_axis = get_x_axis_from_time_range(&input.time_range);
-    let mut y_axis = calculate_y_axis_range(&buckets, identity);
 
-    for &value in input.additional_values {
-        y_axis = y_axis.extend_with_value(value);
-    }
-
-    let num_shape_lists = visible_timeseries_data.len();
+    let num_shape_lists = all_visible_timeseries_data.len();
 
     let interval = calculate_smallest_time_interval(&buckets);
     if let Some(interval) = interval {
@@ -35,39 +35,93 @@ pub(crate) fn generate_bar_chart_from_timeseries<'source>(
     }
 
     let bar_width = calculate_bar_width(&x_axis, interval, num_shape_lists);
-    let bar_args = BarArgs {
-        bar_width,
-        num_shape_lists,
-        x_axis: &x_axis,
-        y_axis: &y_axis,
-    };
-
-    let shape_lists: Vec<_> = input
-        .timeseries_data
+
+    let mut shape_lists = create_shape_lists(
+        input.timeseries_data,
+        &visible_timeseries_data,
+        0,
+        YAxis::Primary,
+        &BarArgs {
+            bar_width,
+            num_shape_lists,
+            x_axis: &x_axis,
+            y_axis: &y_axis,
+        },
+    );
+    if let Some(secondary_y_axis) = &secondary_y_axis {
+        shape_lists.extend(create_shape_lists(
+            input.secondary_timeseries_data,
+            &visible_secondary_timeseries_data,
+            visible_timeseries_data.len(),
+            YAxis::Secondary,
+            &BarArgs {
+                bar_width,
+                num_shape_lists,
+                x_axis: &x_axis,
+                y_axis: secondary_y_axis,
+            },
+        ));
+    }
+
+    MondrianChart {
+        shape_lists,
+        x_axis,
+        y_axis,
+        secondary_y_axis,
+    }
+}
+
+fn get_visible_timeseries<'source>(
+    timeseries_data: &[&'source Timeseries],
+) -> Vec<&'source Timeseries> {
+    timeseries_data
+        .iter()
+        .filter(|timeseries| timeseries.visible)
+        .cloned()
+        .collect()
+}
+
+fn calculate_y_axis(input: &TimeseriesSourceData, visible_timeseries_data: &[&Timeseries]) -> Axis {
+    let buckets = create_metric_buckets(visible_timeseries_data, |min_max, value| {
+        min_max
+            .map(|min_max: MinMax| min_max.extend_with_value(value))
+            .unwrap_or_else(|| MinMax::from_value(value))
+    });
+
+    calculate_y_axis_range_with_scale(input, &buckets)
+}
+
+/// Creates the shape lists for the given timeseries, of which the visible
+/// ones are assigned consecutive bar indices, starting at `first_bar_index`.
+fn create_shape_lists<'source>(
+    timeseries_data: &[&'source Timeseries],
+    visible_timeseries_data: &[&'source Timeseries],
+    first_bar_index: usize,
+    y_axis: YAxis,
+    bar_args: &BarArgs,
+) -> Vec<ShapeList<&'source Timeseries, &'source Metric>> {
+    timeseries_data
         .iter()
         .map(|timeseries| ShapeList {
             shapes: if timeseries.visible {
-                let bar_index = visible_timeseries_data
-                    .iter()
-                    .position(|visible_timeseries| visible_timeseries == timeseries)
-                    .unwrap_or_default();
+                let bar_index = first_bar_index
+                    + visible_timeseries_data
+                        .iter()
+                        .position(|visible_timeseries| visible_timeseries == timeseries)
+                        .unwrap_or_default();
                 timeseries
                     .metrics
                     .iter()
-                    .filter_map(|metric| create_bar_shape(metric, bar_index, &bar_args))
+                    .filter_map(|metric| create_bar_shape(metric, bar_index, bar_args))
                     .collect()
             } else {
                 Vec::new()
             },
             source: *timeseries,
+            y_axis,
+            label: None,
         })
-        .collect();
-
-    MondrianChart {
-        shape_lists,
-        x_axis,
-        y_axis,
-    }
+        .collect()
 }
 
 struct BarArgs<'axes> {
@@ -91,7 +145,8 @@ fn create_bar_shape<'source>(
         x: calculate_bar_x(group_x, args.bar_width, bar_index, args.num_shape_lists),
         width: args.bar_width,
         y: 0.,
-        height: normalize_along_linear_axis(metric.value, args.y_axis),
+        height: args.y_axis.normalize(metric.value),
+        intensity: None,
         source: metric,
     }))
 }
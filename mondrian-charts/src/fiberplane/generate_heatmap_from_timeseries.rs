@@ -0,0 +1,190 @@
+use super::utils::*;
+use crate::fiberplane::{Metric, Timeseries, TimeseriesSourceData, Timestamp};
+use crate::types::{Axis, AxisScale, MondrianChart, Rectangle, Shape, ShapeList, YAxis};
+use std::collections::BTreeMap;
+
+/// Label that contains the upper bound of a histogram bucket.
+const BUCKET_LABEL: &str = "le";
+
+/// Generates a heatmap from timeseries that represent the buckets of a single
+/// histogram.
+///
+/// Every bucket is expected to have an `le` label with its (inclusive) upper
+/// bound, while its values are the cumulative number of observations, as is
+/// customary for Prometheus histograms. Timeseries without a valid `le` label
+/// are not rendered. Observations in the `+Inf` bucket are not rendered either,
+/// since that bucket has no upper bound to display, and neither is a `-Inf`
+/// bucket, since it has no height.
+///
+/// Every bucket results in a shape list with a rectangle for every timestamp,
+/// shaded by the number of observations relative to the maximum number of
+/// observations in any of the rectangles.
+///
+/// The `graph_type` and `stacking_type` of the input are ignored, and so are
+/// any secondary timeseries.
+pub fn generate_heatmap_from_timeseries<'source>(
+    input: TimeseriesSourceData<'source, '_, '_>,
+) -> MondrianChart<&'source Timeseries, &'source Metric> {
+    let mut histogram_buckets: Vec<_> = input
+        .timeseries_data
+        .iter()
+        .filter(|timeseries| timeseries.visible)
+        .filter_map(|timeseries| {
+            let upper_bound = timeseries.labels.get(BUCKET_LABEL)?.parse::<f64>().ok()?;
+            // The `+Inf` bucket is the only non-finite bucket that is kept,
+            // since it sorts last and so does not shift the indices of the
+            // buckets with a finite upper bound.
+            (!upper_bound.is_nan() && upper_bound != f64::NEG_INFINITY)
+                .then_some((upper_bound, *timeseries))
+        })
+        .collect();
+    histogram_buckets.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    let upper_bounds: Vec<f64> = histogram_buckets
+        .iter()
+        .map(|(upper_bound, _)| *upper_bound)
+        .filter(|upper_bound| upper_bound.is_finite())
+        .collect();
+    let y_axis = get_y_axis_for_upper_bounds(&upper_bounds, input.y_axis_scale);
+
+    let cells = get_cells(&histogram_buckets);
+    let max_count = cells
+        .values()
+        .flatten()
+        .flatten()
+        .map(|cell| cell.count)
+        .fold(0., f64::max);
+
+    let mut x_axis = get_x_axis_from_time_range(&input.time_range);
+    let interval = calculate_smallest_time_interval(&cells);
+    if let Some(interval) = interval {
+        x_axis = x_axis.extend_with_interval(interval);
+        attach_suggestions_to_x_axis(&mut x_axis, &cells, interval);
+    }
+    let cell_width = interval
+        .map(|interval| interval / (x_axis.max_value - x_axis.min_value))
+        .unwrap_or(1.);
+
+    let shape_lists = input
+        .timeseries_data
+        .iter()
+        .map(|timeseries| {
+            let bucket_index = histogram_buckets
+                .iter()
+                .position(|(_, bucket)| bucket == timeseries);
+            let shapes = match bucket_index {
+                Some(index) if index < upper_bounds.len() => {
+                    let lower_bound = match index {
+                        0 => y_axis.min_value,
+                        _ => upper_bounds[index - 1],
+                    };
+                    let y = y_axis.normalize(lower_bound);
+                    let height = y_axis.normalize(upper_bounds[index]) - y;
+
+                    cells
+                        .iter()
+                        .filter_map(|(time, row)| {
+                            let cell = row[index].as_ref()?;
+                            (cell.count > 0.).then(|| {
+                                Shape::Rectangle(Rectangle {
+                                    x: normalize_along_linear_axis(
+                                        get_time_from_timestamp(*time),
+                                        &x_axis,
+                                    ) - 0.5 * cell_width,
+                                    y,
+                                    width: cell_width,
+                                    height,
+                                    intensity: Some(cell.count / max_count),
+                                    source: cell.metric,
+                                })
+                            })
+                        })
+                        .collect()
+                }
+                _ => Vec::new(),
+            };
+
+            ShapeList {
+                shapes,
+                source: *timeseries,
+                y_axis: YAxis::Primary,
+                label: None,
+            }
+        })
+        .collect();
+
+    MondrianChart {
+        shape_lists,
+        x_axis,
+        y_axis,
+        secondary_y_axis: None,
+    }
+}
+
+/// A single cell in the heatmap, for a given bucket at a given timestamp.
+struct HeatmapCell<'source> {
+    /// Number of observations inside the bucket, excluding the observations
+    /// of the buckets below it.
+    count: f64,
+
+    /// The metric with the cumulative number of observations.
+    metric: &'source Metric,
+}
+
+/// Returns the cells of the heatmap per timestamp, with an entry for every
+/// histogram bucket, in the same order as the given buckets.
+fn get_cells<'source>(
+    histogram_buckets: &[(f64, &'source Timeseries)],
+) -> BTreeMap<Timestamp, Vec<Option<HeatmapCell<'source>>>> {
+    let mut cumulative_counts = BTreeMap::new();
+    for (index, (_, timeseries)) in histogram_buckets.iter().enumerate() {
+        for metric in &timeseries.metrics {
+            if !metric.value.is_nan() {
+                cumulative_counts
+                    .entry(metric.time)
+                    .or_insert_with(|| vec![None; histogram_buckets.len()])[index] = Some(metric);
+            }
+        }
+    }
+
+    cumulative_counts
+        .into_iter()
+        .map(|(time, metrics)| {
+            let mut previous_count = 0.;
+            let row = metrics
+                .into_iter()
+                .map(|metric| {
+                    metric.map(|metric: &Metric| {
+                        // Buckets are not guaranteed to be scraped at the
+                        // exact same moment, so we never allow negative counts.
+                        let count = (metric.value - previous_count).max(0.);
+                        previous_count = metric.value;
+                        HeatmapCell { count, metric }
+                    })
+                })
+                .collect();
+            (time, row)
+        })
+        .collect()
+}
+
+/// Returns the Y axis for displaying buckets with the given (finite) upper
+/// bounds.
+fn get_y_axis_for_upper_bounds(upper_bounds: &[f64], scale: AxisScale) -> Axis {
+    match scale {
+        AxisScale::Linear => {
+            let min_value = upper_bounds.first().map_or(0., |&first| first.min(0.));
+            let max_value = upper_bounds.last().map_or(1., |&last| last);
+            Axis {
+                min_value,
+                max_value: if max_value > min_value {
+                    max_value
+                } else {
+                    min_value + 1.
+                },
+                ..Default::default()
+            }
+        }
+        AxisScale::Log => calculate_log_y_axis_range(upper_bounds.iter().cloned()),
+    }
+}
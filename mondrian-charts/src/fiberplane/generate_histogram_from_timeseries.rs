@@ -0,0 +1,140 @@
+use super::utils::*;
+use crate::fiberplane::{HistogramBin, HistogramSourceData, Timeseries};
+use crate::types::{Axis, AxisScale, MondrianChart, Rectangle, Shape, ShapeList, YAxis};
+
+/// Generates a histogram that shows the distribution of the values inside the
+/// given timeseries.
+///
+/// The X axis displays the range of values, which is divided into bins of
+/// equal width. The Y axis displays the number of values inside every bin. Every
+/// visible timeseries results in a shape list with a bar for every non-empty
+/// bin, so that the distributions of multiple timeseries can be compared.
+pub fn generate_histogram_from_timeseries<'source>(
+    input: HistogramSourceData<'source, '_>,
+) -> MondrianChart<&'source Timeseries, HistogramBin> {
+    let visible_timeseries_data: Vec<_> = input
+        .timeseries_data
+        .iter()
+        .filter(|timeseries| timeseries.visible)
+        .cloned()
+        .collect();
+
+    let values = || {
+        visible_timeseries_data
+            .iter()
+            .flat_map(|timeseries| timeseries.metrics.iter().map(|metric| metric.value))
+            .filter(|value| value.is_finite())
+    };
+
+    let (min_value, max_value) = values()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+            (min.min(value), max.max(value))
+        });
+    let (min_value, max_value) = if min_value > max_value {
+        (0., 1.)
+    } else if min_value == max_value {
+        (min_value - 0.5, max_value + 0.5)
+    } else {
+        (min_value, max_value)
+    };
+
+    let num_bins = input
+        .num_bins
+        .unwrap_or_else(|| get_default_num_bins(values().count()))
+        .max(1);
+    let bin_width = (max_value - min_value) / num_bins as f64;
+
+    let counts: Vec<Vec<usize>> = visible_timeseries_data
+        .iter()
+        .map(|timeseries| {
+            let mut counts = vec![0; num_bins];
+            for metric in &timeseries.metrics {
+                if metric.value.is_finite() {
+                    let bin = ((metric.value - min_value) / bin_width) as usize;
+                    counts[bin.min(num_bins - 1)] += 1;
+                }
+            }
+            counts
+        })
+        .collect();
+
+    let x_axis = Axis {
+        min_value,
+        max_value,
+        tick_suggestions: Some(
+            (0..=num_bins)
+                .map(|bin| min_value + bin as f64 * bin_width)
+                .collect(),
+        ),
+        ..Default::default()
+    };
+
+    let max_count = counts.iter().flatten().cloned().max().unwrap_or_default();
+    let y_axis = match input.y_axis_scale {
+        AxisScale::Linear => Axis {
+            min_value: 0.,
+            max_value: max_count.max(1) as f64,
+            ..Default::default()
+        },
+        AxisScale::Log => {
+            calculate_log_y_axis_range(counts.iter().flatten().map(|&count| count as f64))
+        }
+    };
+
+    let num_shape_lists = visible_timeseries_data.len();
+    let bar_width = calculate_bar_width(&x_axis, Some(bin_width), num_shape_lists);
+
+    let shape_lists = input
+        .timeseries_data
+        .iter()
+        .map(|timeseries| ShapeList {
+            shapes: match visible_timeseries_data
+                .iter()
+                .position(|visible_timeseries| visible_timeseries == timeseries)
+            {
+                Some(bar_index) => counts[bar_index]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &count)| count > 0)
+                    .map(|(bin, &count)| {
+                        let bin = HistogramBin {
+                            min_value: min_value + bin as f64 * bin_width,
+                            max_value: min_value + (bin + 1) as f64 * bin_width,
+                            count,
+                        };
+                        let group_x = x_axis.normalize(0.5 * (bin.min_value + bin.max_value));
+                        Shape::Rectangle(Rectangle {
+                            x: calculate_bar_x(group_x, bar_width, bar_index, num_shape_lists),
+                            y: 0.,
+                            width: bar_width,
+                            height: y_axis.normalize(count as f64),
+                            intensity: None,
+                            source: bin,
+                        })
+                    })
+                    .collect(),
+                None => Vec::new(),
+            },
+            source: *timeseries,
+            y_axis: YAxis::Primary,
+            label: None,
+        })
+        .collect();
+
+    MondrianChart {
+        shape_lists,
+        x_axis,
+        y_axis,
+        secondary_y_axis: None,
+    }
+}
+
+/// Returns the number of bins to use for the given number of values, using
+/// Sturges' rule.
+fn get_default_num_bins(num_values: usize) -> usize {
+    if num_values == 0 {
+        1
+    } else {
+        (num_values as f64).log2().ceil() as usize + 1
+    }
+}
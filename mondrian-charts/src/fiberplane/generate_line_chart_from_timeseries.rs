@@ -1,7 +1,6 @@
 use super::utils::*;
 use crate::fiberplane::{Metric, MinMax, Timeseries, TimeseriesSourceData};
-use crate::types::{Axis, Line, MondrianChart, Point, Shape, ShapeList};
-use std::convert::identity;
+use crate::types::{Axis, Line, MondrianChart, Point, Shape, ShapeList, YAxis};
 
 pub(crate) fn generate_line_chart_from_timeseries<'source>(
     input: TimeseriesSourceData<'source, '_, '_>,
@@ -13,11 +12,7 @@ pub(crate) fn generate_line_chart_from_timeseries<'source>(
     });
 
     let mut x_axis = get_x_axis_from_time_range(&input.time_range);
-    let mut y_axis = calculate_y_axis_range(&buckets, identity);
-
-    for &value in input.additional_values {
-        y_axis = y_axis.extend_with_value(value);
-    }
+    let y_axis = calculate_y_axis_range_with_scale(&input, &buckets);
 
     let interval = calculate_smallest_time_interval(&buckets);
     if let Some(interval) = interval {
@@ -34,6 +29,8 @@ pub(crate) fn generate_line_chart_from_timeseries<'source>(
                 Vec::new()
             },
             source: *timeseries,
+            y_axis: YAxis::Primary,
+            label: None,
         })
         .collect();
 
@@ -41,6 +38,7 @@ pub(crate) fn generate_line_chart_from_timeseries<'source>(
         shape_lists,
         x_axis,
         y_axis,
+        secondary_y_axis: None,
     }
 }
 
@@ -94,7 +92,7 @@ fn create_point_for_metric<'source>(
 
     Point {
         x: normalize_along_linear_axis(time, x_axis),
-        y: normalize_along_linear_axis(metric.value, y_axis),
+        y: y_axis.normalize(metric.value),
         source: metric,
     }
 }
@@ -0,0 +1,61 @@
+use super::utils::*;
+use crate::fiberplane::{Metric, MinMax, Timeseries, TimeseriesSourceData};
+use crate::types::{MondrianChart, Point, Shape, ShapeList, YAxis};
+
+/// Generates a scatter chart, which renders every metric as an individual
+/// point.
+///
+/// The `graph_type` and `stacking_type` of the input are ignored, and so are
+/// any secondary timeseries.
+pub fn generate_scatter_chart_from_timeseries<'source>(
+    input: TimeseriesSourceData<'source, '_, '_>,
+) -> MondrianChart<&'source Timeseries, &'source Metric> {
+    let buckets = create_metric_buckets(input.timeseries_data, |min_max, value| {
+        min_max
+            .map(|min_max: MinMax| min_max.extend_with_value(value))
+            .unwrap_or_else(|| MinMax::from_value(value))
+    });
+
+    let mut x_axis = get_x_axis_from_time_range(&input.time_range);
+    let y_axis = calculate_y_axis_range_with_scale(&input, &buckets);
+
+    if let Some(interval) = calculate_smallest_time_interval(&buckets) {
+        attach_suggestions_to_x_axis(&mut x_axis, &buckets, interval);
+    }
+
+    let shape_lists: Vec<_> = input
+        .timeseries_data
+        .iter()
+        .map(|timeseries| ShapeList {
+            shapes: if timeseries.visible {
+                timeseries
+                    .metrics
+                    .iter()
+                    .filter(|metric| !metric.value.is_nan())
+                    .map(|metric| {
+                        Shape::Point(Point {
+                            x: normalize_along_linear_axis(
+                                get_time_from_timestamp(metric.time),
+                                &x_axis,
+                            ),
+                            y: y_axis.normalize(metric.value),
+                            source: metric,
+                        })
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            },
+            source: *timeseries,
+            y_axis: YAxis::Primary,
+            label: None,
+        })
+        .collect();
+
+    MondrianChart {
+        shape_lists,
+        x_axis,
+        y_axis,
+        secondary_y_axis: None,
+    }
+}
@@ -0,0 +1,55 @@
+use super::constants::ANNOTATION_INTENSITY;
+use super::utils::*;
+use crate::fiberplane::{Annotation, PointSource, SeriesSource};
+use crate::types::{Axis, Rectangle, Shape, ShapeList, YAxis};
+
+/// Generates a shape list with a single shape for the given annotation.
+///
+/// Thresholds are rendered as horizontal lines, while bands and time spans are
+/// rendered as shaded rectangles that span the full height or width of the
+/// chart, respectively.
+pub(crate) fn generate_shape_list_from_annotation<'source>(
+    x_axis: &Axis,
+    y_axis: &Axis,
+    annotation: &'source Annotation,
+) -> ShapeList<SeriesSource<'source>, PointSource<'source>> {
+    let shape = match annotation {
+        Annotation::Threshold { value, .. } => {
+            Shape::Line(create_horizontal_line(y_axis, *value, PointSource::None))
+        }
+        Annotation::Band {
+            min_value,
+            max_value,
+            ..
+        } => {
+            let y = y_axis.normalize(min_value.min(*max_value));
+            Shape::Rectangle(Rectangle {
+                x: 0.,
+                y,
+                width: 1.,
+                height: y_axis.normalize(min_value.max(*max_value)) - y,
+                intensity: Some(ANNOTATION_INTENSITY),
+                source: PointSource::None,
+            })
+        }
+        Annotation::TimeSpan { time_range, .. } => {
+            let x = normalize_along_linear_axis(get_time_from_timestamp(time_range.from), x_axis);
+            Shape::Rectangle(Rectangle {
+                x,
+                y: 0.,
+                width: normalize_along_linear_axis(get_time_from_timestamp(time_range.to), x_axis)
+                    - x,
+                height: 1.,
+                intensity: Some(ANNOTATION_INTENSITY),
+                source: PointSource::None,
+            })
+        }
+    };
+
+    ShapeList {
+        shapes: vec![shape],
+        source: SeriesSource::Annotation(annotation),
+        y_axis: YAxis::Primary,
+        label: annotation.label().map(str::to_owned),
+    }
+}
@@ -1,7 +1,12 @@
+use super::constants::ANNOTATION_INTENSITY;
 use super::utils::*;
 use crate::fiberplane::{PointSource, ProviderEvent, SeriesSource};
-use crate::types::{Area, AreaPoint, Axis, Shape, ShapeList};
+use crate::types::{Area, AreaPoint, Axis, Rectangle, Shape, ShapeList, YAxis};
 
+/// Generates a shape list with a shape for every event.
+///
+/// Events with an `end_time` are rendered as shaded spans, while other events
+/// are rendered as markers at the time they occurred.
 pub(crate) fn generate_shape_list_from_events<'source>(
     x_axis: &Axis,
     events: &[&'source ProviderEvent],
@@ -10,17 +15,33 @@ pub(crate) fn generate_shape_list_from_events<'source>(
         shapes: events
             .iter()
             .map(|event| {
-                Shape::Area(Area {
-                    area_gradient_shown: None,
-                    points: vec![AreaPoint {
-                        x: normalize_along_linear_axis(get_time_from_timestamp(event.time), x_axis),
-                        y_min: 0.,
-                        y_max: 1.,
+                let x = normalize_along_linear_axis(get_time_from_timestamp(event.time), x_axis);
+                match event.end_time {
+                    Some(end_time) => Shape::Rectangle(Rectangle {
+                        x,
+                        y: 0.,
+                        width: normalize_along_linear_axis(
+                            get_time_from_timestamp(end_time),
+                            x_axis,
+                        ) - x,
+                        height: 1.,
+                        intensity: Some(ANNOTATION_INTENSITY),
                         source: PointSource::Event(event),
-                    }],
-                })
+                    }),
+                    None => Shape::Area(Area {
+                        area_gradient_shown: None,
+                        points: vec![AreaPoint {
+                            x,
+                            y_min: 0.,
+                            y_max: 1.,
+                            source: PointSource::Event(event),
+                        }],
+                    }),
+                }
             })
             .collect(),
         source: SeriesSource::Events,
+        y_axis: YAxis::Primary,
+        label: None,
     }
 }
@@ -1,7 +1,10 @@
+use super::constants::BAR_PLUS_PADDING;
 use super::utils::*;
 use crate::fiberplane::{Metric, StackedChartBuckets, Timeseries, TimeseriesSourceData};
-use crate::types::{Axis, MondrianChart, Rectangle, Shape, ShapeList};
+use crate::types::{Axis, MondrianChart, Rectangle, Shape, ShapeList, YAxis};
 
+/// Generates a stacked bar chart, in which the stacks of the secondary
+/// timeseries, if any, are placed next to those of the primary timeseries.
 pub(crate) fn generate_stacked_bar_chart_from_timeseries<'source>(
     input: TimeseriesSourceData<'source, '_, '_>,
 ) -> MondrianChart<&'source Timeseries, &'source Metric> {
@@ -16,48 +19,100 @@ pub(crate) fn generate_stacked_bar_chart_from_timeseries<'source>(
         y_axis = y_axis.extend_with_value(value);
     }
 
-    let interval = calculate_smallest_time_interval(&buckets);
+    let secondary_input = input.secondary();
+    let mut secondary_buckets_and_axes = (!input.secondary_timeseries_data.is_empty())
+        .then(|| calculate_buckets_and_axes_for_stacked_chart(&secondary_input));
+
+    // The buckets of both Y axes determine the width of the bars, so that the
+    // stacks of both axes fit next to one another.
+    let all_timeseries_data: Vec<_> = input
+        .timeseries_data
+        .iter()
+        .chain(input.secondary_timeseries_data)
+        .cloned()
+        .collect();
+    let all_buckets = create_metric_buckets(&all_timeseries_data, |_, _| ());
+
+    let interval = calculate_smallest_time_interval(&all_buckets);
     if let Some(interval) = interval {
         x_axis = x_axis.extend_with_interval(interval);
-        attach_suggestions_to_x_axis(&mut x_axis, &buckets, interval);
+        attach_suggestions_to_x_axis(&mut x_axis, &all_buckets, interval);
     }
 
-    let mut bar_args = BarArgs {
-        bar_width: calculate_bar_width(&x_axis, interval, 1),
-        buckets: &mut buckets,
-        is_percentage,
-        x_axis: &x_axis,
-        y_axis: &y_axis,
+    let num_stacks = if secondary_buckets_and_axes.is_some() {
+        2
+    } else {
+        1
     };
+    let bar_width = calculate_bar_width(&x_axis, interval, num_stacks);
 
-    let shape_lists: Vec<_> = input
-        .timeseries_data
+    let mut shape_lists = create_shape_lists(
+        input.timeseries_data,
+        YAxis::Primary,
+        &mut BarArgs {
+            bar_width,
+            buckets: &mut buckets,
+            is_percentage,
+            stack_index: 0,
+            num_stacks,
+            x_axis: &x_axis,
+            y_axis: &y_axis,
+        },
+    );
+    if let Some(secondary) = &mut secondary_buckets_and_axes {
+        shape_lists.extend(create_shape_lists(
+            input.secondary_timeseries_data,
+            YAxis::Secondary,
+            &mut BarArgs {
+                bar_width,
+                buckets: &mut secondary.buckets,
+                is_percentage,
+                stack_index: 1,
+                num_stacks,
+                x_axis: &x_axis,
+                y_axis: &secondary.y_axis,
+            },
+        ));
+    }
+
+    MondrianChart {
+        shape_lists,
+        x_axis,
+        y_axis,
+        secondary_y_axis: secondary_buckets_and_axes.map(|secondary| secondary.y_axis),
+    }
+}
+
+fn create_shape_lists<'source>(
+    timeseries_data: &[&'source Timeseries],
+    y_axis: YAxis,
+    bar_args: &mut BarArgs,
+) -> Vec<ShapeList<&'source Timeseries, &'source Metric>> {
+    timeseries_data
         .iter()
         .map(|timeseries| ShapeList {
             shapes: if timeseries.visible {
                 timeseries
                     .metrics
                     .iter()
-                    .filter_map(|metric| create_bar_shape(metric, &mut bar_args))
+                    .filter_map(|metric| create_bar_shape(metric, bar_args))
                     .collect()
             } else {
                 Vec::new()
             },
             source: *timeseries,
+            y_axis,
+            label: None,
         })
-        .collect();
-
-    MondrianChart {
-        shape_lists,
-        x_axis,
-        y_axis,
-    }
+        .collect()
 }
 
 struct BarArgs<'axes, 'buckets> {
     bar_width: f64,
     buckets: &'buckets mut StackedChartBuckets,
     is_percentage: bool,
+    stack_index: usize,
+    num_stacks: usize,
     x_axis: &'axes Axis,
     y_axis: &'axes Axis,
 }
@@ -79,7 +134,11 @@ fn create_bar_shape<'source>(
         metric.value
     };
 
-    let x = normalize_along_linear_axis(time, args.x_axis) - 0.5 * args.bar_width;
+    // Stacks are centered around the time of their bucket.
+    let stack_offset = args.stack_index as f64 - 0.5 * (args.num_stacks - 1) as f64;
+    let x = normalize_along_linear_axis(time, args.x_axis)
+        + stack_offset * args.bar_width * BAR_PLUS_PADDING
+        - 0.5 * args.bar_width;
     let y = bucket_value.current_y;
 
     let height = normalize_along_linear_axis(value, args.y_axis);
@@ -90,6 +149,7 @@ fn create_bar_shape<'source>(
         y,
         width: args.bar_width,
         height,
+        intensity: None,
         source: metric,
     }))
 }
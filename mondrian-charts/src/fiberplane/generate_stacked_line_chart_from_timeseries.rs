@@ -1,6 +1,6 @@
 use super::utils::*;
 use crate::fiberplane::{Metric, StackedChartBuckets, Timeseries, TimeseriesSourceData};
-use crate::types::{Area, AreaPoint, Axis, MondrianChart, Shape, ShapeList};
+use crate::types::{Area, AreaPoint, Axis, MondrianChart, Shape, ShapeList, YAxis};
 
 pub(crate) fn generate_stacked_line_chart_from_timeseries<'source>(
     input: TimeseriesSourceData<'source, '_, '_>,
@@ -38,6 +38,8 @@ pub(crate) fn generate_stacked_line_chart_from_timeseries<'source>(
                 Vec::new()
             },
             source: *timeseries,
+            y_axis: YAxis::Primary,
+            label: None,
         })
         .collect();
 
@@ -45,6 +47,7 @@ pub(crate) fn generate_stacked_line_chart_from_timeseries<'source>(
         shape_lists,
         x_axis,
         y_axis,
+        secondary_y_axis: None,
     }
 }
 
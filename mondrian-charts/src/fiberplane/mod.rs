@@ -1,6 +1,11 @@
 mod constants;
+mod data_attributes;
 mod generate_bar_chart_from_timeseries;
+mod generate_heatmap_from_timeseries;
+mod generate_histogram_from_timeseries;
 mod generate_line_chart_from_timeseries;
+mod generate_scatter_chart_from_timeseries;
+mod generate_shape_list_from_annotation;
 mod generate_shape_list_from_events;
 mod generate_shape_list_from_target_latency;
 mod generate_stacked_bar_chart_from_timeseries;
@@ -11,19 +16,24 @@ mod utils;
 #[cfg(test)]
 mod tests;
 
+pub use data_attributes::*;
 use generate_bar_chart_from_timeseries::generate_bar_chart_from_timeseries;
+pub use generate_heatmap_from_timeseries::generate_heatmap_from_timeseries;
+pub use generate_histogram_from_timeseries::generate_histogram_from_timeseries;
 use generate_line_chart_from_timeseries::generate_line_chart_from_timeseries;
+pub use generate_scatter_chart_from_timeseries::generate_scatter_chart_from_timeseries;
+use generate_shape_list_from_annotation::generate_shape_list_from_annotation;
 use generate_shape_list_from_events::generate_shape_list_from_events;
 use generate_stacked_bar_chart_from_timeseries::generate_stacked_bar_chart_from_timeseries;
 use generate_stacked_line_chart_from_timeseries::generate_stacked_line_chart_from_timeseries;
 pub use types::*;
 
-use crate::MondrianChart;
+use crate::{MondrianChart, ShapeList, YAxis};
 
 use self::generate_shape_list_from_target_latency::generate_shape_list_from_target_latency;
 
-/// Generates an abstract chart from a combination of timeseries data, events
-/// and an optional target latency.
+/// Generates an abstract chart from a combination of timeseries data, events,
+/// annotations and an optional target latency.
 ///
 /// May return `None` if the graph type is unrecognized.
 pub fn generate<'source>(
@@ -33,17 +43,34 @@ pub fn generate<'source>(
         graph_type,
         stacking_type,
         timeseries_data,
+        secondary_timeseries_data,
         events,
+        annotations,
         target_latency,
         time_range,
+        y_axis_scale,
     } = input;
 
+    let additional_values: Vec<f64> = target_latency
+        .into_iter()
+        .chain(
+            annotations
+                .iter()
+                .filter_map(|annotation| match annotation {
+                    Annotation::Threshold { value, .. } => Some(*value),
+                    _ => None,
+                }),
+        )
+        .collect();
+
     let chart = generate_from_timeseries(TimeseriesSourceData {
         graph_type,
         stacking_type,
         timeseries_data,
+        secondary_timeseries_data,
         time_range,
-        additional_values: &target_latency.iter().cloned().collect::<Vec<_>>(),
+        additional_values: &additional_values,
+        y_axis_scale,
     })?;
 
     let mut chart: MondrianChart<SeriesSource, PointSource> = chart.into();
@@ -63,20 +90,73 @@ pub fn generate<'source>(
             ));
     }
 
+    for annotation in annotations {
+        chart.shape_lists.push(generate_shape_list_from_annotation(
+            &chart.x_axis,
+            &chart.y_axis,
+            annotation,
+        ));
+    }
+
     Some(chart)
 }
 
 /// Generates an abstract chart from the given timeseries data.
 ///
+/// If secondary timeseries are given, they are plotted along a secondary Y
+/// axis, and their shape lists are appended after those of the primary
+/// timeseries. In bar charts, the bars of the secondary timeseries are placed
+/// next to those of the primary timeseries.
+///
+/// To generate a scatter chart, a heatmap or a histogram, use
+/// [`generate_scatter_chart_from_timeseries()`],
+/// [`generate_heatmap_from_timeseries()`] or
+/// [`generate_histogram_from_timeseries()`] instead.
+///
 /// May return `None` if the graph type is unrecognized.
 pub fn generate_from_timeseries<'source>(
     input: TimeseriesSourceData<'source, '_, '_>,
+) -> Option<MondrianChart<&'source Timeseries, &'source Metric>> {
+    // Bar charts share the X axis and the bar positions between both Y axes,
+    // so they plot the secondary timeseries themselves.
+    match (input.graph_type, input.stacking_type) {
+        (GraphType::Bar, StackingType::None) => {
+            return Some(generate_bar_chart_from_timeseries(input))
+        }
+        (GraphType::Bar, _) => return Some(generate_stacked_bar_chart_from_timeseries(input)),
+        (_, _) => {}
+    }
+
+    if input.secondary_timeseries_data.is_empty() {
+        return generate_along_single_y_axis(input);
+    }
+
+    let secondary_chart = generate_along_single_y_axis(input.secondary())?;
+
+    let mut chart = generate_along_single_y_axis(TimeseriesSourceData {
+        secondary_timeseries_data: &[],
+        ..input
+    })?;
+    chart.shape_lists.extend(
+        secondary_chart
+            .shape_lists
+            .into_iter()
+            .map(|shape_list| ShapeList {
+                y_axis: YAxis::Secondary,
+                ..shape_list
+            }),
+    );
+    chart.secondary_y_axis = Some(secondary_chart.y_axis);
+
+    Some(chart)
+}
+
+fn generate_along_single_y_axis<'source>(
+    input: TimeseriesSourceData<'source, '_, '_>,
 ) -> Option<MondrianChart<&'source Timeseries, &'source Metric>> {
     let abstract_chart = match (input.graph_type, input.stacking_type) {
         (GraphType::Line, StackingType::None) => generate_line_chart_from_timeseries(input),
         (GraphType::Line, _) => generate_stacked_line_chart_from_timeseries(input),
-        (GraphType::Bar, StackingType::None) => generate_bar_chart_from_timeseries(input),
-        (GraphType::Bar, _) => generate_stacked_bar_chart_from_timeseries(input),
         (_, _) => return None,
     };
 
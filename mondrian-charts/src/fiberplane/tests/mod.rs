@@ -1,6 +1,17 @@
+mod test_chart_to_interactive_svg;
+#[cfg(feature = "pdf")]
+mod test_chart_to_pdf;
+mod test_chart_to_svg_with_downsampling;
+mod test_chart_to_svg_with_labels_and_legend;
 mod test_generate_bar_chart_from_timeseries;
+mod test_generate_chart_with_annotations;
 mod test_generate_combined_chart;
+mod test_generate_dual_axis_chart;
+mod test_generate_heatmap_from_timeseries;
+mod test_generate_histogram_from_timeseries;
 mod test_generate_line_chart_from_timeseries;
+mod test_generate_line_chart_with_log_axis;
+mod test_generate_scatter_chart_from_timeseries;
 mod test_generate_shape_list_from_events;
 mod test_generate_stacked_bar_chart_from_timeseries;
 mod test_generate_stacked_line_chart_from_timeseries;
@@ -0,0 +1,79 @@
+use crate::chart_to_svg::*;
+use crate::fiberplane::{
+    generate, CombinedSourceData, GraphType, Metric, ProviderEvent, StackingType, TimeRange,
+    Timeseries, Timestamp,
+};
+use crate::AxisScale;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+#[test]
+fn test_chart_to_interactive_svg() {
+    let events = [ProviderEvent::builder()
+        .time(get_date_at_minute(2))
+        .title("deploy <1>")
+        .build()];
+
+    let timeseries = Timeseries::builder()
+        .name("requests")
+        .labels(BTreeMap::from([(
+            "service.name".to_owned(),
+            "api".to_owned(),
+        )]))
+        .metrics(vec![
+            get_metric_at_minute(0, 10.),
+            get_metric_at_minute(4, 25.),
+            get_metric_at_minute(8, 15.),
+        ])
+        .visible(true)
+        .build();
+
+    let chart = generate(CombinedSourceData {
+        graph_type: GraphType::Line,
+        stacking_type: StackingType::None,
+        time_range: TimeRange {
+            from: get_date_at_minute(0),
+            to: get_date_at_minute(10),
+        },
+        timeseries_data: &[&timeseries],
+        secondary_timeseries_data: &[],
+        events: &events.iter().collect::<Vec<_>>(),
+        annotations: &[],
+        target_latency: None,
+        y_axis_scale: AxisScale::Linear,
+    })
+    .unwrap();
+
+    let svg = chart_to_interactive_svg(
+        &chart,
+        &ChartOptions {
+            get_shape_list_color: &|_, _| "#c00eae",
+            ..Default::default()
+        },
+        &Interactivity {
+            get_shape_list_attributes: &|source, _| source.data_attributes(),
+            get_point_attributes: &|source| source.data_attributes(),
+            get_point_title: &|series, source| source.title(series),
+            crosshair_shown: true,
+        },
+    );
+
+    let svg_lines = svg
+        .replace('>', ">\n")
+        .split('\n')
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    insta::assert_debug_snapshot!(svg_lines);
+}
+
+fn get_metric_at_minute(minute: u8, value: f64) -> Metric {
+    Metric::builder()
+        .time(get_date_at_minute(minute))
+        .value(value)
+        .build()
+}
+
+fn get_date_at_minute(minute: u8) -> Timestamp {
+    Timestamp::from_str(&format!("2023-07-18T16:{minute:02}:00.000Z")).unwrap()
+}
@@ -0,0 +1,74 @@
+use crate::chart_to_svg::*;
+use crate::fiberplane::{
+    generate_line_chart_from_timeseries, GraphType, Metric, StackingType, TimeRange, Timeseries,
+    TimeseriesSourceData, Timestamp,
+};
+use crate::{charts_to_pdf, fontdb, AxisScale, PdfOptions, PdfRenderingError};
+use std::str::FromStr;
+
+#[test]
+fn test_charts_to_pdf() {
+    let timeseries = Timeseries::builder()
+        .name("dummy_data")
+        .metrics(vec![
+            get_metric_at_minute(0, 10.),
+            get_metric_at_minute(5, 30.),
+            get_metric_at_minute(9, 10.),
+        ])
+        .visible(true)
+        .build();
+
+    let chart = generate_line_chart_from_timeseries(TimeseriesSourceData {
+        graph_type: GraphType::Line,
+        stacking_type: StackingType::None,
+        time_range: TimeRange {
+            from: get_date_at_minute(0),
+            to: get_date_at_minute(10),
+        },
+        timeseries_data: &[&timeseries],
+        secondary_timeseries_data: &[],
+        additional_values: &[],
+        y_axis_scale: AxisScale::Linear,
+    });
+
+    let chart_options = ChartOptions {
+        width: 320,
+        height: 240,
+        axis_lines_shown: true,
+        grid_rows_shown: true,
+        get_shape_list_color: &|_, _| "#c00eae",
+        x_formatter: Some(FormatterKind::Time),
+        y_formatter: Some(FormatterKind::Scientific),
+        title: Some("Requests"),
+        ..Default::default()
+    };
+    let pdf_options = PdfOptions {
+        fonts: fontdb::Database::new(),
+        background_color: Some("#ffffff".to_owned()),
+    };
+
+    let charts = [(&chart, &chart_options); 3];
+    let pdf = charts_to_pdf(&charts, 2, &pdf_options).unwrap();
+    let pdf = String::from_utf8_lossy(&pdf);
+
+    assert!(pdf.starts_with("%PDF-"));
+    // Two columns and two rows of 320x240 charts, with 16pt spacing in between.
+    assert!(pdf.contains("/MediaBox [0 0 656 496]"));
+    assert_eq!(pdf.matches("/Subtype /Form").count(), 3);
+
+    assert!(matches!(
+        charts_to_pdf(&charts, 0, &pdf_options),
+        Err(PdfRenderingError::InvalidColumns)
+    ));
+}
+
+fn get_metric_at_minute(minute: u8, value: f64) -> Metric {
+    Metric::builder()
+        .time(get_date_at_minute(minute))
+        .value(value)
+        .build()
+}
+
+fn get_date_at_minute(minute: u8) -> Timestamp {
+    Timestamp::from_str(&format!("2023-07-18T16:{minute:02}:00.000Z")).unwrap()
+}
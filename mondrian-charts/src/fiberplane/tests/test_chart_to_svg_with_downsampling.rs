@@ -0,0 +1,74 @@
+use crate::chart_to_svg::*;
+use crate::fiberplane::{
+    generate_from_timeseries, GraphType, Metric, StackingType, TimeRange, Timeseries,
+    TimeseriesSourceData, Timestamp,
+};
+use crate::AxisScale;
+use std::str::FromStr;
+
+#[test]
+fn test_chart_to_svg_with_downsampling() {
+    // One metric per second for ten minutes, rendered into a chart that is
+    // only a few hundred pixels wide.
+    let timeseries = Timeseries::builder()
+        .name("dummy_data")
+        .metrics(
+            (0..600)
+                .map(|second| get_metric_at_second(second, (second % 7) as f64))
+                .collect(),
+        )
+        .visible(true)
+        .build();
+
+    for (graph_type, element) in [(GraphType::Line, "L"), (GraphType::Bar, "<rect")] {
+        let chart = generate_from_timeseries(TimeseriesSourceData {
+            graph_type,
+            stacking_type: StackingType::None,
+            time_range: TimeRange {
+                from: get_metric_at_second(0, 0.).time,
+                to: get_metric_at_second(600, 0.).time,
+            },
+            timeseries_data: &[&timeseries],
+            secondary_timeseries_data: &[],
+            additional_values: &[],
+            y_axis_scale: AxisScale::Linear,
+        })
+        .unwrap();
+
+        let render = |downsampling| {
+            chart_to_svg(
+                &chart,
+                &ChartOptions {
+                    width: 320,
+                    height: 240,
+                    downsampling,
+                    get_shape_list_color: &|_, _| "#c00eae",
+                    ..Default::default()
+                },
+            )
+        };
+
+        let plot_width = 320 - 76;
+        let full = render(None).matches(element).count();
+        let downsampled = render(Some(Downsampling::default()))
+            .matches(element)
+            .count();
+
+        assert!(
+            full > 2 * plot_width,
+            "{graph_type:?}: expected all shapes, got {full}"
+        );
+        assert!(
+            downsampled <= plot_width + 2,
+            "{graph_type:?}: expected at most one shape per pixel, got {downsampled}"
+        );
+    }
+}
+
+fn get_metric_at_second(second: u16, value: f64) -> Metric {
+    let time = Timestamp::from_str("2023-07-18T16:00:00.000Z").unwrap();
+    Metric::builder()
+        .time(time + time::Duration::seconds(second as i64))
+        .value(value)
+        .build()
+}
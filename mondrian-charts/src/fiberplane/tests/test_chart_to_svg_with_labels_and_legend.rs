@@ -0,0 +1,70 @@
+use crate::chart_to_svg::*;
+use crate::fiberplane::{
+    generate_line_chart_from_timeseries, GraphType, Metric, StackingType, TimeRange, Timeseries,
+    TimeseriesSourceData, Timestamp,
+};
+use crate::AxisScale;
+use std::str::FromStr;
+
+#[test]
+fn test_chart_to_svg_with_labels_and_legend() {
+    let timeseries = ["http_requests", "http_errors <5xx>", "database_queries"].map(|name| {
+        Timeseries::builder()
+            .name(name)
+            .metrics(vec![
+                get_metric_at_minute(0, 10.),
+                get_metric_at_minute(5, 30.),
+                get_metric_at_minute(9, 10.),
+            ])
+            .visible(true)
+            .build()
+    });
+
+    let chart = generate_line_chart_from_timeseries(TimeseriesSourceData {
+        graph_type: GraphType::Line,
+        stacking_type: StackingType::None,
+        time_range: TimeRange {
+            from: get_date_at_minute(0),
+            to: get_date_at_minute(10),
+        },
+        timeseries_data: &timeseries.iter().collect::<Vec<_>>(),
+        secondary_timeseries_data: &[],
+        additional_values: &[],
+        y_axis_scale: AxisScale::Linear,
+    });
+
+    let svg = chart_to_svg(
+        &chart,
+        &ChartOptions {
+            axis_lines_shown: true,
+            grid_rows_shown: true,
+            get_shape_list_color: &|_, index| ["#c00eae", "#4c7aff", "#63eaad"][index],
+            get_shape_list_name: Some(&|timeseries, _| timeseries.name.clone()),
+            x_formatter: Some(FormatterKind::Time),
+            y_formatter: Some(FormatterKind::Scientific),
+            title: Some("Requests & errors"),
+            x_axis_label: Some("Time"),
+            y_axis_label: Some("Requests per second"),
+            ..Default::default()
+        },
+    );
+
+    let svg_lines = svg
+        .replace('>', ">\n")
+        .split('\n')
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    insta::assert_debug_snapshot!(svg_lines);
+}
+
+fn get_metric_at_minute(minute: u8, value: f64) -> Metric {
+    Metric::builder()
+        .time(get_date_at_minute(minute))
+        .value(value)
+        .build()
+}
+
+fn get_date_at_minute(minute: u8) -> Timestamp {
+    Timestamp::from_str(&format!("2023-07-18T16:{minute:02}:00.000Z")).unwrap()
+}
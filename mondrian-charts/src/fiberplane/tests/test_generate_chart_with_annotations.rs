@@ -0,0 +1,115 @@
+use crate::chart_to_svg::*;
+use crate::fiberplane::{
+    generate, Annotation, CombinedSourceData, GraphType, Metric, ProviderEvent, SeriesSource,
+    StackingType, TimeRange, Timeseries, Timestamp,
+};
+use crate::AxisScale;
+use std::str::FromStr;
+
+#[test]
+fn test_generate_chart_with_annotations() {
+    let events = [
+        get_event_at_minute(2, "deploy"),
+        ProviderEvent::builder()
+            .time(get_date_at_minute(6))
+            .end_time(get_date_at_minute(7))
+            .title("incident")
+            .build(),
+    ];
+
+    let annotations = [
+        Annotation::Threshold {
+            value: 40.,
+            label: Some("Critical".to_owned()),
+        },
+        Annotation::Band {
+            min_value: 25.,
+            max_value: 40.,
+            label: Some("Warning".to_owned()),
+        },
+        Annotation::TimeSpan {
+            time_range: TimeRange {
+                from: get_date_at_minute(3),
+                to: get_date_at_minute(4),
+            },
+            label: Some("Maintenance".to_owned()),
+        },
+    ];
+
+    let timeseries = Timeseries::builder()
+        .name("dummy_data")
+        .metrics(vec![
+            get_metric_at_minute(0, 10.),
+            get_metric_at_minute(1, 15.),
+            get_metric_at_minute(2, 20.),
+            get_metric_at_minute(4, 25.),
+            get_metric_at_minute(5, 30.),
+            get_metric_at_minute(6, 25.),
+            get_metric_at_minute(7, 20.),
+            get_metric_at_minute(8, 15.),
+            get_metric_at_minute(9, 10.),
+        ])
+        .visible(true)
+        .build();
+
+    let chart = generate(CombinedSourceData {
+        graph_type: GraphType::Line,
+        stacking_type: StackingType::None,
+        time_range: TimeRange {
+            from: get_date_at_minute(0),
+            to: get_date_at_minute(10),
+        },
+        timeseries_data: &[&timeseries],
+        secondary_timeseries_data: &[],
+        events: &events.iter().collect::<Vec<_>>(),
+        annotations: &annotations.iter().collect::<Vec<_>>(),
+        target_latency: None,
+        y_axis_scale: AxisScale::Linear,
+    })
+    .unwrap();
+
+    insta::assert_yaml_snapshot!(chart);
+
+    let svg = chart_to_svg(
+        &chart,
+        &ChartOptions {
+            axis_lines_shown: true,
+            get_shape_list_color: &|source, _| match source {
+                SeriesSource::Timeseries(_) => "#c00eae",
+                SeriesSource::Events => "#4c7aff",
+                SeriesSource::Annotation(Annotation::Threshold { .. }) => "#e5383b",
+                SeriesSource::Annotation(Annotation::Band { .. }) => "#f7b32b",
+                _ => "#a4a4a4",
+            },
+            x_formatter: Some(FormatterKind::Time),
+            y_formatter: Some(FormatterKind::Duration),
+            ..Default::default()
+        },
+    );
+
+    let svg_lines = svg
+        .replace('>', ">\n")
+        .split('\n')
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    insta::assert_debug_snapshot!(svg_lines);
+}
+
+fn get_metric_at_minute(minute: u8, value: f64) -> Metric {
+    Metric::builder()
+        .time(get_date_at_minute(minute))
+        .value(value)
+        .build()
+}
+
+fn get_date_at_minute(minute: u8) -> Timestamp {
+    Timestamp::from_str(&format!("2023-07-18T16:{minute:02}:00.000Z")).unwrap()
+}
+
+fn get_event_at_minute(minute: u8, title: &str) -> ProviderEvent {
+    ProviderEvent::builder()
+        .time(get_date_at_minute(minute))
+        .title(title)
+        .build()
+}
@@ -3,6 +3,7 @@ use crate::fiberplane::{
     generate, CombinedSourceData, GraphType, Metric, ProviderEvent, SeriesSource, StackingType,
     TimeRange, Timeseries, Timestamp,
 };
+use crate::AxisScale;
 use std::str::FromStr;
 
 #[test]
@@ -38,8 +39,11 @@ fn test_generate_line_chart_from_timeseries() {
             to: get_date_at_minute(10),
         },
         timeseries_data: &[&timeseries],
+        secondary_timeseries_data: &[],
         events: &events.iter().collect::<Vec<_>>(),
+        annotations: &[],
         target_latency: Some(target_latency),
+        y_axis_scale: AxisScale::Linear,
     })
     .unwrap();
 
@@ -48,23 +52,17 @@ fn test_generate_line_chart_from_timeseries() {
     let svg = chart_to_svg(
         &chart,
         &ChartOptions {
-            width: 640,
-            height: 480,
             area_gradient_shown: true,
             axis_lines_shown: true,
-            grid_columns_shown: false,
-            grid_rows_shown: false,
-            grid_stroke_color: "#e7e7e7",
-            grid_stroke_dasharray: Default::default(),
-            shape_stroke_width: None,
             get_shape_list_color: &|source, _| match source {
                 SeriesSource::Timeseries(_) => "#c00eae",
                 SeriesSource::Events => "#4c7aff",
                 SeriesSource::TargetLatency => "#63eaad",
+                SeriesSource::Annotation(_) => "#a4a4a4",
             },
-            tick_color: "#a4a4a4",
             x_formatter: Some(FormatterKind::Time),
             y_formatter: Some(FormatterKind::Duration),
+            ..Default::default()
         },
     );
 
@@ -0,0 +1,171 @@
+use crate::chart_to_svg::*;
+use crate::fiberplane::{
+    generate_from_timeseries, GraphType, Metric, StackingType, TimeRange, Timeseries,
+    TimeseriesSourceData, Timestamp,
+};
+use crate::{AxisScale, Shape, ShapeList, YAxis};
+use std::str::FromStr;
+
+#[test]
+fn test_generate_dual_axis_chart() {
+    let latency = Timeseries::builder()
+        .name("latency")
+        .metrics(vec![
+            get_metric_at_minute(0, 0.1),
+            get_metric_at_minute(2, 0.15),
+            get_metric_at_minute(4, 0.3),
+            get_metric_at_minute(6, 0.25),
+            get_metric_at_minute(8, 0.2),
+        ])
+        .visible(true)
+        .build();
+
+    let requests = Timeseries::builder()
+        .name("requests")
+        .metrics(vec![
+            get_metric_at_minute(0, 1200.),
+            get_metric_at_minute(2, 1800.),
+            get_metric_at_minute(4, 4500.),
+            get_metric_at_minute(6, 3800.),
+            get_metric_at_minute(8, 2500.),
+        ])
+        .visible(true)
+        .build();
+
+    let chart = generate_from_timeseries(TimeseriesSourceData {
+        graph_type: GraphType::Line,
+        stacking_type: StackingType::None,
+        time_range: TimeRange {
+            from: get_date_at_minute(0),
+            to: get_date_at_minute(10),
+        },
+        timeseries_data: &[&latency],
+        secondary_timeseries_data: &[&requests],
+        additional_values: &[],
+        y_axis_scale: AxisScale::Linear,
+    })
+    .unwrap();
+
+    insta::assert_yaml_snapshot!(chart);
+
+    let svg = chart_to_svg(
+        &chart,
+        &ChartOptions {
+            axis_lines_shown: true,
+            grid_rows_shown: true,
+            get_shape_list_color: &|_, index| match index {
+                0 => "#c00eae",
+                _ => "#4c7aff",
+            },
+            x_formatter: Some(FormatterKind::Time),
+            y_formatter: Some(FormatterKind::Duration),
+            secondary_y_formatter: Some(FormatterKind::Scientific),
+            y_axis_label: Some("Latency"),
+            secondary_y_axis_label: Some("Requests"),
+            ..Default::default()
+        },
+    );
+
+    let svg_lines = svg
+        .replace('>', ">\n")
+        .split('\n')
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    insta::assert_debug_snapshot!(svg_lines);
+}
+
+#[test]
+fn test_generate_dual_axis_bar_chart() {
+    // The timeseries have different intervals, so the width of the bars is
+    // determined by the secondary timeseries.
+    let latency = Timeseries::builder()
+        .name("latency")
+        .metrics(
+            (0..5)
+                .map(|i| get_metric_at_minute(2 * i, 0.1 * (i + 1) as f64))
+                .collect(),
+        )
+        .visible(true)
+        .build();
+
+    let requests = Timeseries::builder()
+        .name("requests")
+        .metrics(
+            (0..10)
+                .map(|i| get_metric_at_minute(i, 1000. * (i + 1) as f64))
+                .collect(),
+        )
+        .visible(true)
+        .build();
+
+    for stacking_type in [StackingType::None, StackingType::Stacked] {
+        let chart = generate_from_timeseries(TimeseriesSourceData {
+            graph_type: GraphType::Bar,
+            stacking_type,
+            time_range: TimeRange {
+                from: get_date_at_minute(0),
+                to: get_date_at_minute(10),
+            },
+            timeseries_data: &[&latency],
+            secondary_timeseries_data: &[&requests],
+            additional_values: &[],
+            y_axis_scale: AxisScale::Linear,
+        })
+        .unwrap();
+
+        insta::assert_yaml_snapshot!(
+            format!("generate_dual_axis_bar_chart_{stacking_type:?}"),
+            chart
+        );
+
+        let [primary, secondary] = chart.shape_lists.as_slice() else {
+            panic!("expected two shape lists");
+        };
+        assert_eq!(primary.y_axis, YAxis::Primary);
+        assert_eq!(secondary.y_axis, YAxis::Secondary);
+        assert_eq!(primary.shapes.len(), 5);
+        assert_eq!(secondary.shapes.len(), 10);
+        assert!(chart.secondary_y_axis.is_some());
+
+        let bars = |shape_list: &ShapeList<_, _>| -> Vec<(f64, f64)> {
+            shape_list
+                .shapes
+                .iter()
+                .map(|shape| match shape {
+                    Shape::Rectangle(rectangle) => (rectangle.x, rectangle.x + rectangle.width),
+                    _ => panic!("expected only rectangles"),
+                })
+                .collect()
+        };
+
+        // All bars fit on the X axis, and the bars of the primary timeseries
+        // don't overlap with those of the secondary timeseries.
+        let secondary_bars = bars(secondary);
+        for (start, end) in bars(primary).into_iter().chain(secondary_bars.clone()) {
+            assert!(
+                start >= 0. && end <= 1.,
+                "bar outside of X axis: {start}..{end}"
+            );
+        }
+        for (start, end) in bars(primary) {
+            for &(secondary_start, secondary_end) in &secondary_bars {
+                assert!(
+                    end <= secondary_start || start >= secondary_end,
+                    "bars overlap: {start}..{end} and {secondary_start}..{secondary_end}"
+                );
+            }
+        }
+    }
+}
+
+fn get_metric_at_minute(minute: u8, value: f64) -> Metric {
+    Metric::builder()
+        .time(get_date_at_minute(minute))
+        .value(value)
+        .build()
+}
+
+fn get_date_at_minute(minute: u8) -> Timestamp {
+    Timestamp::from_str(&format!("2023-07-18T16:{minute:02}:00.000Z")).unwrap()
+}
@@ -0,0 +1,113 @@
+use crate::chart_to_svg::*;
+use crate::fiberplane::{
+    generate_heatmap_from_timeseries, GraphType, Metric, StackingType, TimeRange, Timeseries,
+    TimeseriesSourceData, Timestamp,
+};
+use crate::{AxisScale, MondrianChart};
+use std::str::FromStr;
+
+#[test]
+fn test_generate_heatmap_from_timeseries() {
+    // Cumulative counts per bucket, for minutes 0 through 2.
+    let timeseries = get_histogram_timeseries(&[
+        ("0.1", [2., 4., 0.]),
+        ("0.5", [5., 6., 1.]),
+        ("1", [5., 10., 3.]),
+        ("+Inf", [6., 10., 3.]),
+    ]);
+    let chart = generate_heatmap(&timeseries);
+
+    insta::assert_yaml_snapshot!(chart);
+
+    let svg = chart_to_svg(
+        &chart,
+        &ChartOptions {
+            axis_lines_shown: true,
+            get_shape_list_color: &|_, _| "#c00eae",
+            x_formatter: Some(FormatterKind::Time),
+            y_formatter: Some(FormatterKind::Duration),
+            ..Default::default()
+        },
+    );
+
+    let svg_lines = svg
+        .replace('>', ">\n")
+        .split('\n')
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    insta::assert_debug_snapshot!(svg_lines);
+}
+
+#[test]
+fn test_generate_heatmap_with_negative_infinity_bucket() {
+    let buckets = [
+        ("0.1", [2., 4., 0.]),
+        ("0.5", [5., 6., 1.]),
+        ("+Inf", [6., 10., 3.]),
+    ];
+    let timeseries = get_histogram_timeseries(&buckets);
+    let expected = generate_heatmap(&timeseries);
+
+    let timeseries_with_negative_infinity =
+        get_histogram_timeseries(&[&[("-Inf", [0., 0., 0.])], &buckets[..]].concat());
+    let chart = generate_heatmap(&timeseries_with_negative_infinity);
+
+    assert!(chart.shape_lists[0].shapes.is_empty());
+    let chart = serde_json::to_value(&chart).unwrap();
+    let expected = serde_json::to_value(&expected).unwrap();
+    for (shape_list, expected) in chart["shapeLists"].as_array().unwrap()[1..]
+        .iter()
+        .zip(expected["shapeLists"].as_array().unwrap())
+    {
+        assert_eq!(shape_list["shapes"], expected["shapes"]);
+    }
+    assert_eq!(chart["yAxis"], expected["yAxis"]);
+}
+
+fn get_histogram_timeseries(buckets: &[(&str, [f64; 3])]) -> Vec<Timeseries> {
+    buckets
+        .iter()
+        .map(|(le, counts)| {
+            Timeseries::builder()
+                .name("http_request_duration_seconds_bucket")
+                .labels([("le".to_owned(), le.to_string())])
+                .metrics(
+                    counts
+                        .iter()
+                        .enumerate()
+                        .map(|(minute, &count)| get_metric_at_minute(minute as u8, count))
+                        .collect::<Vec<_>>(),
+                )
+                .visible(true)
+                .build()
+        })
+        .collect()
+}
+
+fn generate_heatmap(timeseries: &[Timeseries]) -> MondrianChart<&Timeseries, &Metric> {
+    generate_heatmap_from_timeseries(TimeseriesSourceData {
+        // The graph type and stacking type are ignored by heatmaps
+        graph_type: GraphType::Line,
+        stacking_type: StackingType::None,
+        time_range: TimeRange {
+            from: get_date_at_minute(0),
+            to: get_date_at_minute(2),
+        },
+        timeseries_data: &timeseries.iter().collect::<Vec<_>>(),
+        secondary_timeseries_data: &[],
+        additional_values: &[],
+        y_axis_scale: AxisScale::Linear,
+    })
+}
+
+fn get_metric_at_minute(minute: u8, value: f64) -> Metric {
+    Metric::builder()
+        .time(get_date_at_minute(minute))
+        .value(value)
+        .build()
+}
+
+fn get_date_at_minute(minute: u8) -> Timestamp {
+    Timestamp::from_str(&format!("2023-07-18T16:{minute:02}:00.000Z")).unwrap()
+}
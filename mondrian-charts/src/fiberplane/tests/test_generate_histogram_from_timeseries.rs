@@ -0,0 +1,45 @@
+use crate::fiberplane::{
+    generate_histogram_from_timeseries, HistogramSourceData, Metric, Timeseries, Timestamp,
+};
+use crate::AxisScale;
+use std::str::FromStr;
+
+#[test]
+fn test_generate_histogram_from_timeseries() {
+    let get_timeseries = |name: &str, values: &[f64]| {
+        Timeseries::builder()
+            .name(name)
+            .metrics(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(minute, &value)| get_metric_at_minute(minute as u8, value))
+                    .collect::<Vec<_>>(),
+            )
+            .visible(true)
+            .build()
+    };
+    let timeseries = [
+        get_timeseries("api", &[1., 2., 2., 3., 3., 3., 8.]),
+        get_timeseries("worker", &[5., 6., 6., f64::NAN, 9.]),
+    ];
+
+    let chart = generate_histogram_from_timeseries(HistogramSourceData {
+        timeseries_data: &timeseries.iter().collect::<Vec<_>>(),
+        num_bins: Some(4),
+        y_axis_scale: AxisScale::Linear,
+    });
+
+    insta::assert_yaml_snapshot!(chart);
+}
+
+fn get_metric_at_minute(minute: u8, value: f64) -> Metric {
+    Metric::builder()
+        .time(get_date_at_minute(minute))
+        .value(value)
+        .build()
+}
+
+fn get_date_at_minute(minute: u8) -> Timestamp {
+    Timestamp::from_str(&format!("2023-07-18T16:{minute:02}:00.000Z")).unwrap()
+}
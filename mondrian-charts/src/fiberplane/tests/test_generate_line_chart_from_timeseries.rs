@@ -3,6 +3,7 @@ use crate::fiberplane::{
     generate_line_chart_from_timeseries, GraphType, Metric, StackingType, TimeRange, Timeseries,
     TimeseriesSourceData, Timestamp,
 };
+use crate::AxisScale;
 use std::str::FromStr;
 
 #[test]
@@ -31,7 +32,9 @@ fn test_generate_line_chart_from_timeseries() {
             to: get_date_at_minute(10),
         },
         timeseries_data: &[&timeseries],
+        secondary_timeseries_data: &[],
         additional_values: &[40.],
+        y_axis_scale: AxisScale::Linear,
     });
 
     insta::assert_yaml_snapshot!(chart);
@@ -39,19 +42,13 @@ fn test_generate_line_chart_from_timeseries() {
     let svg = chart_to_svg(
         &chart,
         &ChartOptions {
-            width: 640,
-            height: 480,
             area_gradient_shown: true,
             axis_lines_shown: true,
-            grid_columns_shown: false,
             grid_rows_shown: true,
-            grid_stroke_color: "#e7e7e7",
-            grid_stroke_dasharray: Default::default(),
-            shape_stroke_width: None,
             get_shape_list_color: &|_, _| "#c00eae",
-            tick_color: "#a4a4a4",
             x_formatter: Some(FormatterKind::Time),
             y_formatter: Some(FormatterKind::Duration),
+            ..Default::default()
         },
     );
 
@@ -0,0 +1,73 @@
+use crate::chart_to_svg::*;
+use crate::fiberplane::{
+    generate_line_chart_from_timeseries, GraphType, Metric, StackingType, TimeRange, Timeseries,
+    TimeseriesSourceData, Timestamp,
+};
+use crate::AxisScale;
+use std::str::FromStr;
+
+#[test]
+fn test_generate_line_chart_with_log_axis() {
+    let timeseries = Timeseries::builder()
+        .name("request_latency")
+        .metrics(vec![
+            get_metric_at_minute(0, 0.004),
+            get_metric_at_minute(1, 0.03),
+            get_metric_at_minute(2, 0.2),
+            get_metric_at_minute(3, 0.),
+            get_metric_at_minute(4, 1.5),
+            get_metric_at_minute(5, -1.),
+            get_metric_at_minute(6, 12.),
+            get_metric_at_minute(7, 0.9),
+            get_metric_at_minute(8, 0.05),
+            get_metric_at_minute(9, 0.006),
+        ])
+        .visible(true)
+        .build();
+
+    let chart = generate_line_chart_from_timeseries(TimeseriesSourceData {
+        graph_type: GraphType::Line,
+        stacking_type: StackingType::None,
+        time_range: TimeRange {
+            from: get_date_at_minute(0),
+            to: get_date_at_minute(10),
+        },
+        timeseries_data: &[&timeseries],
+        secondary_timeseries_data: &[],
+        additional_values: &[],
+        y_axis_scale: AxisScale::Log,
+    });
+
+    insta::assert_yaml_snapshot!(chart);
+
+    let svg = chart_to_svg(
+        &chart,
+        &ChartOptions {
+            axis_lines_shown: true,
+            grid_rows_shown: true,
+            get_shape_list_color: &|_, _| "#c00eae",
+            x_formatter: Some(FormatterKind::Time),
+            y_formatter: Some(FormatterKind::Duration),
+            ..Default::default()
+        },
+    );
+
+    let svg_lines = svg
+        .replace('>', ">\n")
+        .split('\n')
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    insta::assert_debug_snapshot!(svg_lines);
+}
+
+fn get_metric_at_minute(minute: u8, value: f64) -> Metric {
+    Metric::builder()
+        .time(get_date_at_minute(minute))
+        .value(value)
+        .build()
+}
+
+fn get_date_at_minute(minute: u8) -> Timestamp {
+    Timestamp::from_str(&format!("2023-07-18T16:{minute:02}:00.000Z")).unwrap()
+}
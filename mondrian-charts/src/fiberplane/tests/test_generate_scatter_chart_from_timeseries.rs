@@ -0,0 +1,47 @@
+use crate::fiberplane::{
+    generate_scatter_chart_from_timeseries, GraphType, Metric, StackingType, TimeRange, Timeseries,
+    TimeseriesSourceData, Timestamp,
+};
+use crate::AxisScale;
+use std::str::FromStr;
+
+#[test]
+fn test_generate_scatter_chart_from_timeseries() {
+    let timeseries = Timeseries::builder()
+        .name("request_duration")
+        .metrics(vec![
+            get_metric_at_minute(0, 0.12),
+            get_metric_at_minute(0, 0.45),
+            get_metric_at_minute(3, 0.3),
+            get_metric_at_minute(4, f64::NAN),
+            get_metric_at_minute(7, 0.9),
+        ])
+        .visible(true)
+        .build();
+
+    let chart = generate_scatter_chart_from_timeseries(TimeseriesSourceData {
+        graph_type: GraphType::Line,
+        stacking_type: StackingType::None,
+        time_range: TimeRange {
+            from: get_date_at_minute(0),
+            to: get_date_at_minute(10),
+        },
+        timeseries_data: &[&timeseries],
+        secondary_timeseries_data: &[],
+        additional_values: &[],
+        y_axis_scale: AxisScale::Linear,
+    });
+
+    insta::assert_yaml_snapshot!(chart);
+}
+
+fn get_metric_at_minute(minute: u8, value: f64) -> Metric {
+    Metric::builder()
+        .time(get_date_at_minute(minute))
+        .value(value)
+        .build()
+}
+
+fn get_date_at_minute(minute: u8) -> Timestamp {
+    Timestamp::from_str(&format!("2023-07-18T16:{minute:02}:00.000Z")).unwrap()
+}
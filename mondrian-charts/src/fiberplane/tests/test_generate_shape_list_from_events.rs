@@ -15,6 +15,7 @@ fn test_generate_shape_list_from_events() {
             min_value: get_time_from_timestamp(get_date_at_minute(0)),
             max_value: get_time_from_timestamp(get_date_at_minute(10)),
             tick_suggestions: None,
+            ..Default::default()
         },
         &events.iter().collect::<Vec<_>>(),
     );
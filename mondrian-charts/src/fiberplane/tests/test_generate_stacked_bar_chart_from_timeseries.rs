@@ -3,6 +3,7 @@ use crate::fiberplane::{
     generate_stacked_bar_chart_from_timeseries, GraphType, Metric, StackingType, TimeRange,
     Timeseries, TimeseriesSourceData, Timestamp,
 };
+use crate::AxisScale;
 use std::collections::BTreeMap;
 use std::str::FromStr;
 
@@ -51,7 +52,9 @@ fn test_generate_stacked_bar_chart_from_timeseries() {
             to: get_date_at_minute(10),
         },
         timeseries_data: &[&timeseries_a, &timeseries_b],
+        secondary_timeseries_data: &[],
         additional_values: &[40.],
+        y_axis_scale: AxisScale::Linear,
     });
 
     insta::assert_yaml_snapshot!(chart);
@@ -59,19 +62,13 @@ fn test_generate_stacked_bar_chart_from_timeseries() {
     let svg = chart_to_svg(
         &chart,
         &ChartOptions {
-            width: 640,
-            height: 480,
             area_gradient_shown: true,
             axis_lines_shown: true,
             grid_columns_shown: true,
-            grid_rows_shown: false,
-            grid_stroke_color: "#e7e7e7",
-            grid_stroke_dasharray: Default::default(),
-            shape_stroke_width: None,
             get_shape_list_color: &|_, index| if index % 2 == 0 { "#c00eae" } else { "#23304a" },
-            tick_color: "#a4a4a4",
             x_formatter: Some(FormatterKind::Time),
             y_formatter: Some(FormatterKind::Exponent),
+            ..Default::default()
         },
     );
 
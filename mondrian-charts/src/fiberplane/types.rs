@@ -2,7 +2,7 @@ use crate::types::*;
 pub use fiberplane_models::notebooks::{GraphType, StackingType};
 pub use fiberplane_models::providers::{Metric, ProviderEvent, Timeseries};
 pub use fiberplane_models::timestamps::{TimeRange, Timestamp};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
 /// All the data necessary to generate an abstract chart from an array of
@@ -20,6 +20,14 @@ pub struct TimeseriesSourceData<'source, 'slice, 'value> {
     /// may not see any results.
     pub timeseries_data: &'slice [&'source Timeseries],
 
+    /// Array of timeseries data to display along a secondary Y axis, on the
+    /// right-hand side of the chart.
+    ///
+    /// These timeseries are displayed using the same graph type, stacking type
+    /// and scale as the primary timeseries, but the range of their Y axis is
+    /// determined independently.
+    pub secondary_timeseries_data: &'slice [&'source Timeseries],
+
     /// The time range to be displayed.
     pub time_range: TimeRange,
 
@@ -27,6 +35,31 @@ pub struct TimeseriesSourceData<'source, 'slice, 'value> {
     /// part of any timeseries. These are not plotted, but are taken into
     /// account when deciding the range of the Y axis.
     pub additional_values: &'value [f64],
+
+    /// The scale to use for the Y axis.
+    ///
+    /// Stacked charts always use a linear Y axis, because stacked values
+    /// cannot be compared on a logarithmic scale.
+    pub y_axis_scale: AxisScale,
+}
+
+impl<'source, 'slice, 'value> TimeseriesSourceData<'source, 'slice, 'value> {
+    /// Returns the input for plotting the secondary timeseries along their own
+    /// Y axis.
+    ///
+    /// The additional values are only taken into account for the primary Y
+    /// axis.
+    pub(crate) fn secondary(&self) -> TimeseriesSourceData<'source, 'slice, 'value> {
+        TimeseriesSourceData {
+            graph_type: self.graph_type,
+            stacking_type: self.stacking_type,
+            timeseries_data: self.secondary_timeseries_data,
+            secondary_timeseries_data: &[],
+            time_range: self.time_range.clone(),
+            additional_values: &[],
+            y_axis_scale: self.y_axis_scale,
+        }
+    }
 }
 
 /// All the data necessary to generate an abstract chart from a combination of
@@ -48,17 +81,114 @@ pub struct CombinedSourceData<'source, 'slice> {
     /// may not see any results.
     pub timeseries_data: &'slice [&'source Timeseries],
 
+    /// Array of timeseries data to display along a secondary Y axis.
+    ///
+    /// See [TimeseriesSourceData::secondary_timeseries_data].
+    pub secondary_timeseries_data: &'slice [&'source Timeseries],
+
     /// Array of events to display in the chart.
     ///
+    /// Events with an `end_time` are displayed as shaded spans, while other
+    /// events are displayed as markers.
+    ///
     /// Note that events will not be displayed if the `graph_type` is anything
     /// other than [`GraphType::Line`].
     pub events: &'slice [&'source ProviderEvent],
 
+    /// Array of annotations to display on top of the chart.
+    pub annotations: &'slice [&'source Annotation],
+
     /// Optional target latency to display on the chart, in seconds.
     pub target_latency: Option<f64>,
 
     /// The time range to be displayed.
     pub time_range: TimeRange,
+
+    /// The scale to use for the Y axis.
+    ///
+    /// Stacked charts always use a linear Y axis, because stacked values
+    /// cannot be compared on a logarithmic scale.
+    pub y_axis_scale: AxisScale,
+}
+
+/// An annotation to display on top of a chart, such as a threshold or the
+/// duration of an incident.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Annotation {
+    /// A horizontal line at a given value along the Y axis.
+    ///
+    /// The value is taken into account when deciding the range of the Y axis.
+    Threshold {
+        value: f64,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+    },
+
+    /// A shaded band between two values along the Y axis, such as the warning
+    /// zone of an SLO.
+    ///
+    /// Bands do not affect the range of the Y axis, so they may be (partially)
+    /// outside of the visible area.
+    #[serde(rename_all = "camelCase")]
+    Band {
+        min_value: f64,
+        max_value: f64,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+    },
+
+    /// A shaded range in time, such as a deploy window.
+    #[serde(rename_all = "camelCase")]
+    TimeSpan {
+        time_range: TimeRange,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+    },
+}
+
+impl Annotation {
+    /// Returns the label to display alongside the annotation, if any.
+    pub fn label(&self) -> Option<&str> {
+        match self {
+            Self::Threshold { label, .. }
+            | Self::Band { label, .. }
+            | Self::TimeSpan { label, .. } => label.as_deref(),
+        }
+    }
+}
+
+/// All the data necessary to generate a histogram of the values inside an
+/// array of timeseries.
+pub struct HistogramSourceData<'source, 'slice> {
+    /// Array of timeseries data of which the values are counted.
+    pub timeseries_data: &'slice [&'source Timeseries],
+
+    /// The number of bins to divide the range of values into.
+    ///
+    /// If `None`, the number of bins is derived from the number of values.
+    pub num_bins: Option<usize>,
+
+    /// The scale to use for the Y axis, which displays the number of values
+    /// inside every bin.
+    pub y_axis_scale: AxisScale,
+}
+
+/// A bin inside a histogram, which is used as the source for its rectangles.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBin {
+    /// Lower bound of the bin (inclusive).
+    pub min_value: f64,
+
+    /// Upper bound of the bin (exclusive, unless this is the last bin).
+    pub max_value: f64,
+
+    /// Number of values inside the bin.
+    pub count: usize,
 }
 
 /// Source type for series in charts that contain combined data sources.
@@ -69,6 +199,7 @@ pub enum SeriesSource<'source> {
     Timeseries(&'source Timeseries),
     Events,
     TargetLatency,
+    Annotation(&'source Annotation),
 }
 
 impl<'source> From<&'source Timeseries> for SeriesSource<'source> {
@@ -78,7 +209,7 @@ impl<'source> From<&'source Timeseries> for SeriesSource<'source> {
 }
 
 /// Source type for points in charts that contain combined data sources.
-#[derive(Serialize)]
+#[derive(Clone, Copy, Serialize)]
 #[non_exhaustive]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum PointSource<'source> {
@@ -101,6 +232,7 @@ impl<'source> From<MondrianChart<&'source Timeseries, &'source Metric>>
             shape_lists: value.shape_lists.into_iter().map(ShapeList::into).collect(),
             x_axis: value.x_axis,
             y_axis: value.y_axis,
+            secondary_y_axis: value.secondary_y_axis,
         }
     }
 }
@@ -151,6 +283,8 @@ impl<'source> From<ShapeList<&'source Timeseries, &'source Metric>>
         Self {
             shapes: value.shapes.into_iter().map(Shape::into).collect(),
             source: value.source.into(),
+            y_axis: value.y_axis,
+            label: value.label,
         }
     }
 }
@@ -212,6 +346,7 @@ impl<'source> From<Rectangle<&'source Metric>> for Rectangle<PointSource<'source
             y: value.y,
             width: value.width,
             height: value.height,
+            intensity: value.intensity,
             source: value.source.into(),
         }
     }
@@ -64,6 +64,7 @@ mod tests {
             min_value: get_time_from_timestamp(get_date_at_minute_and_second(0, 23)),
             max_value: get_time_from_timestamp(get_date_at_minute_and_second(4, 23)),
             tick_suggestions: None,
+            ..Default::default()
         };
         attach_suggestions_to_x_axis(&mut axis, &buckets, 60.);
 
@@ -93,6 +94,7 @@ mod tests {
             min_value: get_time_from_timestamp(get_date_at_minute_and_second(0, 23)),
             max_value: get_time_from_timestamp(get_date_at_minute_and_second(4, 23)),
             tick_suggestions: None,
+            ..Default::default()
         };
         attach_suggestions_to_x_axis(&mut axis, &buckets, 60.);
 
@@ -0,0 +1,56 @@
+use crate::types::{Axis, AxisScale};
+
+/// Detects the range to display along a logarithmic Y axis by looking at all
+/// the given values.
+///
+/// The range is extended to the nearest powers of ten around the positive
+/// values. Zero and negative values cannot be displayed on a logarithmic axis,
+/// so they are ignored. If there are no positive values at all, the axis
+/// ranges from 1 to 10.
+pub(crate) fn calculate_log_y_axis_range(values: impl IntoIterator<Item = f64>) -> Axis {
+    let mut min_exponent: Option<f64> = None;
+    let mut max_exponent: Option<f64> = None;
+    for value in values {
+        if value > 0. && value.is_finite() {
+            let exponent = value.log10();
+            min_exponent = Some(min_exponent.map_or(exponent, |min| min.min(exponent)));
+            max_exponent = Some(max_exponent.map_or(exponent, |max| max.max(exponent)));
+        }
+    }
+
+    let min_exponent = min_exponent.unwrap_or(0.).floor();
+    let max_exponent = max_exponent.unwrap_or(1.).ceil().max(min_exponent + 1.);
+
+    Axis {
+        min_value: 10_f64.powf(min_exponent),
+        max_value: 10_f64.powf(max_exponent),
+        tick_suggestions: None,
+        scale: AxisScale::Log,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{calculate_log_y_axis_range, Axis, AxisScale};
+
+    #[test]
+    fn test_calculate_log_y_axis_range() {
+        let log_axis = |min_value, max_value| Axis {
+            min_value,
+            max_value,
+            tick_suggestions: None,
+            scale: AxisScale::Log,
+        };
+
+        assert_eq!(
+            calculate_log_y_axis_range([0.02, 3., 450.]),
+            log_axis(0.01, 1000.)
+        );
+        assert_eq!(calculate_log_y_axis_range([100.]), log_axis(100., 1000.));
+        assert_eq!(
+            calculate_log_y_axis_range([-5., 0., 20., f64::NAN]),
+            log_axis(10., 100.)
+        );
+        assert_eq!(calculate_log_y_axis_range([-5., 0.]), log_axis(1., 10.));
+    }
+}
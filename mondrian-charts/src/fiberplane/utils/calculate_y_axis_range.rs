@@ -40,6 +40,7 @@ pub(crate) fn calculate_y_axis_range<T: Clone>(
         min_value,
         max_value,
         tick_suggestions: None,
+        ..Default::default()
     }
 }
 
@@ -0,0 +1,34 @@
+use super::{calculate_log_y_axis_range, calculate_y_axis_range};
+use crate::fiberplane::{Buckets, MinMax, TimeseriesSourceData};
+use crate::types::{Axis, AxisScale};
+use std::convert::identity;
+
+/// Detects the range to display along the Y axis of a non-stacked chart,
+/// using the Y axis scale from the input.
+///
+/// For linear axes, the range is determined by the min-max values inside the
+/// buckets. For logarithmic axes, it is determined by the values of all visible
+/// timeseries. In both cases, the additional values from the input are taken
+/// into account as well.
+pub(crate) fn calculate_y_axis_range_with_scale(
+    input: &TimeseriesSourceData,
+    buckets: &Buckets<MinMax>,
+) -> Axis {
+    match input.y_axis_scale {
+        AxisScale::Linear => {
+            let mut y_axis = calculate_y_axis_range(buckets, identity);
+            for &value in input.additional_values {
+                y_axis = y_axis.extend_with_value(value);
+            }
+            y_axis
+        }
+        AxisScale::Log => calculate_log_y_axis_range(
+            input
+                .timeseries_data
+                .iter()
+                .filter(|timeseries| timeseries.visible)
+                .flat_map(|timeseries| timeseries.metrics.iter().map(|metric| metric.value))
+                .chain(input.additional_values.iter().cloned()),
+        ),
+    }
+}
@@ -0,0 +1,19 @@
+use crate::types::{Axis, Line, Point};
+
+/// Creates a line that spans the full width of the chart at the given value
+/// along the Y axis.
+pub(crate) fn create_horizontal_line<P: Clone>(y_axis: &Axis, value: f64, source: P) -> Line<P> {
+    let y = y_axis.normalize(value);
+
+    Line {
+        area_gradient_shown: Some(false),
+        points: vec![
+            Point {
+                x: 0.,
+                y,
+                source: source.clone(),
+            },
+            Point { x: 1., y, source },
+        ],
+    }
+}
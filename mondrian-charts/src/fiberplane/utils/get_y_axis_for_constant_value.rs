@@ -13,12 +13,14 @@ pub(super) fn get_y_axis_for_constant_value(value: f64) -> Axis {
                 min_value: 0.,
                 max_value: value + 1.,
                 tick_suggestions: None,
+                ..Default::default()
             }
         } else {
             Axis {
                 min_value: value - 1.,
                 max_value: 0.,
                 tick_suggestions: None,
+                ..Default::default()
             }
         }
     } else {
@@ -26,6 +28,7 @@ pub(super) fn get_y_axis_for_constant_value(value: f64) -> Axis {
             min_value: value - 1.,
             max_value: value + 1.,
             tick_suggestions: None,
+            ..Default::default()
         }
     };
 
@@ -52,7 +55,8 @@ mod tests {
             Axis {
                 min_value: -1.5,
                 max_value: 0.,
-                tick_suggestions: Some(vec![-1.5, -0.5, 0.])
+                tick_suggestions: Some(vec![-1.5, -0.5, 0.]),
+                ..Default::default()
             }
         );
 
@@ -61,7 +65,8 @@ mod tests {
             Axis {
                 min_value: 0.,
                 max_value: 1.,
-                tick_suggestions: Some(vec![0., 1.])
+                tick_suggestions: Some(vec![0., 1.]),
+                ..Default::default()
             }
         );
 
@@ -70,7 +75,8 @@ mod tests {
             Axis {
                 min_value: 0.,
                 max_value: 1.5,
-                tick_suggestions: Some(vec![0., 0.5, 1.5])
+                tick_suggestions: Some(vec![0., 0.5, 1.5]),
+                ..Default::default()
             }
         );
 
@@ -79,7 +85,8 @@ mod tests {
             Axis {
                 min_value: 0.,
                 max_value: 2.,
-                tick_suggestions: Some(vec![0., 1., 2.])
+                tick_suggestions: Some(vec![0., 1., 2.]),
+                ..Default::default()
             }
         );
 
@@ -88,7 +95,8 @@ mod tests {
             Axis {
                 min_value: 0.5,
                 max_value: 2.5,
-                tick_suggestions: Some(vec![0.5, 1.5, 2.5])
+                tick_suggestions: Some(vec![0.5, 1.5, 2.5]),
+                ..Default::default()
             }
         );
     }
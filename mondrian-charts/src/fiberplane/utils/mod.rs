@@ -2,9 +2,12 @@ mod attach_suggestions_to_x_axis;
 mod calculate_bar_width;
 mod calculate_bar_x;
 mod calculate_buckets_and_axes_for_stacked_chart;
+mod calculate_log_y_axis_range;
 mod calculate_smallest_time_interval;
 mod calculate_stacked_y_axis_range;
 mod calculate_y_axis_range;
+mod calculate_y_axis_range_with_scale;
+mod create_horizontal_line;
 mod create_metric_buckets;
 mod get_time_from_timestamp;
 mod get_x_axis_from_time_range;
@@ -16,9 +19,12 @@ pub(crate) use attach_suggestions_to_x_axis::*;
 pub(crate) use calculate_bar_width::*;
 pub(crate) use calculate_bar_x::*;
 pub(crate) use calculate_buckets_and_axes_for_stacked_chart::*;
+pub(crate) use calculate_log_y_axis_range::*;
 pub(crate) use calculate_smallest_time_interval::*;
 use calculate_stacked_y_axis_range::*;
 pub(crate) use calculate_y_axis_range::*;
+pub(crate) use calculate_y_axis_range_with_scale::*;
+pub(crate) use create_horizontal_line::*;
 pub(crate) use create_metric_buckets::*;
 pub(crate) use get_time_from_timestamp::*;
 pub(crate) use get_x_axis_from_time_range::*;
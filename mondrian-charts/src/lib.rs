@@ -26,6 +26,13 @@ X and Y axis of a chart are represented using the [`Axis`] type.
 To generate Mondrian charts, use the [`generate()`] or
 [`generate_from_timeseries()`] functions.
 
+Besides the line and bar charts that correspond to the graph types of
+Fiberplane graph cells, timeseries can be rendered as scatter charts using
+[`generate_scatter_chart_from_timeseries()`], or as heatmaps using
+[`generate_heatmap_from_timeseries()`] when the timeseries represent the
+buckets of a histogram. To show the distribution of the values inside
+timeseries, use [`generate_histogram_from_timeseries()`].
+
 Currently, this crate only contains generators to create charts from
 [Fiberplane data types](fiberplane_models), although we welcome contributions to
 generate charts from other types.
@@ -39,13 +46,31 @@ Charts can also be serialized to SVG using the [`chart_to_svg()`] function.
 Currently, all images are first converted to SVG before being rendered to a
 binary format, although this may change in the future.
 
+For printable reports, charts can be rendered to PDF documents without
+rasterizing them, using the `chart_to_pdf()` function. Multiple charts can be
+laid out in a grid on a single page using `charts_to_pdf()`. Both are only
+available with the `pdf` feature enabled.
+
+Besides the chart itself, the rendered output can include a title, labels for
+both axes and a legend with an item for every shape list. These are configured
+through the [`ChartOptions`], and the area in which the chart is plotted shrinks
+to make room for them.
+
+For large timeseries, [`ChartOptions::downsampling`] can be used to reduce the
+number of shapes that get rendered to roughly one per pixel of the chart width.
+
+To render an SVG that shows the values of data points when they are hovered,
+use [`chart_to_interactive_svg()`]. This only relies on inline CSS and `<title>`
+elements, so it works without any scripting.
+
 ## Features
 
-This crate offers the following feature flags, all of which are enabled by
-default:
+This crate offers the following feature flags, all of which except `pdf` are
+enabled by default:
 
 * **`fiberplane`**: Supports generating charts from Fiberplane data type.
 * **`image`**: Supports rendering charts to static images, such as PNGs.
+* **`pdf`**: Supports rendering charts to vector PDF documents.
 * **`svg`**: Supports serializing charts to SVG strings.
 
 */
@@ -58,6 +83,14 @@ mod chart_to_image;
 #[cfg(feature = "image")]
 pub use chart_to_image::*;
 
+#[cfg(feature = "pdf")]
+mod chart_to_pdf;
+#[cfg(feature = "pdf")]
+pub use chart_to_pdf::*;
+
+#[cfg(all(feature = "pdf", not(feature = "image")))]
+pub use usvg::fontdb;
+
 #[cfg(feature = "svg")]
 mod chart_to_svg;
 #[cfg(feature = "svg")]
@@ -17,6 +17,15 @@ use serde::{Deserialize, Serialize};
 pub struct MondrianChart<S, P> {
     pub x_axis: Axis,
     pub y_axis: Axis,
+
+    /// Optional secondary Y axis, typically rendered on the right-hand side of
+    /// the chart.
+    ///
+    /// Shape lists are only normalized along this axis if their `y_axis` is
+    /// set to [`YAxis::Secondary`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secondary_y_axis: Option<Axis>,
+
     pub shape_lists: Vec<ShapeList<S, P>>,
 }
 
@@ -35,9 +44,29 @@ pub struct Axis {
     ///
     /// Ticks are expressed as values between `minValue` and `maxValue`.
     pub tick_suggestions: Option<Vec<f64>>,
+
+    /// The scale used to distribute values along the axis.
+    #[serde(default, skip_serializing_if = "AxisScale::is_linear")]
+    pub scale: AxisScale,
 }
 
 impl Axis {
+    /// Normalizes an absolute value to a coordinate along the axis.
+    ///
+    /// Values within the range of the axis result in a coordinate between 0.0
+    /// and 1.0. Because zero and negative values cannot be displayed on a
+    /// logarithmic axis, they are clamped to the origin of such an axis.
+    pub fn normalize(&self, value: f64) -> f64 {
+        match self.scale {
+            AxisScale::Linear => (value - self.min_value) / (self.max_value - self.min_value),
+            AxisScale::Log if value <= 0. => 0.,
+            AxisScale::Log => {
+                let log_min = self.min_value.log10();
+                (value.log10() - log_min) / (self.max_value.log10() - log_min)
+            }
+        }
+    }
+
     /// Extends the range of the axis with the given interval.
     ///
     /// The range of the interval is divided among ends of the axis. This can be
@@ -67,6 +96,27 @@ impl Axis {
     }
 }
 
+/// The scale used to distribute values along an [`Axis`].
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AxisScale {
+    /// Values are distributed linearly between the min and max values.
+    #[default]
+    Linear,
+
+    /// Values are distributed logarithmically (base 10) between the min and
+    /// max values.
+    ///
+    /// Both the min and max values of a logarithmic axis must be positive.
+    Log,
+}
+
+impl AxisScale {
+    pub fn is_linear(&self) -> bool {
+        *self == Self::Linear
+    }
+}
+
 /// List of shapes that belongs together.
 ///
 /// These are usually rendered in the same color and would correspond to a
@@ -85,6 +135,32 @@ pub struct ShapeList<S, P> {
     /// This would be the type of series data the chart was generated from, such
     /// as [Timeseries](crate::fiberplane::Timeseries).
     pub source: S,
+
+    /// The Y axis along which the shapes are normalized.
+    #[serde(default, skip_serializing_if = "YAxis::is_primary")]
+    pub y_axis: YAxis,
+
+    /// Optional label to render inside the chart, alongside the shapes.
+    ///
+    /// This is used for annotations, such as thresholds, that are not
+    /// described by the legend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// Identifies one of the Y axes of a chart.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum YAxis {
+    #[default]
+    Primary,
+    Secondary,
+}
+
+impl YAxis {
+    pub fn is_primary(&self) -> bool {
+        *self == Self::Primary
+    }
 }
 
 /// An abstract shape used to visualize data points.
@@ -175,6 +251,14 @@ pub struct Rectangle<P> {
     /// Width of the rectangle, between 0.0 and 1.0.
     pub height: f64,
 
+    /// Optional intensity between 0.0 and 1.0, used for shading rectangles
+    /// based on their value, such as the cells in a heatmap.
+    ///
+    /// If set, the rectangle is filled using this opacity and rendered
+    /// without a stroke.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intensity: Option<f64>,
+
     /// The source this rectangle was generated from.
     ///
     /// This would be a [Metric](crate::fiberplane::Metric) if the chart was